@@ -1,13 +1,30 @@
 use std::collections::HashSet;
 
+use serde::Deserialize;
+
 use crate::{
     common::utils::Drawable,
-    entities::entity::{Entity, EntityStats},
-    map::map::Map,
+    entities::{
+        entity::{Entity, EntityStats},
+        status_effect::{StatusEffect, StatusEffectKind},
+    },
+    items::item::WeaponKind,
+    map::{
+        environment,
+        fluids::GasKind,
+        map::{Direction, Map},
+        tile::TerrainHit,
+    },
     menu::Logger,
 };
 
-#[derive(Clone, Copy, PartialEq)]
+use super::shapes::AreaShape;
+
+/// density of the cloud left by a spell on each tile of its area
+const GAS_CLOUD_DENSITY: u32 = 6;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ActionType {
     Physical,
     Fire,
@@ -16,12 +33,91 @@ pub enum ActionType {
     Healing,
 }
 
+impl ActionType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ActionType::Physical => "physical",
+            ActionType::Fire => "fire",
+            ActionType::Ice => "ice",
+            ActionType::Lightning => "lightning",
+            ActionType::Healing => "healing",
+        }
+    }
+
+    /// returns the lasting effect applied by a hit of this ActionType
+    pub fn status_effect(&self, damage: u32) -> Option<StatusEffect> {
+        match self {
+            ActionType::Fire => Some(StatusEffect::new(
+                StatusEffectKind::Burning,
+                3,
+                (damage / 10).max(1),
+            )),
+            ActionType::Ice => Some(StatusEffect::new(StatusEffectKind::Frozen, 4, 0)),
+            ActionType::Lightning => Some(StatusEffect::new(StatusEffectKind::Stunned, 1, 0)),
+            ActionType::Physical | ActionType::Healing => None,
+        }
+    }
+}
+
+/// damages or heals the target depending on the ActionType and returns the message to log
+pub fn hit(source: &Entity, target: &mut Entity, amount: u32, action_type: ActionType) -> String {
+    if action_type == ActionType::Healing {
+        let healed = target.heal(amount);
+        return format!(
+            "{} heals {} (+{} PV)",
+            source.symbol(),
+            target.symbol(),
+            healed
+        );
+    }
+
+    let target_was_alive = !target.is_dead();
+    let resistance = target.total_resistances().get(action_type);
+    let actual_damage = target.take_damage(amount, action_type);
+
+    // the element leaves a lasting effect on targets that survived the hit
+    let mut applied_effect = None;
+    if actual_damage > 0
+        && !target.is_dead()
+        && let Some(effect) = action_type.status_effect(actual_damage)
+        && !target.is_immune_to(effect.kind)
+        && target.status_effects.apply(effect)
+    {
+        applied_effect = Some(effect.kind);
+    }
+
+    format!(
+        "{} attacks {} with {} (-{} PV){}{}{}",
+        source.symbol(),
+        target.symbol(),
+        action_type.name(),
+        actual_damage,
+        if resistance > 0 {
+            ", resisted"
+        } else if resistance < 0 {
+            ", weak"
+        } else {
+            ""
+        },
+        if target_was_alive && target.is_dead() {
+            " and it died"
+        } else {
+            ""
+        },
+        match applied_effect {
+            Some(kind) => format!(", it is {}", kind.name()),
+            None => String::new(),
+        }
+    )
+}
+
 pub trait Action {
     fn affect(
         &self,
         source: &Entity,
         target_coordinates: (i32, i32, i32),
         other_entities: &mut [&mut Entity],
+        map: &mut Map,
         logger: &mut Logger,
     );
 
@@ -56,12 +152,18 @@ pub trait Action {
         0
     }
 
+    /// id of the item used up by each use, None by default
+    fn ammunition(&self) -> Option<&str> {
+        None
+    }
+
     /// only the target_pos by default
     fn get_area_of_effect(
         &self,
-        source_pos: (i32, i32, i32),
+        _source_pos: (i32, i32, i32),
+        _direction: Direction,
         target_pos: (i32, i32, i32),
-        map: &Map,
+        _map: &Map,
     ) -> HashSet<(i32, i32, i32)> {
         let mut targets = HashSet::new();
         targets.insert(target_pos);
@@ -85,7 +187,7 @@ impl MeleeAttack {
     }
 
     pub fn calculate_damage(&self, source: &Entity) -> u32 {
-        let mut damage = self.damage + source.stats.strength;
+        let mut damage = self.damage + source.strength();
         if let Some(weapon_data) = source.get_weapon_data() {
             damage += weapon_data.strenght;
         }
@@ -99,30 +201,49 @@ impl Action for MeleeAttack {
         source: &Entity,
         target_coordinates: (i32, i32, i32),
         other_entities: &mut [&mut Entity],
+        map: &mut Map,
         logger: &mut Logger,
     ) {
         if !self.can_reach(source, target_coordinates) {
             return;
         }
+        let damage = self.calculate_damage(source);
         if let Some(target) = other_entities
             .iter_mut()
             .find(|e| e.position == target_coordinates)
         {
-            let target_was_alive = !target.is_dead();
-            let damage = self.calculate_damage(source);
-            let actual_damage = target.take_damage(damage);
+            let message = hit(source, target, damage, self.attack_type);
+            logger.push_message(message);
+            return;
+        }
 
-            logger.push_message(format!(
-                "{} attacks {} (-{} PV){}",
+        // without a creature to hit, the attack damages the terrain
+        let tool = source
+            .get_weapon_data()
+            .map(|weapon_data| weapon_data.kind());
+        match map.hit_terrain(target_coordinates, damage, tool) {
+            Some(TerrainHit::NeedsTool { name, tool }) => logger.push_message(format!(
+                "{} needs an {} to cut the {}",
                 source.symbol(),
-                target.symbol(),
-                actual_damage,
-                if target_was_alive && target.is_dead() {
-                    " and it died"
-                } else {
-                    ""
+                tool.name(),
+                name
+            )),
+            Some(TerrainHit::Damaged {
+                name,
+                durability_left,
+            }) => logger.push_message(format!(
+                "{} hits the {} ({} left)",
+                source.symbol(),
+                name,
+                durability_left
+            )),
+            Some(TerrainHit::Destroyed { name, drops }) => {
+                logger.push_message(format!("{} destroys the {}", source.symbol(), name));
+                if !drops.is_empty() {
+                    logger.push_message(format!("it leaves {}", drops.join(", ")));
                 }
-            ));
+            }
+            Some(TerrainHit::Indestructible) | None => {}
         }
     }
 
@@ -139,9 +260,11 @@ pub struct AreaAttack {
     pub name: String,
     pub damage: u32,
     pub attack_range: u32,
-    pub area_radius: u32,
+    pub shape: AreaShape,
     pub attack_type: ActionType,
     pub mana_cost: u32,
+    /// cloud of gas left over the area
+    pub gas: Option<GasKind>,
 }
 
 impl AreaAttack {
@@ -149,23 +272,28 @@ impl AreaAttack {
         name: &str,
         damage: u32,
         range: u32,
-        radius: u32,
+        shape: AreaShape,
         attack_type: ActionType,
         mana_cost: u32,
+        gas: Option<GasKind>,
     ) -> Self {
         Self {
             damage,
             name: name.to_string(),
             attack_range: range,
-            area_radius: radius,
+            shape,
             attack_type,
             mana_cost,
+            gas,
         }
     }
 
+    /// spell power scales with the magic of the source, and a staff adds its strength
     pub fn calculate_damage(&self, source: &Entity) -> u32 {
-        let mut damage = self.damage + source.stats.strength;
-        if let Some(weapon_data) = source.get_weapon_data() {
+        let mut damage = self.damage + source.magic();
+        if let Some(weapon_data) = source.get_weapon_data()
+            && weapon_data.kind() == WeaponKind::Staff
+        {
             damage += weapon_data.strenght;
         }
         damage
@@ -178,49 +306,59 @@ impl Action for AreaAttack {
         source: &Entity,
         target_coordinates: (i32, i32, i32),
         other_entities: &mut [&mut Entity],
+        map: &mut Map,
         logger: &mut Logger,
     ) {
         if !self.can_reach(source, target_coordinates) {
             return;
         }
 
-        let area = self.get_area_of_effect(source.position, target_coordinates, &Map::default());
+        let area =
+            self.get_area_of_effect(source.position, source.direction, target_coordinates, map);
         let mut killed_count = 0;
         let mut affected_count = 0;
         let damage = self.calculate_damage(source);
 
+        // fire sets the ground on fire and ice freezes the water
+        for &position in area.iter() {
+            match self.attack_type {
+                ActionType::Fire => map.ignite(position),
+                ActionType::Ice => map.freeze(position),
+                _ => false,
+            };
+            if let Some(gas) = self.gas {
+                environment::release_gas(map, position, gas, GAS_CLOUD_DENSITY);
+            }
+        }
+
         // attacks all entities in the area
         for target in other_entities.iter_mut() {
             if area.contains(&target.position) {
                 let target_was_alive = !target.is_dead();
-                let actual_damage = target.take_damage(damage);
+                let message = hit(source, target, damage, self.attack_type);
 
                 affected_count += 1;
                 if target_was_alive && target.is_dead() {
                     killed_count += 1;
                 }
 
-                logger.push_message(format!(
-                    "{} attacks {} (-{} PV){}",
-                    source.symbol(),
-                    target.symbol(),
-                    actual_damage,
-                    if target_was_alive && target.is_dead() {
-                        " and it died"
-                    } else {
-                        ""
-                    }
-                ));
+                logger.push_message(message);
             }
         }
 
         if affected_count > 0 {
             logger.push_message(format!(
-                "{} attacks {} and hits {} entity{} (-{} PV){}",
+                "{} uses {} ({}) and hits {} entity{} ({}{} PV){}",
                 source.symbol(),
                 self.name,
+                self.attack_type.name(),
                 affected_count,
                 if affected_count > 1 { "s" } else { "" },
+                if self.attack_type == ActionType::Healing {
+                    "+"
+                } else {
+                    "-"
+                },
                 damage,
                 if killed_count > 0 {
                     format!(
@@ -235,7 +373,7 @@ impl Action for AreaAttack {
             ));
         } else {
             logger.push_message(format!(
-                "{} attacks {} but does not reach any entities",
+                "{} uses {} but does not reach any entities",
                 source.symbol(),
                 self.name
             ));
@@ -260,23 +398,11 @@ impl Action for AreaAttack {
 
     fn get_area_of_effect(
         &self,
-        _source_pos: (i32, i32, i32),
+        source_pos: (i32, i32, i32),
+        direction: Direction,
         target_pos: (i32, i32, i32),
-        _map: &Map,
+        map: &Map,
     ) -> HashSet<(i32, i32, i32)> {
-        let mut targets = HashSet::new();
-        let (tx, ty, tz) = target_pos;
-
-        for y in (ty - self.area_radius as i32)..=(ty + self.area_radius as i32) {
-            for x in (tx - self.area_radius as i32)..=(tx + self.area_radius as i32) {
-                let dx = x - tx;
-                let dy = y - ty;
-                if (dx * dx + dy * dy) <= (self.area_radius * self.area_radius) as i32 {
-                    targets.insert((x, y, tz));
-                }
-            }
-        }
-
-        targets
+        self.shape.cells(source_pos, direction, target_pos, map)
     }
 }
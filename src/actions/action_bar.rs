@@ -0,0 +1,85 @@
+/// number of hotkeys, bound to the keys 1 to 9
+pub const ACTION_BAR_SIZE: usize = 9;
+
+/// what a hotkey uses: one of the actions of the Entity or one of the spells of its Spellbook
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ActionSlot {
+    Action(usize),
+    Spell(usize),
+}
+
+/// hotkeys of an Entity
+pub struct ActionBar {
+    slots: [Option<ActionSlot>; ACTION_BAR_SIZE],
+    /// slot currently selected by the player
+    pub selected_slot: usize,
+    /// index of the action used when bumping into another Entity
+    pub default_action: usize,
+}
+
+impl ActionBar {
+    /// assigns the actions then the spells to the first slots, the first action being the default one
+    pub fn new(action_count: usize, spell_count: usize) -> Self {
+        let mut slots = [None; ACTION_BAR_SIZE];
+        let entries = (0..action_count)
+            .map(ActionSlot::Action)
+            .chain((0..spell_count).map(ActionSlot::Spell));
+        for (slot, entry) in slots.iter_mut().zip(entries) {
+            *slot = Some(entry);
+        }
+        Self {
+            slots,
+            selected_slot: 0,
+            default_action: 0,
+        }
+    }
+
+    pub fn get(&self, slot: usize) -> Option<ActionSlot> {
+        self.slots.get(slot).copied().flatten()
+    }
+
+    pub fn assign(&mut self, slot: usize, entry: Option<ActionSlot>) {
+        if let Some(current) = self.slots.get_mut(slot) {
+            *current = entry;
+        }
+    }
+
+    /// puts the entry in the first empty slot, if any
+    pub fn assign_to_empty_slot(&mut self, entry: ActionSlot) {
+        if let Some(slot) = self.slots.iter_mut().find(|slot| slot.is_none()) {
+            *slot = Some(entry);
+        }
+    }
+
+    pub fn select(&mut self, slot: usize) {
+        if slot < ACTION_BAR_SIZE {
+            self.selected_slot = slot;
+        }
+    }
+
+    pub fn selected_action(&self) -> Option<ActionSlot> {
+        self.get(self.selected_slot)
+    }
+
+    /// assigns the next action or spell to the selected slot, going through an empty slot after the last spell
+    pub fn cycle_selected_slot(&mut self, action_count: usize, spell_count: usize) {
+        let next = match self.selected_action() {
+            None if action_count > 0 => Some(ActionSlot::Action(0)),
+            None if spell_count > 0 => Some(ActionSlot::Spell(0)),
+            None => None,
+            Some(ActionSlot::Action(index)) if index + 1 < action_count => {
+                Some(ActionSlot::Action(index + 1))
+            }
+            Some(ActionSlot::Action(_)) if spell_count > 0 => Some(ActionSlot::Spell(0)),
+            Some(ActionSlot::Spell(index)) if index + 1 < spell_count => {
+                Some(ActionSlot::Spell(index + 1))
+            }
+            Some(_) => None,
+        };
+        self.assign(self.selected_slot, next);
+    }
+
+    pub fn slots(&self) -> impl Iterator<Item = (usize, Option<ActionSlot>)> + '_ {
+        self.slots.iter().copied().enumerate()
+    }
+}
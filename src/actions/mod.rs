@@ -1 +1,6 @@
 pub mod action;
+pub mod action_bar;
+pub mod projectile;
+pub mod resistances;
+pub mod shapes;
+pub mod spell;
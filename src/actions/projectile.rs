@@ -0,0 +1,266 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::{Position, Rect},
+    style::{Color, Style},
+};
+use serde::Deserialize;
+
+use crate::{
+    common::utils::{Drawable, bresenham_line},
+    data::items::items,
+    entities::entity::Entity,
+    items::item::WeaponKind,
+    map::map::Map,
+    menu::Logger,
+    systems::camera::Camera,
+};
+
+use super::action::{Action, ActionType, hit};
+
+/// id of the item thrown by a Thrown projectile, defined in data/items.toml
+pub const THROWN_ITEM: &str = "stone";
+
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectileKind {
+    Arrow,
+    Firebolt,
+    Thrown,
+}
+
+impl ProjectileKind {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            ProjectileKind::Arrow => "/",
+            ProjectileKind::Firebolt => "*",
+            ProjectileKind::Thrown => "o",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            ProjectileKind::Arrow => Color::Rgb(200, 170, 120),
+            ProjectileKind::Firebolt => Color::Rgb(255, 120, 0),
+            ProjectileKind::Thrown => Color::Rgb(160, 160, 160),
+        }
+    }
+}
+
+/// the flight of a projectile, traced when it is launched; its effect is resolved on impact
+pub struct Projectile {
+    pub kind: ProjectileKind,
+    /// tiles crossed after leaving the source, the last one being the impact
+    path: Vec<(i32, i32, i32)>,
+}
+
+impl Projectile {
+    /// follows the line from the source to the target and stops at the first tile blocking projectiles, at the first living entity or once out of range
+    pub fn trace(
+        kind: ProjectileKind,
+        source: (i32, i32, i32),
+        target: (i32, i32, i32),
+        range: u32,
+        map: &Map,
+        other_entities: &[&mut Entity],
+    ) -> Self {
+        let mut path = Vec::new();
+        let line = bresenham_line(source.0, source.1, target.0, target.1);
+        for &(x, y) in line.iter().skip(1).take(range as usize) {
+            let position = (x, y, source.2);
+            path.push(position);
+
+            let blocked = map
+                .get_tile(position)
+                .is_none_or(|tile| tile.blocks_projectiles());
+            let hits_entity = other_entities
+                .iter()
+                .any(|e| e.position == position && !e.is_dead());
+            if blocked || hits_entity {
+                break;
+            }
+        }
+        Self { kind, path }
+    }
+
+    /// returns where the projectile stopped
+    pub fn impact(&self) -> Option<(i32, i32, i32)> {
+        self.path.last().copied()
+    }
+
+    /// returns where a thrown item falls: the impact, or the last tile crossed before it when the impact blocks projectiles
+    pub fn landing(&self, map: &Map) -> Option<(i32, i32, i32)> {
+        self.path.iter().rev().copied().find(|&position| {
+            map.get_tile(position)
+                .is_some_and(|tile| !tile.blocks_projectiles())
+        })
+    }
+}
+
+/// a traced projectile moving one tile per frame, only drawn by the App
+pub struct ProjectileAnimation {
+    projectile: Projectile,
+    step: usize,
+}
+
+impl ProjectileAnimation {
+    pub fn new(projectile: Projectile) -> Self {
+        Self {
+            projectile,
+            step: 0,
+        }
+    }
+
+    /// moves the projectile one tile forward; returns false once it reached its impact
+    pub fn advance(&mut self) -> bool {
+        self.step += 1;
+        self.step < self.projectile.path.len()
+    }
+
+    pub fn draw(&self, buffer: &mut Buffer, area: Rect, camera: &Camera, map: &Map) {
+        let kind = self.projectile.kind;
+        if let Some(&position) = self.projectile.path.get(self.step)
+            && camera.is_visible_tile(position, map)
+            && let Some((x, y)) = camera.world_to_screen((position.0, position.1), area)
+        {
+            camera.draw_from_screen_coordinates(
+                kind.symbol(),
+                Style::default().fg(kind.color()),
+                Position { x, y },
+                buffer,
+            );
+        }
+    }
+}
+
+/// ranged attack that launches a projectile hitting the first entity on its way
+pub struct ProjectileAttack {
+    name: String,
+    damage: u32,
+    attack_range: u32,
+    attack_type: ActionType,
+    mana_cost: u32,
+    projectile: ProjectileKind,
+}
+
+impl ProjectileAttack {
+    pub fn new(
+        name: &str,
+        damage: u32,
+        range: u32,
+        attack_type: ActionType,
+        mana_cost: u32,
+        projectile: ProjectileKind,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            damage,
+            attack_range: range,
+            attack_type,
+            mana_cost,
+            projectile,
+        }
+    }
+
+    pub fn calculate_damage(&self, source: &Entity) -> u32 {
+        // firebolts are spells, their power scales with magic and a staff
+        let (stat, weapon_kind) = match self.projectile {
+            ProjectileKind::Firebolt => (source.magic(), Some(WeaponKind::Staff)),
+            ProjectileKind::Arrow => (source.strength(), Some(WeaponKind::Bow)),
+            ProjectileKind::Thrown => (source.strength(), None),
+        };
+        let mut damage = self.damage + stat;
+        if let Some(weapon_data) = source.get_weapon_data()
+            && Some(weapon_data.kind()) == weapon_kind
+        {
+            damage += weapon_data.strenght;
+        }
+        damage
+    }
+}
+
+impl Action for ProjectileAttack {
+    fn affect(
+        &self,
+        source: &Entity,
+        target_coordinates: (i32, i32, i32),
+        other_entities: &mut [&mut Entity],
+        map: &mut Map,
+        logger: &mut Logger,
+    ) {
+        if self.projectile == ProjectileKind::Arrow
+            && source
+                .get_weapon_data()
+                .is_none_or(|weapon_data| weapon_data.kind() != WeaponKind::Bow)
+        {
+            logger.push_message(format!("{} needs a bow to shoot", source.symbol()));
+            return;
+        }
+
+        let projectile = Projectile::trace(
+            self.projectile,
+            source.position,
+            target_coordinates,
+            self.attack_range,
+            map,
+            other_entities,
+        );
+        let impact = projectile.impact();
+        let landing = projectile.landing(map).unwrap_or(source.position);
+        map.launched.push(projectile);
+        if let Some(impact) = impact {
+            match self.attack_type {
+                ActionType::Fire => map.ignite(impact),
+                ActionType::Ice => map.freeze(impact),
+                _ => false,
+            };
+        }
+        if let Some(id) = self.ammunition()
+            && let Some(tile) = map.get_tile_mut(landing)
+        {
+            tile.items.add(items().build(id));
+        }
+
+        match other_entities
+            .iter_mut()
+            .find(|e| Some(e.position) == impact && !e.is_dead())
+        {
+            Some(target) => {
+                let damage = self.calculate_damage(source);
+                let message = hit(source, target, damage, self.attack_type);
+                logger.push_message(message);
+            }
+            None => logger.push_message(format!(
+                "{} uses {} but hits nothing",
+                source.symbol(),
+                self.name
+            )),
+        }
+    }
+
+    /// the projectile flies along a Bresenham line on the layer of the source, so the range counts its steps
+    fn can_reach(&self, source: &Entity, target_coordinates: (i32, i32, i32)) -> bool {
+        let (sx, sy, sz) = source.position;
+        let (tx, ty, tz) = target_coordinates;
+        sz == tz && (sx - tx).abs().max((sy - ty).abs()) as u32 <= self.attack_range
+    }
+
+    fn range(&self) -> u32 {
+        self.attack_range
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn attack_type(&self) -> ActionType {
+        self.attack_type
+    }
+
+    fn mana_cost(&self) -> u32 {
+        self.mana_cost
+    }
+
+    fn ammunition(&self) -> Option<&str> {
+        (self.projectile == ProjectileKind::Thrown).then_some(THROWN_ITEM)
+    }
+}
@@ -0,0 +1,64 @@
+use serde::Deserialize;
+
+use super::action::ActionType;
+
+/// percentage of damage resisted for each ActionType, negative values are weaknesses
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+pub struct Resistances {
+    pub physical: i32,
+    pub fire: i32,
+    pub ice: i32,
+    pub lightning: i32,
+}
+
+impl Resistances {
+    pub fn new(physical: i32, fire: i32, ice: i32, lightning: i32) -> Self {
+        Self {
+            physical,
+            fire,
+            ice,
+            lightning,
+        }
+    }
+
+    /// resistance against a single ActionType
+    pub fn single(action_type: ActionType, value: i32) -> Self {
+        let mut resistances = Self::default();
+        match action_type {
+            ActionType::Physical => resistances.physical = value,
+            ActionType::Fire => resistances.fire = value,
+            ActionType::Ice => resistances.ice = value,
+            ActionType::Lightning => resistances.lightning = value,
+            ActionType::Healing => {}
+        }
+        resistances
+    }
+
+    /// returns the resistance in percent against the ActionType; healing can't be resisted
+    pub fn get(&self, action_type: ActionType) -> i32 {
+        match action_type {
+            ActionType::Physical => self.physical,
+            ActionType::Fire => self.fire,
+            ActionType::Ice => self.ice,
+            ActionType::Lightning => self.lightning,
+            ActionType::Healing => 0,
+        }
+    }
+
+    /// returns the sum of both resistances
+    pub fn combine(&self, other: &Resistances) -> Self {
+        Self {
+            physical: self.physical + other.physical,
+            fire: self.fire + other.fire,
+            ice: self.ice + other.ice,
+            lightning: self.lightning + other.lightning,
+        }
+    }
+
+    /// returns the damage left after the resistance is applied; a resistance of 100 or more means immunity
+    pub fn apply(&self, damage: u32, action_type: ActionType) -> u32 {
+        let resistance = self.get(action_type).min(100) as i64;
+        (damage as i64 * (100 - resistance) / 100) as u32
+    }
+}
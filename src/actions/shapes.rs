@@ -0,0 +1,307 @@
+use std::collections::HashSet;
+
+use serde::Deserialize;
+
+use crate::{
+    common::utils::bresenham_line,
+    map::{
+        map::{Direction, Map},
+        tile::TileKind,
+    },
+};
+
+/// shape of the area of effect of an Action; every shape is stopped by the tiles that block sight
+#[derive(Clone, Copy, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum AreaShape {
+    /// filled circle around the target
+    Burst { radius: u32 },
+    /// quarter circle starting next to the caster in the direction it faces
+    Cone { length: u32 },
+    /// beam from the caster toward the target, stopped by the first blocking tile
+    Line { length: u32 },
+    /// circle outline around the target, the tiles closer than inner_radius are spared
+    Ring {
+        inner_radius: u32,
+        outer_radius: u32,
+    },
+    /// ball around the target spreading over the layers above and below through the holes of its column
+    Sphere { radius: u32 },
+}
+
+impl AreaShape {
+    /// returns the coordinates of the tiles hit by the shape
+    pub fn cells(
+        &self,
+        source_pos: (i32, i32, i32),
+        direction: Direction,
+        target_pos: (i32, i32, i32),
+        map: &Map,
+    ) -> HashSet<(i32, i32, i32)> {
+        match *self {
+            AreaShape::Burst { radius } => Self::disc(target_pos, 0, radius, map),
+            AreaShape::Ring {
+                inner_radius,
+                outer_radius,
+            } => Self::disc(target_pos, inner_radius, outer_radius, map),
+            AreaShape::Cone { length } => Self::cone(source_pos, direction, length, map),
+            AreaShape::Line { length } => Self::line(source_pos, target_pos, length, map),
+            AreaShape::Sphere { radius } => Self::sphere(target_pos, radius, map),
+        }
+    }
+
+    /// returns true if the line from the centre to the position crosses no tile blocking sight; the position itself may block sight
+    pub fn is_reachable_from(
+        centre: (i32, i32, i32),
+        position: (i32, i32, i32),
+        map: &Map,
+    ) -> bool {
+        if map.get_tile(position).is_none() {
+            return false;
+        }
+        let line = bresenham_line(centre.0, centre.1, position.0, position.1);
+        line[..line.len() - 1].iter().skip(1).all(|&(x, y)| {
+            map.get_tile((x, y, position.2))
+                .is_some_and(|tile| !tile.block_sight())
+        })
+    }
+
+    /// tiles at a distance between inner_radius (excluded unless 0) and outer_radius from the centre
+    fn disc(
+        centre: (i32, i32, i32),
+        inner_radius: u32,
+        outer_radius: u32,
+        map: &Map,
+    ) -> HashSet<(i32, i32, i32)> {
+        let mut cells = HashSet::new();
+        let (cx, cy, cz) = centre;
+        let outer = outer_radius as i32;
+        let inner = inner_radius as i32;
+        for y in (cy - outer)..=(cy + outer) {
+            for x in (cx - outer)..=(cx + outer) {
+                let distance = (x - cx).pow(2) + (y - cy).pow(2);
+                let outside_inner = inner == 0 || distance > inner * inner;
+                if distance <= outer * outer
+                    && outside_inner
+                    && Self::is_reachable_from(centre, (x, y, cz), map)
+                {
+                    cells.insert((x, y, cz));
+                }
+            }
+        }
+        cells
+    }
+
+    fn cone(
+        source: (i32, i32, i32),
+        direction: Direction,
+        length: u32,
+        map: &Map,
+    ) -> HashSet<(i32, i32, i32)> {
+        let mut cells = HashSet::new();
+        let (sx, sy, sz) = source;
+        let length = length as i32;
+        let (forward_x, forward_y) = direction.delta();
+        for forward in 1..=length {
+            for lateral in -forward..=forward {
+                // the lateral axis is perpendicular to the direction
+                let x = sx + forward_x * forward - forward_y * lateral;
+                let y = sy + forward_y * forward + forward_x * lateral;
+                if forward * forward + lateral * lateral <= length * length
+                    && Self::is_reachable_from(source, (x, y, sz), map)
+                {
+                    cells.insert((x, y, sz));
+                }
+            }
+        }
+        cells
+    }
+
+    fn line(
+        source: (i32, i32, i32),
+        target: (i32, i32, i32),
+        length: u32,
+        map: &Map,
+    ) -> HashSet<(i32, i32, i32)> {
+        let mut cells = HashSet::new();
+        if source == target {
+            return cells;
+        }
+        // extends the line past the target so the beam always has its full length
+        let (dx, dy) = (target.0 - source.0, target.1 - source.1);
+        let scale = length as i32 * 2 / dx.abs().max(dy.abs()).max(1) + 1;
+        let end = (source.0 + dx * scale, source.1 + dy * scale);
+        for &(x, y) in bresenham_line(source.0, source.1, end.0, end.1)
+            .iter()
+            .skip(1)
+            .take(length as usize)
+        {
+            let Some(tile) = map.get_tile((x, y, source.2)) else {
+                break;
+            };
+            cells.insert((x, y, source.2));
+            if tile.block_sight() {
+                break;
+            }
+        }
+        cells
+    }
+
+    /// returns true if the layers between the centre and the layer z are linked by holes in the column of the centre, a hole opening its layer to the one below
+    fn is_column_open(centre: (i32, i32, i32), z: i32, map: &Map) -> bool {
+        let (cx, cy, cz) = centre;
+        (cz.min(z) + 1..=cz.max(z)).all(|layer| {
+            map.get_tile((cx, cy, layer))
+                .is_some_and(|tile| tile.kind == TileKind::Hole)
+        })
+    }
+
+    fn sphere(centre: (i32, i32, i32), radius: u32, map: &Map) -> HashSet<(i32, i32, i32)> {
+        let mut cells = HashSet::new();
+        let (cx, cy, cz) = centre;
+        let radius = radius as i32;
+        for z in (cz - radius)..=(cz + radius) {
+            if !Self::is_column_open(centre, z, map) {
+                continue;
+            }
+            // radius of the slice of the sphere on this layer
+            let dz = z - cz;
+            let slice_radius_squared = radius * radius - dz * dz;
+            for y in (cy - radius)..=(cy + radius) {
+                for x in (cx - radius)..=(cx + radius) {
+                    if (x - cx).pow(2) + (y - cy).pow(2) <= slice_radius_squared
+                        && Self::is_reachable_from((cx, cy, z), (x, y, z), map)
+                    {
+                        cells.insert((x, y, z));
+                    }
+                }
+            }
+        }
+        cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::structures::structure::Wall;
+
+    /// map of grass with a wall at each position; the chunks already have structures on their first tiles so the tests stay around (10, 10)
+    fn map_with_walls(walls: &[(i32, i32, i32)]) -> Map {
+        let mut map = Map::default();
+        for &position in walls {
+            map.get_tile_mut(position)
+                .unwrap()
+                .add_structure(Box::new(Wall {}));
+        }
+        map
+    }
+
+    #[test]
+    fn burst_is_a_filled_circle() {
+        let map = map_with_walls(&[]);
+        let cells =
+            AreaShape::Burst { radius: 2 }.cells((5, 5, 0), Direction::North, (10, 10, 0), &map);
+        assert!(cells.contains(&(10, 10, 0)));
+        assert!(cells.contains(&(12, 10, 0)));
+        assert!(cells.contains(&(11, 11, 0)));
+        assert!(!cells.contains(&(12, 12, 0)));
+        assert_eq!(cells.len(), 13);
+    }
+
+    #[test]
+    fn burst_is_occluded_by_walls() {
+        let map = map_with_walls(&[(11, 10, 0)]);
+        let cells =
+            AreaShape::Burst { radius: 3 }.cells((5, 5, 0), Direction::North, (10, 10, 0), &map);
+        // the wall is hit but not what is behind it
+        assert!(cells.contains(&(11, 10, 0)));
+        assert!(!cells.contains(&(12, 10, 0)));
+        assert!(!cells.contains(&(13, 10, 0)));
+        assert!(cells.contains(&(9, 10, 0)));
+    }
+
+    #[test]
+    fn cone_follows_the_direction() {
+        let map = map_with_walls(&[]);
+        let cells =
+            AreaShape::Cone { length: 3 }.cells((10, 10, 0), Direction::East, (10, 10, 0), &map);
+        assert!(cells.contains(&(11, 10, 0)));
+        assert!(cells.contains(&(13, 10, 0)));
+        assert!(cells.contains(&(12, 12, 0)));
+        assert!(!cells.contains(&(10, 10, 0)));
+        assert!(!cells.contains(&(9, 10, 0)));
+        assert!(!cells.contains(&(11, 12, 0)));
+        assert!(!cells.contains(&(14, 10, 0)));
+    }
+
+    #[test]
+    fn cone_is_occluded_by_walls() {
+        let map = map_with_walls(&[(11, 10, 0)]);
+        let cells =
+            AreaShape::Cone { length: 3 }.cells((10, 10, 0), Direction::East, (10, 10, 0), &map);
+        assert!(cells.contains(&(11, 10, 0)));
+        assert!(!cells.contains(&(12, 10, 0)));
+        assert!(cells.contains(&(11, 11, 0)));
+    }
+
+    #[test]
+    fn line_has_its_full_length() {
+        let map = map_with_walls(&[]);
+        let cells =
+            AreaShape::Line { length: 4 }.cells((10, 10, 0), Direction::North, (10, 8, 0), &map);
+        let expected: HashSet<(i32, i32, i32)> =
+            [(10, 9, 0), (10, 8, 0), (10, 7, 0), (10, 6, 0)].into();
+        assert_eq!(cells, expected);
+    }
+
+    #[test]
+    fn line_stops_at_the_first_wall() {
+        let map = map_with_walls(&[(12, 10, 0)]);
+        let cells =
+            AreaShape::Line { length: 5 }.cells((10, 10, 0), Direction::North, (15, 10, 0), &map);
+        let expected: HashSet<(i32, i32, i32)> = [(11, 10, 0), (12, 10, 0)].into();
+        assert_eq!(cells, expected);
+    }
+
+    #[test]
+    fn ring_spares_the_centre() {
+        let map = map_with_walls(&[]);
+        let cells = AreaShape::Ring {
+            inner_radius: 1,
+            outer_radius: 2,
+        }
+        .cells((5, 5, 0), Direction::North, (10, 10, 0), &map);
+        assert!(!cells.contains(&(10, 10, 0)));
+        assert!(!cells.contains(&(11, 10, 0)));
+        assert!(cells.contains(&(11, 11, 0)));
+        assert!(cells.contains(&(12, 10, 0)));
+        assert_eq!(cells.len(), 8);
+    }
+
+    #[test]
+    fn sphere_spreads_over_loaded_layers() {
+        let mut map = map_with_walls(&[]);
+        map.load_chunk(0, 0, 1);
+        map.get_tile_mut((10, 10, 1)).unwrap().kind = TileKind::Hole;
+        let cells =
+            AreaShape::Sphere { radius: 2 }.cells((5, 5, 0), Direction::North, (10, 10, 0), &map);
+        assert!(cells.contains(&(12, 10, 0)));
+        assert!(cells.contains(&(11, 10, 1)));
+        assert!(cells.contains(&(10, 10, 1)));
+        assert!(!cells.contains(&(12, 10, 1)));
+        // the layer below isn't loaded
+        assert!(!cells.iter().any(|&(_, _, z)| z == -1));
+    }
+
+    #[test]
+    fn sphere_is_stopped_by_a_solid_floor() {
+        let mut map = map_with_walls(&[]);
+        map.load_chunk(0, 0, 1);
+        let cells =
+            AreaShape::Sphere { radius: 2 }.cells((5, 5, 0), Direction::North, (10, 10, 0), &map);
+        assert!(cells.contains(&(12, 10, 0)));
+        // no hole links the layer above to the centre
+        assert!(!cells.iter().any(|&(_, _, z)| z != 0));
+    }
+}
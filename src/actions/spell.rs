@@ -0,0 +1,199 @@
+use crate::{
+    data::{
+        progression::progression,
+        spells::{SpellDefinition, spells},
+    },
+    systems::level_manager::XPCurve,
+};
+
+use super::action::{Action, ActionType};
+
+/// xp gained by a spell each time it takes effect
+pub const SPELL_XP_PER_CAST: u32 = 1;
+
+/// responsible for counting the levels and XP of a spell
+pub struct SpellMastery {
+    pub level: u32,
+    current_xp: u32,
+    xp_curve: Box<dyn XPCurve>,
+}
+
+impl SpellMastery {
+    pub fn new(xp_curve: Box<dyn XPCurve>) -> Self {
+        Self {
+            level: 1,
+            current_xp: 0,
+            xp_curve,
+        }
+    }
+
+    /// add xp to the spell and returns the number of levels gained
+    pub fn add_xp(&mut self, xp: u32) -> u32 {
+        let initial_level = self.level;
+
+        self.current_xp += xp;
+        while self.current_xp >= self.xp_curve.xp_required(self.level + 1) {
+            self.current_xp -= self.xp_curve.xp_required(self.level + 1);
+            self.level += 1;
+        }
+        self.level - initial_level
+    }
+
+    pub fn current_xp(&self) -> u32 {
+        self.current_xp
+    }
+
+    /// returns the required xp for next lvl
+    pub fn xp_to_next_level(&self) -> u32 {
+        self.xp_curve.xp_required(self.level + 1)
+    }
+}
+
+impl Default for SpellMastery {
+    fn default() -> Self {
+        Self::new(progression().spell_curve.build())
+    }
+}
+
+/// an Action learned in a Spellbook, which has to recharge after being cast and may take several turns to cast
+pub struct Spell {
+    /// id of the definition of the spell in data/spells.toml
+    pub id: String,
+    pub action: Box<dyn Action>,
+    /// turns to wait before casting the spell again
+    pub cooldown: u32,
+    pub remaining_cooldown: u32,
+    /// turns spent casting before the spell takes effect, 0 to release it immediately
+    pub casting_time: u32,
+    pub mastery: SpellMastery,
+}
+
+impl Spell {
+    pub fn new(id: &str, action: Box<dyn Action>, cooldown: u32, casting_time: u32) -> Self {
+        Self {
+            id: id.to_string(),
+            action,
+            cooldown,
+            remaining_cooldown: 0,
+            casting_time,
+            mastery: SpellMastery::default(),
+        }
+    }
+
+    /// the school of a spell is the ActionType of its action
+    pub fn school(&self) -> ActionType {
+        self.action.attack_type()
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.remaining_cooldown == 0
+    }
+
+    /// returns the spells this one can evolve into at its current level
+    pub fn evolution_branches(&self) -> Vec<&'static SpellDefinition> {
+        let registry = spells();
+        registry
+            .evolution(&self.id, self.mastery.level)
+            .map(|evolution| {
+                evolution
+                    .branches
+                    .iter()
+                    .filter_map(|id| registry.get(id))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// spell being cast over several turns
+pub struct Casting {
+    pub spell_index: usize,
+    pub target: (i32, i32, i32),
+    pub remaining_turns: u32,
+}
+
+/// spells known by an Entity
+#[derive(Default)]
+pub struct Spellbook {
+    pub spells: Vec<Spell>,
+    pub casting: Option<Casting>,
+}
+
+impl Spellbook {
+    pub fn new(spells: Vec<Spell>) -> Self {
+        Self {
+            spells,
+            casting: None,
+        }
+    }
+
+    pub fn learn(&mut self, spell: Spell) {
+        self.spells.push(spell);
+    }
+
+    /// returns the indices of the spells grouped by school, in the order the schools were learned
+    pub fn by_school(&self) -> Vec<(ActionType, Vec<usize>)> {
+        let mut schools: Vec<(ActionType, Vec<usize>)> = Vec::new();
+        for (index, spell) in self.spells.iter().enumerate() {
+            match schools
+                .iter_mut()
+                .find(|(school, _)| *school == spell.school())
+            {
+                Some((_, indices)) => indices.push(index),
+                None => schools.push((spell.school(), vec![index])),
+            }
+        }
+        schools
+    }
+
+    /// returns for each spell whether it is recharging, before the Entity acts
+    pub fn recharging(&self) -> Vec<bool> {
+        self.spells.iter().map(|spell| !spell.is_ready()).collect()
+    }
+
+    /// decreases the cooldowns of the spells that were recharging before the Entity acted, so that a spell cast this turn waits its whole cooldown
+    pub fn decrease_cooldowns(&mut self, recharging: &[bool]) {
+        for (spell, _) in self
+            .spells
+            .iter_mut()
+            .zip(recharging)
+            .filter(|(_, recharging)| **recharging)
+        {
+            spell.remaining_cooldown = spell.remaining_cooldown.saturating_sub(1);
+        }
+    }
+
+    /// returns the index of the first spell waiting for an evolution to be chosen
+    pub fn pending_evolution(&self) -> Option<usize> {
+        self.pending_evolutions().next()
+    }
+
+    /// returns the indices of the spells waiting for an evolution to be chosen
+    pub fn pending_evolutions(&self) -> impl Iterator<Item = usize> + '_ {
+        self.spells
+            .iter()
+            .enumerate()
+            .filter(|(_, spell)| !spell.evolution_branches().is_empty())
+            .map(|(index, _)| index)
+    }
+
+    /// replaces the spell by the chosen branch of its evolution, which starts again at level 1;
+    /// returns the names of the old and new spells
+    pub fn evolve(&mut self, spell_index: usize, branch: usize) -> Option<(String, String)> {
+        let spell = self.spells.get_mut(spell_index)?;
+        let definition = *spell.evolution_branches().get(branch)?;
+        let old_name = spell.action.name().to_string();
+        let mut evolved = definition.build();
+        // the cooldown in progress is kept so evolving isn't a free recharge
+        evolved.remaining_cooldown = spell.remaining_cooldown.min(evolved.cooldown);
+        *spell = evolved;
+        if self
+            .casting
+            .as_ref()
+            .is_some_and(|casting| casting.spell_index == spell_index)
+        {
+            self.casting = None;
+        }
+        Some((old_name, definition.name.clone()))
+    }
+}
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use color_eyre::Result;
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, MouseEvent, MouseEventKind};
 use ratatui::{
@@ -8,24 +10,40 @@ use ratatui::{
 };
 
 use crate::{
+    actions::projectile::ProjectileAnimation,
     common::utils::Drawable,
     entities::{
-        AIbehavior::ChasePlayerBehavior,
-        controller::Controller,
+        character::{Class, Race},
+        controller::PlayerInput,
         entity::{Entity, EntityKind},
     },
     map::map::*,
     menu::Menu,
+    screens::{
+        self,
+        character_creation::{CharacterCreation, CreationEvent},
+        console::{Console, ConsoleEvent},
+    },
     systems::{
         camera::Camera,
-        entity_manager::EntityManager,
-        spawner::{Spawner, SpawnerConfiguration},
+        replay::{Recorder, Replay},
+        scripting,
+        simulation::Simulation,
+        targeting::Targeting,
+        wizard::Command,
     },
 };
 
+/// time a projectile takes to cross one tile
+const PROJECTILE_FRAME_DURATION: Duration = Duration::from_millis(30);
+/// time each recorded input is shown when replaying at normal speed
+const REPLAY_FRAME_DURATION: Duration = Duration::from_millis(150);
+
 #[derive(Clone)]
 pub struct Config {
     background_style: Style,
+    /// the debug console opens with `, only in debug builds unless --wizard is passed
+    pub wizard: bool,
 }
 
 impl Default for Config {
@@ -35,64 +53,216 @@ impl Default for Config {
             //background_style: Style::default().bg(Color::Rgb(30, 26, 21)),
             // dark blue
             background_style: Style::default().bg(Color::Rgb(30, 30, 40)),
+            wizard: cfg!(debug_assertions),
         }
     }
 }
 
+/// what the keyboard input currently controls
+enum Mode {
+    /// new game screen, the player doesn't exist yet
+    CharacterCreation(CharacterCreation),
+    Playing,
+    Inventory,
+    Spellbook,
+    SkillTree,
+    Crafting,
+    Targeting(Targeting),
+    /// the player chooses the branch of the spell at the index
+    Evolution(usize),
+    /// debug commands are typed in the console
+    Console,
+}
+
+/// replay played in the terminal, one input per frame
+struct Playback {
+    replay: Replay,
+    next_input: usize,
+    frame_duration: Duration,
+}
+
 pub struct App {
-    map: Map,
-    pub entity_manager: EntityManager,
+    pub simulation: Simulation,
+    /// where the map is drawn from, following the player
+    pub camera: Camera,
+    /// projectiles still flying, only kept for their animation
+    projectiles: Vec<ProjectileAnimation>,
+    mode: Mode,
+    recorder: Recorder,
+    playback: Option<Playback>,
+    console: Console,
     config: Config,
+    /// spells whose evolution the player put off, only chosen from the spellbook
+    deferred_evolutions: Vec<usize>,
     exit: bool,
     menu: Menu,
-    pub camera: Camera,
     pub fps_counter: FpsCounter,
 }
 
 impl App {
     pub fn new(config: Config) -> Self {
-        let mut entity_manager = EntityManager::new();
-        entity_manager.add_entity(Entity::new(
-            EntityKind::Dragon,
-            EntityKind::Dragon.name().to_owned(),
-            (0, 1, 0),
-            Controller::AI(Box::new(ChasePlayerBehavior)),
-        ));
-        entity_manager.add_entity(Entity::new(
-            EntityKind::Sheep,
-            EntityKind::Sheep.name().to_owned(),
-            (1, 0, 0),
-            Controller::AI(Box::new(ChasePlayerBehavior)),
-        ));
+        let mut simulation = Simulation::new(Map::default());
+        simulation
+            .entity_manager
+            .add_entity(Entity::monster(EntityKind::Dragon, (0, 1, 0)));
+        simulation
+            .entity_manager
+            .add_entity(Entity::monster(EntityKind::Sheep, (1, 0, 0)));
         Self {
-            map: Map::default(),
-            entity_manager: entity_manager,
+            simulation,
+            camera: Camera::new((0, 0, 0)),
+            projectiles: Vec::new(),
+            mode: Mode::CharacterCreation(CharacterCreation::new()),
+            recorder: Recorder::disabled(),
+            playback: None,
+            console: Console::default(),
             config,
+            deferred_evolutions: Vec::new(),
             exit: false,
             menu: Menu::default(),
-            camera: Camera::new((0, 0, 0)),
             fps_counter: FpsCounter::default(),
         }
     }
 
     pub fn run(mut self, mut terminal: Terminal<CrosstermBackend<std::io::Stdout>>) -> Result<()> {
-        let spawner_config = SpawnerConfiguration::default();
-        let mut spawner = Spawner::new(spawner_config);
         while !self.exit {
             self.fps_counter.update();
+            // the scripts of the mods edited while playing are compiled again between two frames
+            scripting::hot_reload();
+            for message in scripting::take_messages() {
+                self.simulation.logger.push_message(message);
+            }
 
-            self.handle_events()?;
+            if self.playback.is_some() {
+                self.play_next_input()?;
+            } else {
+                self.handle_events()?;
+            }
+            self.update_projectiles();
             let (cols, rows) = crossterm::terminal::size()?;
             self.update_camera_position(Rect::new(0, 0, cols, rows));
             //self.update();
-            spawner.try_spawn(&mut self.entity_manager, &self.map);
 
             terminal.draw(|f| self.draw(f))?;
         }
+        self.finish_recording();
+        Ok(())
+    }
+
+    /// starts the game of the replay, its inputs then being played one per frame in the terminal
+    pub fn start_playback(&mut self, replay: Replay, fast: bool) {
+        self.start_game(replay.seed, replay.race, replay.class, replay.name.clone());
+        self.playback = Some(Playback {
+            replay,
+            next_input: 0,
+            frame_duration: if fast {
+                Duration::ZERO
+            } else {
+                REPLAY_FRAME_DURATION
+            },
+        });
+    }
+
+    /// plays the whole replay without a terminal and returns the hash of the final state, nothing being animated
+    pub fn run_headless(mut self, replay: &Replay) -> Result<u64, String> {
+        self.start_game(replay.seed, replay.race, replay.class, replay.name.clone());
+        for &(turn, input) in replay.inputs.iter() {
+            self.check_turn(turn)?;
+            self.simulation.step(input);
+        }
+        Ok(self.simulation.state_hash())
+    }
+
+    /// plays the next input of the replay, pressing q or Esc stops it
+    fn play_next_input(&mut self) -> Result<()> {
+        let Some(playback) = &mut self.playback else {
+            return Ok(());
+        };
+        if event::poll(playback.frame_duration)?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+            && let KeyCode::Char('q') | KeyCode::Esc = key.code
+        {
+            self.playback = None;
+            self.simulation
+                .logger
+                .push_message("replay stopped".to_string());
+            return Ok(());
+        }
+        let Some(&(turn, input)) = playback.replay.inputs.get(playback.next_input) else {
+            self.end_playback();
+            return Ok(());
+        };
+        playback.next_input += 1;
+        if let Err(error) = self.check_turn(turn) {
+            self.playback = None;
+            self.simulation.logger.push_message(error);
+            return Ok(());
+        }
+        self.play_input(input);
         Ok(())
     }
 
+    /// compares the final state to the one of the recorded game, the game then going on unrecorded
+    fn end_playback(&mut self) {
+        let Some(playback) = self.playback.take() else {
+            return;
+        };
+        let hash = self.simulation.state_hash();
+        let message = match playback.replay.hash {
+            Some(expected) if expected == hash => format!("replay done, state {hash:016x} matches"),
+            Some(expected) => {
+                format!("replay done, state {hash:016x} differs from {expected:016x}")
+            }
+            None => format!("replay done, state {hash:016x}"),
+        };
+        self.simulation.logger.push_message(message);
+    }
+
+    /// returns an error if the replay reached an input on another turn than the one it was recorded on
+    fn check_turn(&self, turn: u64) -> Result<(), String> {
+        let current = self.simulation.turn();
+        if current == turn {
+            Ok(())
+        } else {
+            Err(format!(
+                "replay out of sync: input of turn {turn} played on turn {current}"
+            ))
+        }
+    }
+
+    /// writes the hash of the state at the end of the recorded game
+    fn finish_recording(&mut self) {
+        if !matches!(self.mode, Mode::CharacterCreation(_)) {
+            let hash = self.simulation.state_hash();
+            self.recorder.finish(hash);
+        }
+    }
+
+    /// generates the world and adds the player, from the new game screen or a replay
+    pub fn start_game(&mut self, seed: u64, race: Race, class: Class, name: String) {
+        // the world is generated from the seed chosen on the screen
+        self.simulation.map = Map::generate(seed);
+        self.simulation
+            .logger
+            .push_message(format!("world seed: {seed}"));
+        let player = Entity::character(name, race, class, (0, 0, 0));
+        self.simulation.logger.push_message(format!(
+            "{} the {} {} wakes up",
+            player.name,
+            race.name(),
+            class.name()
+        ));
+        self.simulation.entity_manager.add_player(player);
+        self.deferred_evolutions.clear();
+        self.mode = Mode::Playing;
+    }
+
     fn handle_events(&mut self) -> Result<()> {
+        // doesn't wait for an input while projectiles are animated
+        if !self.projectiles.is_empty() && !event::poll(PROJECTILE_FRAME_DURATION)? {
+            return Ok(());
+        }
         match event::read()? {
             Event::Key(key) => {
                 if key.kind == KeyEventKind::Press {
@@ -107,22 +277,267 @@ impl App {
         Ok(())
     }
 
+    /// moves the projectiles one tile forward and removes the ones that reached their impact
+    fn update_projectiles(&mut self) {
+        self.projectiles
+            .retain_mut(|projectile| projectile.advance());
+    }
+
     fn process_key(&mut self, key: KeyEvent) {
         if key.kind == KeyEventKind::Press {
-            match key.code {
-                KeyCode::Char('q') => self.exit = true,
-                KeyCode::Char('f') => self.menu.visible = !self.menu.visible, // Toggle inventaire
-                _ => self.entity_manager.update(
-                    key.code,
-                    &self.camera,
-                    &mut self.map,
-                    &mut self.menu.logger,
-                ),
+            match &mut self.mode {
+                Mode::CharacterCreation(creation) => match creation.process_key(key.code) {
+                    CreationEvent::Cancel => self.exit = true,
+                    CreationEvent::Done => {
+                        let (seed, race, class, name) = (
+                            creation.seed(),
+                            creation.race(),
+                            creation.class(),
+                            creation.name().to_string(),
+                        );
+                        self.recorder = Recorder::start(seed, race, class, &name);
+                        self.start_game(seed, race, class, name);
+                    }
+                    CreationEvent::None => {}
+                },
+                Mode::Playing => match key.code {
+                    KeyCode::Char('q') => self.exit = true,
+                    KeyCode::Char('f') => self.menu.visible = !self.menu.visible, // Toggle inventaire
+                    KeyCode::Char('i') => self.mode = Mode::Inventory,
+                    KeyCode::Char('b') => self.mode = Mode::Spellbook,
+                    KeyCode::Char('k') => self.mode = Mode::SkillTree,
+                    KeyCode::Char('c') => self.mode = Mode::Crafting,
+                    KeyCode::Char('e') => {
+                        self.play_input(PlayerInput::Key(key.code));
+                        // using a workbench opens the crafting screen
+                        if let Mode::Playing = self.mode
+                            && self.player_faces_workbench()
+                        {
+                            self.mode = Mode::Crafting;
+                        }
+                    }
+                    KeyCode::Char('t') => self.start_targeting(),
+                    KeyCode::Char('`') if self.config.wizard => self.mode = Mode::Console,
+                    _ => self.play_input(PlayerInput::Key(key.code)),
+                },
+                Mode::Targeting(targeting) => match key.code {
+                    KeyCode::Esc => self.mode = Mode::Playing,
+                    KeyCode::Up => targeting.move_cursor(0, -1),
+                    KeyCode::Down => targeting.move_cursor(0, 1),
+                    KeyCode::Left => targeting.move_cursor(-1, 0),
+                    KeyCode::Right => targeting.move_cursor(1, 0),
+                    KeyCode::Tab => {
+                        let hostiles = self
+                            .simulation
+                            .entity_manager
+                            .visible_hostiles(&self.simulation.fov, &self.simulation.map);
+                        targeting.cycle_hostiles(&hostiles);
+                    }
+                    KeyCode::Enter => self.confirm_target(),
+                    _ => {}
+                },
+                Mode::Console => match self.console.process_key(key.code) {
+                    ConsoleEvent::Close => self.mode = Mode::Playing,
+                    ConsoleEvent::Run(line) => self.run_command(&line),
+                    ConsoleEvent::None => {}
+                },
+                Mode::Spellbook => match key.code {
+                    KeyCode::Esc | KeyCode::Char('b') => self.mode = Mode::Playing,
+                    // reopens the evolutions put off
+                    KeyCode::Char('v') => {
+                        if let Some(spell_index) = self
+                            .simulation
+                            .entity_manager
+                            .get_player()
+                            .and_then(|player| player.spellbook.pending_evolution())
+                        {
+                            self.mode = Mode::Evolution(spell_index);
+                        }
+                    }
+                    _ => {}
+                },
+                Mode::Evolution(spell_index) => match key.code {
+                    KeyCode::Esc => {
+                        if !self.deferred_evolutions.contains(spell_index) {
+                            self.deferred_evolutions.push(*spell_index);
+                        }
+                        self.mode = Mode::Playing;
+                    }
+                    // the branch is chosen with the same letters as the items
+                    KeyCode::Char(c) => {
+                        if let Some(branch) = screens::inventory::index_from_key(c) {
+                            let spell_index = *spell_index;
+                            self.deferred_evolutions
+                                .retain(|&index| index != spell_index);
+                            self.mode = Mode::Playing;
+                            self.play_input(PlayerInput::EvolveSpell {
+                                spell_index,
+                                branch,
+                            });
+                        }
+                    }
+                    _ => {}
+                },
+                Mode::SkillTree => match key.code {
+                    KeyCode::Esc => self.mode = Mode::Playing,
+                    KeyCode::Char(c) => {
+                        let input = if let Some(attribute) =
+                            screens::skill_tree::attribute_from_key(c)
+                        {
+                            Some(PlayerInput::SpendAttributePoint(attribute))
+                        } else if let Some(player) = self.simulation.entity_manager.get_player() {
+                            screens::inventory::index_from_key(c)
+                                .and_then(|index| {
+                                    screens::skill_tree::perk_tree(player).get(index).copied()
+                                })
+                                .map(|(perk_index, _)| PlayerInput::TakePerk(perk_index))
+                        } else {
+                            None
+                        };
+                        if let Some(input) = input {
+                            self.play_input(input);
+                        }
+                    }
+                    _ => {}
+                },
+                Mode::Crafting => match key.code {
+                    KeyCode::Esc | KeyCode::Char('c') => self.mode = Mode::Playing,
+                    KeyCode::Char(c) => {
+                        let recipe_index =
+                            self.simulation
+                                .entity_manager
+                                .get_player()
+                                .and_then(|player| {
+                                    screens::inventory::index_from_key(c).and_then(|index| {
+                                        screens::crafting::known_recipes(player).get(index).copied()
+                                    })
+                                });
+                        if let Some(recipe_index) = recipe_index {
+                            self.play_input(PlayerInput::Craft(recipe_index));
+                        }
+                    }
+                    _ => {}
+                },
+                Mode::Inventory => match key.code {
+                    KeyCode::Esc | KeyCode::Char('i') => self.mode = Mode::Playing,
+                    KeyCode::Char(c) => {
+                        if let Some(index) = screens::inventory::index_from_key(c) {
+                            self.play_input(PlayerInput::UseItem(index));
+                            self.mode = Mode::Playing;
+                        }
+                    }
+                    _ => {}
+                },
             }
         }
     }
 
+    /// runs a command of the console, the creatures spawning on the selected tile or in front of the player
+    fn run_command(&mut self, line: &str) {
+        let message = match Command::parse(line) {
+            Ok(command) => {
+                let cursor = self.menu.selected_position.or_else(|| {
+                    self.simulation
+                        .player()
+                        .map(|player| player.direction.coordinates_in_front(player.position))
+                });
+                // a replay can't play the commands again
+                self.recorder = Recorder::disabled();
+                command.run(&mut self.simulation, cursor.unwrap_or((0, 0, 0)))
+            }
+            Err(error) => error,
+        };
+        self.simulation.logger.push_message(message);
+    }
+
+    /// returns true if the tile in front of the player holds a workbench
+    fn player_faces_workbench(&self) -> bool {
+        let Some(player) = self.simulation.entity_manager.get_player() else {
+            return false;
+        };
+        self.simulation
+            .map
+            .get_tile(player.direction.coordinates_in_front(player.position))
+            .and_then(|tile| tile.structure.as_ref())
+            .is_some_and(|structure| structure.workbench().is_some())
+    }
+
+    /// aims the action of the selected slot, starting on the closest visible hostile
+    fn start_targeting(&mut self) {
+        let Some(player) = self.simulation.entity_manager.get_player() else {
+            return;
+        };
+        let Some(slot) = player.action_bar.selected_action() else {
+            self.simulation
+                .logger
+                .push_message("no action in the selected slot".to_string());
+            return;
+        };
+        let mut targeting = Targeting::new(slot, player.position);
+        let hostiles = self
+            .simulation
+            .entity_manager
+            .visible_hostiles(&self.simulation.fov, &self.simulation.map);
+        targeting.cycle_hostiles(&hostiles);
+        self.mode = Mode::Targeting(targeting);
+    }
+
+    /// uses the aimed action on the cursor if it is a valid target
+    fn confirm_target(&mut self) {
+        let (Mode::Targeting(targeting), Some(player)) =
+            (&self.mode, self.simulation.entity_manager.get_player())
+        else {
+            return;
+        };
+        if !targeting.is_valid_target(player, &self.camera, &self.simulation.map) {
+            self.simulation
+                .logger
+                .push_message("target out of range or out of sight".to_string());
+            return;
+        }
+        let input = PlayerInput::UseAction {
+            slot: targeting.slot,
+            target: targeting.cursor,
+        };
+        self.mode = Mode::Playing;
+        self.play_input(input);
+    }
+
+    /// records the input of the player and plays it
+    fn play_input(&mut self, input: PlayerInput) {
+        self.recorder.record(self.simulation.turn(), input);
+        let launched = self.simulation.step(input);
+        self.projectiles
+            .extend(launched.into_iter().map(ProjectileAnimation::new));
+
+        // asks the player to choose the evolution of a spell as soon as one is unlocked, unless it was put off
+        if let Mode::Playing = self.mode
+            && let Some(player) = self.simulation.entity_manager.get_player()
+            && let Some(spell_index) = player
+                .spellbook
+                .pending_evolutions()
+                .find(|index| !self.deferred_evolutions.contains(index))
+        {
+            self.mode = Mode::Evolution(spell_index);
+        }
+    }
+
     fn process_mouse(&mut self, mouse_event: MouseEvent) {
+        // the mouse moves the reticle and a click fires
+        if let Mode::Targeting(targeting) = &mut self.mode {
+            if let Ok((cols, rows)) = crossterm::terminal::size() {
+                let (x, y) = self.camera.screen_to_world(
+                    (mouse_event.column, mouse_event.row),
+                    Rect::new(0, 0, cols, rows),
+                );
+                targeting.cursor = (x, y, self.camera.position.2);
+            }
+            if let MouseEventKind::Down(_) = mouse_event.kind {
+                self.confirm_target();
+            }
+            return;
+        }
+
         match mouse_event.kind {
             MouseEventKind::Down(_) => {
                 let click_x = mouse_event.column;
@@ -141,8 +556,9 @@ impl App {
                         return;
                     }
 
-                    //let player_position = self.entity_manager.player.position;
+                    //let player_position = self.simulation.entity_manager.player.position;
                     let player_position = self
+                        .simulation
                         .entity_manager
                         .get_player_position()
                         .unwrap_or((0, 0, 0));
@@ -153,18 +569,30 @@ impl App {
                     // converts to map coordinates
                     let world_x = camera_x + click_x as i32;
                     let world_y = camera_y + click_y as i32;
+                    self.menu.selected_position = Some((world_x, world_y, player_position.2));
 
                     // try to find the entity at the coordiantes
-                    if let Some(entity) =
-                        self.entity_manager
-                            .find_entity_at((world_x, world_y, player_position.2))
-                    {
-                        self.menu.selected_entity_info = Some(String::from(entity.symbol()));
+                    if let Some(entity) = self.simulation.entity_manager.find_entity_at((
+                        world_x,
+                        world_y,
+                        player_position.2,
+                    )) {
+                        let effects: Vec<&str> = entity
+                            .status_effects
+                            .iter()
+                            .map(|e| e.kind.name())
+                            .collect();
+                        self.menu.selected_entity_info = if effects.is_empty() {
+                            Some(String::from(entity.symbol()))
+                        } else {
+                            Some(format!("{} ({})", entity.symbol(), effects.join(", ")))
+                        };
                         self.menu.selected_tile_info = None;
                     }
                     // otherwise gets the tile
                     else if let Some(tile) =
-                        self.map
+                        self.simulation
+                            .map
                             .get_tile((world_x, world_y, self.camera.position.2))
                     {
                         self.menu.selected_tile_info = Some(String::from(tile.symbol()));
@@ -179,9 +607,10 @@ impl App {
         }
     }
 
-    fn update_camera_position(&mut self, area: Rect) {
-        //let player_pos = self.entity_manager.player.position;
+    pub fn update_camera_position(&mut self, area: Rect) {
+        //let player_pos = self.simulation.entity_manager.player.position;
         let player_position = self
+            .simulation
             .entity_manager
             .get_player_position()
             .unwrap_or((0, 0, 0));
@@ -190,9 +619,14 @@ impl App {
         self.camera.position.2 = player_position.2;
     }
 
-    fn draw(&self, frame: &mut Frame) {
+    pub fn draw(&self, frame: &mut Frame) {
         let area = frame.area();
 
+        if let Mode::CharacterCreation(creation) = &self.mode {
+            creation.draw(frame, area);
+            return;
+        }
+
         // draws background color
         let background = Block::default()
             .style(self.config.background_style)
@@ -202,16 +636,70 @@ impl App {
         let buffer = frame.buffer_mut();
 
         // draws map
-        self.map.draw(buffer, area, &self.camera, &self.map);
+        self.simulation
+            .map
+            .draw(buffer, area, &self.camera, &self.simulation.map);
 
         // draws entities
-        self.entity_manager
-            .draw(buffer, area, &self.camera, &self.map);
+        self.simulation
+            .entity_manager
+            .draw(buffer, area, &self.camera, &self.simulation.map);
+
+        for projectile in self.projectiles.iter() {
+            projectile.draw(buffer, area, &self.camera, &self.simulation.map);
+        }
+
+        // draws the rain, snow or fog over the map
+        self.simulation
+            .map
+            .weather
+            .draw(buffer, area, &self.camera, &self.simulation.map);
+
+        // draws the range and area of effect of the aimed action
+        if let Mode::Targeting(targeting) = &self.mode
+            && let Some(player) = self.simulation.entity_manager.get_player()
+        {
+            targeting.draw(buffer, area, &self.camera, &self.simulation.map, player);
+        }
 
         // draws menu
         if self.menu.visible {
             self.menu.draw(frame, area, &self);
         }
+
+        if let Mode::Inventory = self.mode
+            && let Some(player) = self.simulation.entity_manager.get_player()
+        {
+            screens::inventory::draw(frame, area, player);
+        }
+
+        if let Mode::Spellbook = self.mode
+            && let Some(player) = self.simulation.entity_manager.get_player()
+        {
+            screens::spellbook::draw(frame, area, player);
+        }
+
+        if let Mode::SkillTree = self.mode
+            && let Some(player) = self.simulation.entity_manager.get_player()
+        {
+            screens::skill_tree::draw(frame, area, player);
+        }
+
+        if let Mode::Crafting = self.mode
+            && let Some(player) = self.simulation.entity_manager.get_player()
+        {
+            screens::crafting::draw(frame, area, player, &self.simulation.map);
+        }
+
+        if let Mode::Evolution(spell_index) = self.mode
+            && let Some(player) = self.simulation.entity_manager.get_player()
+        {
+            screens::evolution::draw(frame, area, player, spell_index);
+        }
+
+        if let Mode::Console = self.mode {
+            self.console.draw(frame, area);
+        }
     }
 }
 
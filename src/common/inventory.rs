@@ -12,4 +12,40 @@ impl Inventory {
     pub fn add(&mut self, item: Item) {
         self.items.push(item);
     }
+
+    /// puts the item back at the index, or at the end if the index is out of bounds
+    pub fn insert(&mut self, index: usize, item: Item) {
+        let index = index.min(self.items.len());
+        self.items.insert(index, item);
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<Item> {
+        if index < self.items.len() {
+            Some(self.items.remove(index))
+        } else {
+            None
+        }
+    }
+
+    pub fn items(&self) -> &[Item] {
+        &self.items
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// removes the items matching the predicate and returns them
+    pub fn take_if(&mut self, predicate: impl Fn(&Item) -> bool) -> Vec<Item> {
+        let (taken, kept) = std::mem::take(&mut self.items)
+            .into_iter()
+            .partition(|item| predicate(item));
+        self.items = kept;
+        taken
+    }
+
+    /// removes every item and returns them
+    pub fn take_all(&mut self) -> Vec<Item> {
+        std::mem::take(&mut self.items)
+    }
 }
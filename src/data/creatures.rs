@@ -0,0 +1,143 @@
+use std::sync::OnceLock;
+
+use ratatui::style::Color;
+use serde::Deserialize;
+
+use crate::{actions::resistances::Resistances, entities::entity::EntityKind};
+
+use super::{
+    mods::{self, Definition, Location, Source},
+    spells::spells,
+};
+
+pub const CREATURES_FILE: &str = "data/creatures.toml";
+pub const CREATURES_CONTENT: &str = include_str!("../../data/creatures.toml");
+
+#[derive(Deserialize)]
+pub struct CreatureDefinition {
+    pub id: String,
+    pub name: String,
+    /// creature of the game whose attacks, spells and level it takes
+    pub kind: EntityKind,
+    pub symbol: String,
+    /// [red, green, blue]
+    pub color: [u8; 3],
+    pub hp: u32,
+    pub mana: u32,
+    pub defense: u32,
+    pub strength: u32,
+    pub magic: u32,
+    #[serde(default)]
+    pub resistances: Resistances,
+    /// ids of the spells in data/spells.toml, the ones of the kind if None
+    pub spells: Option<Vec<String>>,
+    #[serde(skip)]
+    pub location: Location,
+}
+
+impl CreatureDefinition {
+    pub fn color(&self) -> Color {
+        let [r, g, b] = self.color;
+        Color::Rgb(r, g, b)
+    }
+}
+
+impl Definition for CreatureDefinition {
+    const TABLE: &'static str = "creature";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn location(&self) -> &Location {
+        &self.location
+    }
+
+    fn set_location(&mut self, location: Location) {
+        self.location = location;
+    }
+}
+
+pub struct CreatureRegistry {
+    creatures: Vec<CreatureDefinition>,
+}
+
+impl CreatureRegistry {
+    /// merges the creatures files of the game and the mods and checks that every kind has its creature and that the spells exist
+    pub fn load(sources: &[Source]) -> Result<Self, Vec<String>> {
+        let creatures: Vec<CreatureDefinition> = mods::merge(sources)?;
+        let mut errors = Vec::new();
+        for kind in EntityKind::ALL {
+            if !creatures.iter().any(|creature| creature.id == kind.id()) {
+                errors.push(format!(
+                    "{CREATURES_FILE}:1: the creature {} of the game is missing",
+                    kind.id()
+                ));
+            }
+        }
+        for creature in creatures.iter() {
+            if creature.symbol.chars().count() != 1 {
+                errors.push(format!(
+                    "{}: creature {} needs a symbol of one character",
+                    creature.location, creature.id
+                ));
+            }
+            for id in creature.spells.iter().flatten() {
+                if spells().get(id).is_none() {
+                    errors.push(format!(
+                        "{}: creature {} knows the unknown spell {}",
+                        creature.location, creature.id, id
+                    ));
+                }
+            }
+        }
+        if errors.is_empty() {
+            Ok(Self { creatures })
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn all(&self) -> &[CreatureDefinition] {
+        &self.creatures
+    }
+
+    pub fn get(&self, id: &str) -> Option<&CreatureDefinition> {
+        self.creatures.iter().find(|creature| creature.id == id)
+    }
+
+    /// the creature with the id or the name, ignoring the case
+    pub fn find(&self, name: &str) -> Option<&CreatureDefinition> {
+        self.creatures.iter().find(|creature| {
+            creature.id.eq_ignore_ascii_case(name) || creature.name.eq_ignore_ascii_case(name)
+        })
+    }
+
+    /// the creature of the game of the kind, always defined since the registry checks it
+    pub fn of_kind(&self, kind: EntityKind) -> &CreatureDefinition {
+        match self.get(kind.id()) {
+            Some(creature) => creature,
+            None => panic!("missing creature {}", kind.id()),
+        }
+    }
+}
+
+static REGISTRY: OnceLock<CreatureRegistry> = OnceLock::new();
+
+/// returns the creatures described in data/creatures.toml and in the loaded mods
+pub fn creatures() -> &'static CreatureRegistry {
+    REGISTRY.get_or_init(|| {
+        CreatureRegistry::load(&[Source::game(CREATURES_FILE, CREATURES_CONTENT)])
+            .unwrap_or_else(|errors| panic!("invalid {CREATURES_FILE}: {}", errors.join(", ")))
+    })
+}
+
+/// replaces the creatures of the game by the ones merged with the mods, before anything reads them
+pub fn install(sources: &[Source]) -> Result<(), Vec<String>> {
+    let registry = CreatureRegistry::load(sources)?;
+    REGISTRY.set(registry).map_err(|_| {
+        vec![format!(
+            "{CREATURES_FILE}: the creatures were read before the mods loaded"
+        )]
+    })
+}
@@ -0,0 +1,161 @@
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+use super::mods::{self, Definition, Location, Source};
+use crate::{
+    actions::resistances::Resistances,
+    items::item::{ArmorKind, ConsumableEffect, ConsumableKind, Item, ItemKind, WeaponKind},
+};
+
+pub const ITEMS_FILE: &str = "data/items.toml";
+pub const ITEMS_CONTENT: &str = include_str!("../../data/items.toml");
+
+#[derive(Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum ItemKindDefinition {
+    Weapon {
+        weapon: WeaponKind,
+        strength: u32,
+    },
+    Armor {
+        armor: ArmorKind,
+        defense: u32,
+        #[serde(default)]
+        warmth: i32,
+    },
+    Consumable {
+        consumable: ConsumableKind,
+        effect: ConsumableEffect,
+        charges: u32,
+    },
+    Material,
+}
+
+#[derive(Deserialize)]
+pub struct ItemDefinition {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    #[serde(flatten)]
+    pub kind: ItemKindDefinition,
+    #[serde(skip)]
+    pub location: Location,
+}
+
+impl Definition for ItemDefinition {
+    const TABLE: &'static str = "item";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn location(&self) -> &Location {
+        &self.location
+    }
+
+    fn set_location(&mut self, location: Location) {
+        self.location = location;
+    }
+}
+
+impl ItemDefinition {
+    pub fn build(&self) -> Item {
+        let (name, description) = (self.name.clone(), self.description.clone());
+        match &self.kind {
+            ItemKindDefinition::Weapon { weapon, strength } => {
+                Item::new_weapon(name, description, *weapon, *strength)
+            }
+            ItemKindDefinition::Armor {
+                armor,
+                defense,
+                warmth,
+            } => Item::new_armor(
+                name,
+                description,
+                *armor,
+                *defense,
+                Resistances::default(),
+                *warmth,
+            ),
+            ItemKindDefinition::Consumable {
+                consumable,
+                effect,
+                charges,
+            } => Item::new_consumable(name, description, *consumable, effect.clone(), *charges),
+            ItemKindDefinition::Material => Item::new(name, description, ItemKind::Material),
+        }
+    }
+
+    /// returns true if the item was built from this definition
+    pub fn matches(&self, item: &Item) -> bool {
+        item.name == self.name
+    }
+}
+
+pub struct ItemRegistry {
+    items: Vec<ItemDefinition>,
+}
+
+impl ItemRegistry {
+    /// merges the items files of the game and the mods and checks that the names are unique
+    pub fn load(sources: &[Source]) -> Result<Self, Vec<String>> {
+        let items: Vec<ItemDefinition> = mods::merge(sources)?;
+        let mut errors = Vec::new();
+        for (index, item) in items.iter().enumerate() {
+            if let Some(other) = items[..index].iter().find(|other| other.name == item.name) {
+                errors.push(format!(
+                    "{}: item {} reuses the name {} of {}",
+                    item.location, item.id, item.name, other.location
+                ));
+            }
+            if let ItemKindDefinition::Consumable { charges: 0, .. } = item.kind {
+                errors.push(format!(
+                    "{}: consumable {} needs at least one charge",
+                    item.location, item.id
+                ));
+            }
+        }
+        if errors.is_empty() {
+            Ok(Self { items })
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn all(&self) -> &[ItemDefinition] {
+        &self.items
+    }
+
+    pub fn get(&self, id: &str) -> Option<&ItemDefinition> {
+        self.items.iter().find(|item| item.id == id)
+    }
+
+    /// builds the item, panics if the id is unknown since the ids come from the code or checked data
+    pub fn build(&self, id: &str) -> Item {
+        match self.get(id) {
+            Some(definition) => definition.build(),
+            None => panic!("unknown item {id}"),
+        }
+    }
+}
+
+static REGISTRY: OnceLock<ItemRegistry> = OnceLock::new();
+
+/// returns the items described in data/items.toml and in the loaded mods
+pub fn items() -> &'static ItemRegistry {
+    REGISTRY.get_or_init(|| {
+        ItemRegistry::load(&[Source::game(ITEMS_FILE, ITEMS_CONTENT)])
+            .unwrap_or_else(|errors| panic!("invalid {ITEMS_FILE}: {}", errors.join(", ")))
+    })
+}
+
+/// replaces the items of the game by the ones merged with the mods, before anything reads them
+pub fn install(sources: &[Source]) -> Result<(), Vec<String>> {
+    let registry = ItemRegistry::load(sources)?;
+    REGISTRY.set(registry).map_err(|_| {
+        vec![format!(
+            "{ITEMS_FILE}: the items were read before the mods loaded"
+        )]
+    })
+}
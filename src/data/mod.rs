@@ -0,0 +1,10 @@
+pub mod creatures;
+pub mod items;
+pub mod mods;
+pub mod perks;
+pub mod progression;
+pub mod recipes;
+pub mod spawns;
+pub mod spells;
+pub mod structures;
+pub mod tiles;
@@ -0,0 +1,553 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt, fs,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use toml::Spanned;
+
+use crate::{items::item::ConsumableEffect, systems::scripting};
+
+use super::{
+    creatures,
+    items::{self, ItemKindDefinition},
+    recipes, spawns, structures, tiles,
+};
+
+/// directory of the mods, each one a directory with a mod.toml manifest and the data files it adds or overrides
+pub const MODS_DIRECTORY: &str = "mods";
+/// ids of the enabled mods in the mods directory, written by the mod selection screen
+const ENABLED_FILE: &str = "enabled.toml";
+/// variable naming the directory holding the mods directory, instead of looking for it from the executable
+pub const DATA_DIRECTORY_VARIABLE: &str = "MIREVEIL_DATA";
+const MANIFEST_FILE: &str = "mod.toml";
+/// directory of the scripts of a mod, next to its manifest
+pub const MOD_SCRIPTS_DIRECTORY: &str = "scripts";
+
+/// file and line of a definition, shown in the errors
+#[derive(Clone, Default)]
+pub struct Location {
+    pub path: String,
+    pub line: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.path, self.line)
+    }
+}
+
+/// line of the byte offset in the content, starting at 1
+fn line_of(content: &str, offset: usize) -> usize {
+    content[..offset.min(content.len())].matches('\n').count() + 1
+}
+
+/// mod.toml of a mod
+#[derive(Deserialize)]
+pub struct Manifest {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    /// ids of the mods loaded before this one, whose definitions it may override
+    #[serde(default)]
+    pub dependencies: Vec<Spanned<String>>,
+    /// a mod overrides the definitions of the mods with a lower load order
+    #[serde(default)]
+    pub load_order: i32,
+    /// directory of the mod, holding its data files
+    #[serde(skip)]
+    pub directory: PathBuf,
+}
+
+impl Manifest {
+    pub fn dependency_ids(&self) -> impl Iterator<Item = &str> {
+        self.dependencies
+            .iter()
+            .map(|dependency| dependency.get_ref().as_str())
+    }
+
+    fn path(&self) -> String {
+        self.directory.join(MANIFEST_FILE).display().to_string()
+    }
+}
+
+/// a data file of the game or of a mod
+pub struct Source {
+    /// path shown in the errors
+    pub path: String,
+    pub content: String,
+    /// the mod of the file, None for the game
+    owner: Option<Owner>,
+}
+
+struct Owner {
+    id: String,
+    load_order: i32,
+    /// ids of the mods it depends on, directly or not
+    dependencies: HashSet<String>,
+}
+
+impl Source {
+    /// a data file of the game, embedded in the executable
+    pub fn game(path: &str, content: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            content: content.to_string(),
+            owner: None,
+        }
+    }
+
+    /// returns true if a definition of this source may replace the one of the other source
+    fn overrides(&self, other: &Source) -> Result<bool, ()> {
+        let (Some(owner), Some(other_owner)) = (&self.owner, &other.owner) else {
+            // the definitions of the game are overridden by every mod
+            return Ok(self.owner.is_some());
+        };
+        if owner.dependencies.contains(&other_owner.id) {
+            Ok(true)
+        } else if owner.load_order != other_owner.load_order {
+            Ok(owner.load_order > other_owner.load_order)
+        } else {
+            Err(())
+        }
+    }
+}
+
+/// a definition of a data file, identified by its id so that a mod can override it
+pub trait Definition: DeserializeOwned {
+    /// name of the tables of the definitions, "item" for [[item]]
+    const TABLE: &'static str;
+
+    fn id(&self) -> &str;
+
+    fn location(&self) -> &Location;
+
+    fn set_location(&mut self, location: Location);
+}
+
+/// parses the definitions of the sources, a mod replacing the definitions of the game, of its dependencies and of the mods with a lower load order
+pub fn merge<T: Definition>(sources: &[Source]) -> Result<Vec<T>, Vec<String>> {
+    let mut errors = Vec::new();
+    // the definitions with the index of their source, an override keeping the place of the definition
+    let mut definitions: Vec<(T, usize)> = Vec::new();
+    for (source_index, source) in sources.iter().enumerate() {
+        let tables: BTreeMap<String, Spanned<Vec<Spanned<T>>>> =
+            match toml::from_str(&source.content) {
+                Ok(tables) => tables,
+                Err(error) => {
+                    let line = error
+                        .span()
+                        .map_or(1, |span| line_of(&source.content, span.start));
+                    errors.push(format!("{}:{line}: {}", source.path, error.message()));
+                    continue;
+                }
+            };
+        for (name, table) in tables {
+            if name != T::TABLE {
+                let line = line_of(&source.content, table.span().start);
+                errors.push(format!(
+                    "{}:{line}: unknown table {name}, expected [[{}]]",
+                    source.path,
+                    T::TABLE
+                ));
+                continue;
+            }
+            for definition in table.into_inner() {
+                let line = line_of(&source.content, definition.span().start);
+                let mut definition = definition.into_inner();
+                definition.set_location(Location {
+                    path: source.path.clone(),
+                    line,
+                });
+                let Some(index) = definitions
+                    .iter()
+                    .position(|(other, _)| other.id() == definition.id())
+                else {
+                    definitions.push((definition, source_index));
+                    continue;
+                };
+                let (other, other_source) = &definitions[index];
+                if *other_source == source_index {
+                    errors.push(format!(
+                        "{}: {} {} is already defined at line {}",
+                        definition.location(),
+                        T::TABLE,
+                        definition.id(),
+                        other.location().line
+                    ));
+                    continue;
+                }
+                match source.overrides(&sources[*other_source]) {
+                    Ok(true) => definitions[index] = (definition, source_index),
+                    Ok(false) => {}
+                    Err(()) => errors.push(format!(
+                        "{}: {} {} is also defined by {}, the mods having the same load order and no dependency between them",
+                        definition.location(),
+                        T::TABLE,
+                        definition.id(),
+                        other.location()
+                    )),
+                }
+            }
+        }
+    }
+    if errors.is_empty() {
+        Ok(definitions
+            .into_iter()
+            .map(|(definition, _)| definition)
+            .collect())
+    } else {
+        Err(errors)
+    }
+}
+
+/// returns the mods directory in the directory named by MIREVEIL_DATA, else in the closest directory holding one from the executable up (the root of the repository with cargo run), else next to the executable; never relative to the working directory
+pub fn mods_directory() -> &'static Path {
+    static DIRECTORY: OnceLock<PathBuf> = OnceLock::new();
+    DIRECTORY.get_or_init(|| {
+        if let Some(directory) = std::env::var_os(DATA_DIRECTORY_VARIABLE) {
+            return PathBuf::from(directory).join(MODS_DIRECTORY);
+        }
+        let executable = std::env::current_exe()
+            .and_then(|path| path.canonicalize())
+            .unwrap_or_default();
+        let Some(directory) = executable.parent() else {
+            return PathBuf::from(MODS_DIRECTORY);
+        };
+        directory
+            .ancestors()
+            .map(|directory| directory.join(MODS_DIRECTORY))
+            .find(|directory| directory.is_dir())
+            .unwrap_or_else(|| directory.join(MODS_DIRECTORY))
+    })
+}
+
+/// returns the file of the enabled mods, in the mods directory
+pub fn enabled_file() -> PathBuf {
+    mods_directory().join(ENABLED_FILE)
+}
+
+/// the mods of the mods directory sorted by id, and the errors of the ones that can't be read
+pub fn installed() -> (Vec<Manifest>, Vec<String>) {
+    let mut manifests: Vec<Manifest> = Vec::new();
+    let mut errors = Vec::new();
+    let Ok(entries) = fs::read_dir(mods_directory()) else {
+        return (manifests, errors);
+    };
+    for entry in entries.flatten() {
+        let directory = entry.path();
+        let path = directory.join(MANIFEST_FILE);
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        match toml::from_str::<Manifest>(&content) {
+            Ok(mut manifest) => {
+                if let Some(other) = manifests.iter().find(|other| other.id == manifest.id) {
+                    errors.push(format!(
+                        "{}:1: the id {} is already used by {}",
+                        path.display(),
+                        manifest.id,
+                        other.path()
+                    ));
+                    continue;
+                }
+                manifest.directory = directory;
+                manifests.push(manifest);
+            }
+            Err(error) => {
+                let line = error.span().map_or(1, |span| line_of(&content, span.start));
+                errors.push(format!("{}:{line}: {}", path.display(), error.message()));
+            }
+        }
+    }
+    manifests.sort_by(|a, b| a.id.cmp(&b.id));
+    (manifests, errors)
+}
+
+#[derive(Default, Deserialize, Serialize)]
+struct EnabledFile {
+    #[serde(default)]
+    enabled: Vec<String>,
+}
+
+/// ids of the mods enabled on the mod selection screen, none if the file doesn't exist
+pub fn enabled() -> Result<Vec<String>, String> {
+    let path = enabled_file();
+    match fs::read_to_string(&path) {
+        Ok(content) => toml::from_str::<EnabledFile>(&content)
+            .map(|file| file.enabled)
+            .map_err(|error| {
+                let line = error.span().map_or(1, |span| line_of(&content, span.start));
+                format!("{}:{line}: {}", path.display(), error.message())
+            }),
+        Err(_) => Ok(Vec::new()),
+    }
+}
+
+pub fn save_enabled(ids: &[String]) -> Result<(), String> {
+    let content = toml::to_string(&EnabledFile {
+        enabled: ids.to_vec(),
+    })
+    .map_err(|e| e.to_string())?;
+    let path = enabled_file();
+    fs::create_dir_all(mods_directory()).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+/// sorts the enabled mods so that a mod comes after its dependencies and else by load order, the missing dependencies and the cycles being errors
+pub fn load_order(
+    installed: Vec<Manifest>,
+    enabled: &[String],
+) -> Result<Vec<Manifest>, Vec<String>> {
+    let mut errors = Vec::new();
+    for id in enabled {
+        if !installed.iter().any(|manifest| &manifest.id == id) {
+            errors.push(format!(
+                "{}:1: the mod {id} isn't installed in {}",
+                enabled_file().display(),
+                mods_directory().display()
+            ));
+        }
+    }
+    let mut remaining: Vec<Manifest> = installed
+        .into_iter()
+        .filter(|manifest| enabled.contains(&manifest.id))
+        .collect();
+    for manifest in remaining.iter() {
+        let content =
+            fs::read_to_string(manifest.directory.join(MANIFEST_FILE)).unwrap_or_default();
+        for dependency in manifest.dependencies.iter() {
+            if !remaining
+                .iter()
+                .any(|other| &other.id == dependency.get_ref())
+            {
+                errors.push(format!(
+                    "{}:{}: the dependency {} isn't enabled",
+                    manifest.path(),
+                    line_of(&content, dependency.span().start),
+                    dependency.get_ref()
+                ));
+            }
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let mut sorted: Vec<Manifest> = Vec::new();
+    while !remaining.is_empty() {
+        // the mod with the lowest load order among the ones whose dependencies are loaded
+        let next = remaining
+            .iter()
+            .enumerate()
+            .filter(|(_, manifest)| {
+                manifest
+                    .dependency_ids()
+                    .all(|id| sorted.iter().any(|loaded| loaded.id == id))
+            })
+            .min_by_key(|(_, manifest)| (manifest.load_order, manifest.id.clone()))
+            .map(|(index, _)| index);
+        match next {
+            Some(index) => sorted.push(remaining.remove(index)),
+            None => {
+                return Err(remaining
+                    .iter()
+                    .map(|manifest| {
+                        format!(
+                            "{}:1: the mod {} is part of a dependency cycle",
+                            manifest.path(),
+                            manifest.id
+                        )
+                    })
+                    .collect());
+            }
+        }
+    }
+    Ok(sorted)
+}
+
+/// the data file of the game followed by the ones of the mods, in their load order
+fn sources(file: &str, game_content: &str, mods: &[Manifest]) -> Vec<Source> {
+    let name = Path::new(file).file_name().unwrap_or_default();
+    let mut sources = vec![Source::game(file, game_content)];
+    for (index, manifest) in mods.iter().enumerate() {
+        let path = manifest.directory.join(name);
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let mut dependencies = HashSet::new();
+        let mut pending: Vec<&str> = manifest.dependency_ids().collect();
+        while let Some(id) = pending.pop() {
+            if dependencies.insert(id.to_string())
+                && let Some(dependency) = mods[..index].iter().find(|other| other.id == id)
+            {
+                pending.extend(dependency.dependency_ids());
+            }
+        }
+        sources.push(Source {
+            path: path.display().to_string(),
+            content,
+            owner: Some(Owner {
+                id: manifest.id.clone(),
+                load_order: manifest.load_order,
+                dependencies,
+            }),
+        });
+    }
+    sources
+}
+
+static ACTIVE: OnceLock<Vec<Manifest>> = OnceLock::new();
+
+/// the mods loaded at startup, in their load order
+pub fn active() -> &'static [Manifest] {
+    ACTIVE.get_or_init(Vec::new)
+}
+
+/// directories of the scripts of the loaded mods, a script of a later directory replacing the one with the same name
+pub fn script_directories() -> Vec<PathBuf> {
+    active()
+        .iter()
+        .map(|manifest| manifest.directory.join(MOD_SCRIPTS_DIRECTORY))
+        .collect()
+}
+
+/// loads the data of the game merged with the data of the enabled mods, before anything reads the data; returns every error with its file and line
+pub fn load() -> Result<(), Vec<String>> {
+    let (installed, mut errors) = installed();
+    let enabled = enabled().map_err(|error| vec![error])?;
+    let mods = match load_order(installed, &enabled) {
+        Ok(mods) => mods,
+        Err(order_errors) => {
+            errors.extend(order_errors);
+            return Err(errors);
+        }
+    };
+
+    // the registries are installed in the order of their references, a registry referring to one that failed being skipped
+    let items = items::install(&sources(items::ITEMS_FILE, items::ITEMS_CONTENT, &mods));
+    let creatures = creatures::install(&sources(
+        creatures::CREATURES_FILE,
+        creatures::CREATURES_CONTENT,
+        &mods,
+    ));
+    let mut results = vec![];
+    if items.is_ok() {
+        results.push(recipes::install(&sources(
+            recipes::RECIPES_FILE,
+            recipes::RECIPES_CONTENT,
+            &mods,
+        )));
+        results.push(tiles::install(&sources(
+            tiles::TILES_FILE,
+            tiles::TILES_CONTENT,
+            &mods,
+        )));
+        results.push(structures::install(&sources(
+            structures::STRUCTURES_FILE,
+            structures::STRUCTURES_CONTENT,
+            &mods,
+        )));
+    }
+    if creatures.is_ok() {
+        results.push(spawns::install(&sources(
+            spawns::SPAWNS_FILE,
+            spawns::SPAWNS_CONTENT,
+            &mods,
+        )));
+    }
+    for result in [items, creatures].into_iter().chain(results) {
+        if let Err(registry_errors) = result {
+            errors.extend(registry_errors);
+        }
+    }
+    let _ = ACTIVE.set(mods);
+    errors.extend(missing_scripts());
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// the items whose effect is a script that neither the game embeds nor a loaded mod ships
+fn missing_scripts() -> Vec<String> {
+    let directories = script_directories();
+    items::items()
+        .all()
+        .iter()
+        .filter_map(|item| match &item.kind {
+            ItemKindDefinition::Consumable {
+                effect: ConsumableEffect::Script(script),
+                ..
+            } if !scripting::is_game_script(script)
+                && !directories
+                    .iter()
+                    .any(|directory| directory.join(format!("{script}.rhai")).is_file()) =>
+            {
+                Some(format!(
+                    "{}: item {} runs the unknown script {script}",
+                    item.location, item.id
+                ))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::items::ItemDefinition;
+
+    fn source(id: &str, load_order: i32, dependencies: &[&str], content: &str) -> Source {
+        Source {
+            path: format!("mods/{id}/items.toml"),
+            content: content.to_string(),
+            owner: Some(Owner {
+                id: id.to_string(),
+                load_order,
+                dependencies: dependencies.iter().map(|id| id.to_string()).collect(),
+            }),
+        }
+    }
+
+    const GAME: &str =
+        "[[item]]\nid = \"wood\"\nname = \"Wood\"\ndescription = \"\"\nkind = \"material\"\n";
+    const MOD: &str = "# more wood\n\n[[item]]\nid = \"wood\"\nname = \"Oak\"\ndescription = \"\"\nkind = \"material\"\n";
+
+    #[test]
+    fn mod_overrides_the_game() {
+        let sources = [
+            Source::game("data/items.toml", GAME),
+            source("oak", 0, &[], MOD),
+        ];
+        let items = merge::<ItemDefinition>(&sources).unwrap_or_default();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].name, "Oak");
+    }
+
+    #[test]
+    fn mods_with_the_same_load_order_conflict() {
+        let sources = [
+            Source::game("data/items.toml", GAME),
+            source("oak", 0, &[], MOD),
+            source("pine", 0, &[], MOD),
+        ];
+        let errors = merge::<ItemDefinition>(&sources).err().unwrap_or_default();
+        assert_eq!(
+            errors,
+            [
+                "mods/pine/items.toml:3: item wood is also defined by mods/oak/items.toml:3, the mods having the same load order and no dependency between them"
+            ]
+        );
+        // the dependency decides
+        let sources = [source("oak", 0, &[], MOD), source("pine", 0, &["oak"], MOD)];
+        assert!(merge::<ItemDefinition>(&sources).is_ok());
+    }
+}
@@ -0,0 +1,135 @@
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+use crate::{
+    actions::action::ActionType,
+    entities::character::{Attribute, Class, Race},
+};
+
+use super::spells::spells;
+
+const PERKS_FILE: &str = include_str!("../../data/perks.toml");
+
+/// what a perk grants once taken
+#[derive(Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PerkEffect {
+    Stat { attribute: Attribute, amount: i32 },
+    Resistance { element: ActionType, amount: i32 },
+    Spell { spell: String },
+    HpRegeneration { amount: u32 },
+    ManaRegeneration { percent: u32 },
+    XpBonus { percent: u32 },
+}
+
+#[derive(Deserialize)]
+pub struct PerkDefinition {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    /// perks to take first
+    #[serde(default)]
+    pub requires: Vec<String>,
+    /// races allowed to take the perk, every race if empty
+    #[serde(default)]
+    pub races: Vec<Race>,
+    /// classes allowed to take the perk, every class if empty
+    #[serde(default)]
+    pub classes: Vec<Class>,
+    pub effects: Vec<PerkEffect>,
+}
+
+/// whether an Entity can take a perk
+#[derive(Clone, Copy, PartialEq)]
+pub enum PerkStatus {
+    Taken,
+    Available,
+    /// some prerequisites are missing
+    Locked,
+}
+
+impl PerkDefinition {
+    pub fn status(&self, taken: &[String]) -> PerkStatus {
+        if taken.contains(&self.id) {
+            PerkStatus::Taken
+        } else if self
+            .requires
+            .iter()
+            .all(|required| taken.contains(required))
+        {
+            PerkStatus::Available
+        } else {
+            PerkStatus::Locked
+        }
+    }
+
+    /// returns true if the race and the class are allowed to take the perk
+    pub fn is_allowed_for(&self, race: Option<Race>, class: Option<Class>) -> bool {
+        let race_allowed = self.races.is_empty() || race.is_some_and(|r| self.races.contains(&r));
+        let class_allowed =
+            self.classes.is_empty() || class.is_some_and(|c| self.classes.contains(&c));
+        race_allowed && class_allowed
+    }
+}
+
+#[derive(Deserialize)]
+struct PerksFile {
+    #[serde(default)]
+    perk: Vec<PerkDefinition>,
+}
+
+pub struct PerkRegistry {
+    perks: Vec<PerkDefinition>,
+}
+
+impl PerkRegistry {
+    /// parses a perks file and checks that the prerequisites and spells refer to known definitions
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let file: PerksFile = toml::from_str(content).map_err(|e| e.to_string())?;
+        for (index, perk) in file.perk.iter().enumerate() {
+            if file.perk[..index].iter().any(|other| other.id == perk.id) {
+                return Err(format!("perk {} is defined twice", perk.id));
+            }
+            for required in &perk.requires {
+                if !file.perk.iter().any(|other| &other.id == required) {
+                    return Err(format!(
+                        "perk {} requires the unknown perk {}",
+                        perk.id, required
+                    ));
+                }
+            }
+            for effect in &perk.effects {
+                if let PerkEffect::Spell { spell } = effect
+                    && spells().get(spell).is_none()
+                {
+                    return Err(format!(
+                        "perk {} grants the unknown spell {}",
+                        perk.id, spell
+                    ));
+                }
+            }
+        }
+        Ok(Self { perks: file.perk })
+    }
+
+    pub fn all(&self) -> &[PerkDefinition] {
+        &self.perks
+    }
+
+    pub fn get(&self, index: usize) -> Option<&PerkDefinition> {
+        self.perks.get(index)
+    }
+
+    pub fn find(&self, id: &str) -> Option<&PerkDefinition> {
+        self.perks.iter().find(|perk| perk.id == id)
+    }
+}
+
+/// returns the perks described in data/perks.toml
+pub fn perks() -> &'static PerkRegistry {
+    static REGISTRY: OnceLock<PerkRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        PerkRegistry::parse(PERKS_FILE).unwrap_or_else(|e| panic!("invalid data/perks.toml: {e}"))
+    })
+}
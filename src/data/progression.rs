@@ -0,0 +1,137 @@
+use std::{collections::HashMap, sync::OnceLock};
+
+use serde::Deserialize;
+
+use crate::{
+    entities::{
+        character::{Class, Race},
+        entity::EntityKind,
+    },
+    systems::level_manager::{ExponentialCurve, LinearCurve, PolynomialCurve, TableCurve, XPCurve},
+};
+
+const PROGRESSION_FILE: &str = include_str!("../../data/progression.toml");
+
+#[derive(Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum CurveDefinition {
+    Exponential { base: u32, exponent: f32 },
+    Linear { base: u32, increment: u32 },
+    Polynomial { coefficients: Vec<f32> },
+    Table { xp: Vec<u32> },
+}
+
+impl CurveDefinition {
+    pub fn build(&self) -> Box<dyn XPCurve> {
+        match self {
+            CurveDefinition::Exponential { base, exponent } => {
+                Box::new(ExponentialCurve::new(*base, *exponent))
+            }
+            CurveDefinition::Linear { base, increment } => {
+                Box::new(LinearCurve::new(*base, *increment))
+            }
+            CurveDefinition::Polynomial { coefficients } => {
+                Box::new(PolynomialCurve::new(coefficients.clone()))
+            }
+            CurveDefinition::Table { xp } => Box::new(TableCurve::new(xp.clone())),
+        }
+    }
+
+    /// a curve requiring no xp would level up forever
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            CurveDefinition::Exponential { base: 0, .. } => {
+                Err("an exponential curve needs a base above 0".to_string())
+            }
+            CurveDefinition::Table { xp } if xp.is_empty() => {
+                Err("a table curve needs at least one value".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct MonsterLevels {
+    pub base_levels: HashMap<EntityKind, u32>,
+    pub levels_per_depth: u32,
+    pub distance_per_level: u32,
+    pub max_level: u32,
+}
+
+#[derive(Deserialize)]
+pub struct XpDrop {
+    pub base: u32,
+    pub per_level: u32,
+    pub level_difference_percent: u32,
+    pub min_percent: u32,
+    pub max_percent: u32,
+}
+
+#[derive(Deserialize)]
+pub struct Progression {
+    pub default_curve: CurveDefinition,
+    pub spell_curve: CurveDefinition,
+    #[serde(default)]
+    pub race_curves: HashMap<Race, CurveDefinition>,
+    #[serde(default)]
+    pub class_curves: HashMap<Class, CurveDefinition>,
+    pub monster_levels: MonsterLevels,
+    pub xp_drop: XpDrop,
+}
+
+impl Progression {
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let progression: Progression = toml::from_str(content).map_err(|e| e.to_string())?;
+        let curves = [&progression.default_curve, &progression.spell_curve]
+            .into_iter()
+            .chain(progression.race_curves.values())
+            .chain(progression.class_curves.values());
+        for curve in curves {
+            curve.validate()?;
+        }
+        if progression.monster_levels.distance_per_level == 0 {
+            return Err("distance_per_level must be above 0".to_string());
+        }
+        Ok(progression)
+    }
+
+    /// returns the curve of the class, or else of the race, or else the default one
+    pub fn curve_for(&self, race: Option<Race>, class: Option<Class>) -> Box<dyn XPCurve> {
+        class
+            .and_then(|class| self.class_curves.get(&class))
+            .or_else(|| race.and_then(|race| self.race_curves.get(&race)))
+            .unwrap_or(&self.default_curve)
+            .build()
+    }
+
+    /// level of a creature spawning at the position, higher deep underground, high in the sky and far from the origin
+    pub fn monster_level(&self, kind: EntityKind, position: (i32, i32, i32)) -> u32 {
+        let config = &self.monster_levels;
+        let base_level = config.base_levels.get(&kind).copied().unwrap_or(1);
+        let depth = position.2.unsigned_abs();
+        let distance = ((position.0 as f64).powi(2) + (position.1 as f64).powi(2)).sqrt() as u32;
+        let level =
+            base_level + depth * config.levels_per_depth + distance / config.distance_per_level;
+        level.clamp(1, config.max_level.max(1))
+    }
+
+    /// xp dropped by the victim, more if it had a higher level than the killer and less otherwise
+    pub fn xp_drop(&self, victim_level: u32, killer_level: u32) -> u32 {
+        let config = &self.xp_drop;
+        let base = config.base + config.per_level * victim_level;
+        let difference = victim_level as i64 - killer_level as i64;
+        let percent = (100 + difference * config.level_difference_percent as i64)
+            .clamp(config.min_percent as i64, config.max_percent as i64);
+        (base as i64 * percent / 100) as u32
+    }
+}
+
+/// returns the progression settings described in data/progression.toml
+pub fn progression() -> &'static Progression {
+    static PROGRESSION: OnceLock<Progression> = OnceLock::new();
+    PROGRESSION.get_or_init(|| {
+        Progression::parse(PROGRESSION_FILE)
+            .unwrap_or_else(|e| panic!("invalid data/progression.toml: {e}"))
+    })
+}
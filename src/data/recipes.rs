@@ -0,0 +1,163 @@
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+use crate::{
+    entities::character::Attribute, items::item::ConsumableEffect,
+    map::structures::structure::WorkbenchKind,
+};
+
+use super::{
+    items::{ItemKindDefinition, items},
+    mods::{self, Definition, Location, Source},
+};
+
+pub const RECIPES_FILE: &str = "data/recipes.toml";
+pub const RECIPES_CONTENT: &str = include_str!("../../data/recipes.toml");
+
+#[derive(Deserialize)]
+pub struct Component {
+    /// id of the item in data/items.toml
+    pub item: String,
+    #[serde(default = "default_count")]
+    pub count: u32,
+}
+
+fn default_count() -> u32 {
+    1
+}
+
+/// attribute value needed to craft an item
+#[derive(Deserialize)]
+pub struct SkillRequirement {
+    pub attribute: Attribute,
+    pub value: u32,
+}
+
+#[derive(Deserialize)]
+pub struct RecipeDefinition {
+    pub id: String,
+    pub name: String,
+    /// id of the crafted item
+    pub result: String,
+    #[serde(default)]
+    pub components: Vec<Component>,
+    /// items needed but not consumed
+    #[serde(default)]
+    pub tools: Vec<String>,
+    pub workbench: Option<WorkbenchKind>,
+    pub skill: Option<SkillRequirement>,
+    /// turns spent crafting
+    #[serde(default)]
+    pub time: u32,
+    /// known from the start, the other recipes have to be learned
+    #[serde(default)]
+    pub known: bool,
+    #[serde(skip)]
+    pub location: Location,
+}
+
+impl Definition for RecipeDefinition {
+    const TABLE: &'static str = "recipe";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn location(&self) -> &Location {
+        &self.location
+    }
+
+    fn set_location(&mut self, location: Location) {
+        self.location = location;
+    }
+}
+
+pub struct RecipeRegistry {
+    recipes: Vec<RecipeDefinition>,
+}
+
+impl RecipeRegistry {
+    /// merges the recipes files of the game and the mods and checks that the items exist and that the items teaching a recipe refer to a known one
+    pub fn load(sources: &[Source]) -> Result<Self, Vec<String>> {
+        let recipes: Vec<RecipeDefinition> = mods::merge(sources)?;
+        let mut errors = Vec::new();
+        for recipe in recipes.iter() {
+            let item_ids = std::iter::once(&recipe.result)
+                .chain(recipe.components.iter().map(|component| &component.item))
+                .chain(&recipe.tools);
+            for id in item_ids {
+                if items().get(id).is_none() {
+                    errors.push(format!(
+                        "{}: recipe {} refers to the unknown item {}",
+                        recipe.location, recipe.id, id
+                    ));
+                }
+            }
+            if recipe
+                .components
+                .iter()
+                .any(|component| component.count == 0)
+            {
+                errors.push(format!(
+                    "{}: recipe {} needs 0 of a component",
+                    recipe.location, recipe.id
+                ));
+            }
+        }
+        for item in items().all() {
+            if let ItemKindDefinition::Consumable {
+                effect: ConsumableEffect::LearnRecipe(id),
+                ..
+            } = &item.kind
+                && !recipes.iter().any(|recipe| &recipe.id == id)
+            {
+                errors.push(format!(
+                    "{}: item {} teaches the unknown recipe {}",
+                    item.location, item.id, id
+                ));
+            }
+        }
+        if errors.is_empty() {
+            Ok(Self { recipes })
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn all(&self) -> &[RecipeDefinition] {
+        &self.recipes
+    }
+
+    pub fn get(&self, index: usize) -> Option<&RecipeDefinition> {
+        self.recipes.get(index)
+    }
+
+    pub fn find(&self, id: &str) -> Option<&RecipeDefinition> {
+        self.recipes.iter().find(|recipe| recipe.id == id)
+    }
+
+    pub fn index_of(&self, id: &str) -> Option<usize> {
+        self.recipes.iter().position(|recipe| recipe.id == id)
+    }
+}
+
+static REGISTRY: OnceLock<RecipeRegistry> = OnceLock::new();
+
+/// returns the recipes described in data/recipes.toml and in the loaded mods
+pub fn recipes() -> &'static RecipeRegistry {
+    REGISTRY.get_or_init(|| {
+        RecipeRegistry::load(&[Source::game(RECIPES_FILE, RECIPES_CONTENT)])
+            .unwrap_or_else(|errors| panic!("invalid {RECIPES_FILE}: {}", errors.join(", ")))
+    })
+}
+
+/// replaces the recipes of the game by the ones merged with the mods, once the items are installed
+pub fn install(sources: &[Source]) -> Result<(), Vec<String>> {
+    let registry = RecipeRegistry::load(sources)?;
+    REGISTRY.set(registry).map_err(|_| {
+        vec![format!(
+            "{RECIPES_FILE}: the recipes were read before the mods loaded"
+        )]
+    })
+}
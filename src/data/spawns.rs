@@ -0,0 +1,103 @@
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+use super::{
+    creatures::{CreatureDefinition, creatures},
+    mods::{self, Definition, Location, Source},
+};
+
+pub const SPAWNS_FILE: &str = "data/spawns.toml";
+pub const SPAWNS_CONTENT: &str = include_str!("../../data/spawns.toml");
+
+/// chance of a creature to spawn, relative to the other ones
+#[derive(Deserialize)]
+pub struct SpawnDefinition {
+    /// id of the creature in data/creatures.toml, a mod overriding the weight of a creature with the same id
+    pub creature: String,
+    pub weight: f32,
+    #[serde(skip)]
+    pub location: Location,
+}
+
+impl Definition for SpawnDefinition {
+    const TABLE: &'static str = "spawn";
+
+    fn id(&self) -> &str {
+        &self.creature
+    }
+
+    fn location(&self) -> &Location {
+        &self.location
+    }
+
+    fn set_location(&mut self, location: Location) {
+        self.location = location;
+    }
+}
+
+pub struct SpawnTable {
+    spawns: Vec<SpawnDefinition>,
+}
+
+impl SpawnTable {
+    /// merges the spawns files of the game and the mods and checks that the creatures exist and that one of them can spawn
+    pub fn load(sources: &[Source]) -> Result<Self, Vec<String>> {
+        let spawns: Vec<SpawnDefinition> = mods::merge(sources)?;
+        let mut errors = Vec::new();
+        for spawn in spawns.iter() {
+            if creatures().get(&spawn.creature).is_none() {
+                errors.push(format!(
+                    "{}: the unknown creature {} spawns",
+                    spawn.location, spawn.creature
+                ));
+            }
+            if spawn.weight < 0.0 {
+                errors.push(format!(
+                    "{}: the weight of {} is negative",
+                    spawn.location, spawn.creature
+                ));
+            }
+        }
+        if spawns.iter().all(|spawn| spawn.weight <= 0.0) {
+            errors.push(format!("{SPAWNS_FILE}:1: no creature can spawn"));
+        }
+        if errors.is_empty() {
+            Ok(Self { spawns })
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// the creatures with their weights
+    pub fn chances(&self) -> Vec<(&'static CreatureDefinition, f32)> {
+        self.spawns
+            .iter()
+            .filter_map(|spawn| {
+                creatures()
+                    .get(&spawn.creature)
+                    .map(|creature| (creature, spawn.weight))
+            })
+            .collect()
+    }
+}
+
+static REGISTRY: OnceLock<SpawnTable> = OnceLock::new();
+
+/// returns the spawn table of data/spawns.toml and of the loaded mods
+pub fn spawns() -> &'static SpawnTable {
+    REGISTRY.get_or_init(|| {
+        SpawnTable::load(&[Source::game(SPAWNS_FILE, SPAWNS_CONTENT)])
+            .unwrap_or_else(|errors| panic!("invalid {SPAWNS_FILE}: {}", errors.join(", ")))
+    })
+}
+
+/// replaces the spawn table of the game by the one merged with the mods, once the creatures are installed
+pub fn install(sources: &[Source]) -> Result<(), Vec<String>> {
+    let registry = SpawnTable::load(sources)?;
+    REGISTRY.set(registry).map_err(|_| {
+        vec![format!(
+            "{SPAWNS_FILE}: the spawn table was read before the mods loaded"
+        )]
+    })
+}
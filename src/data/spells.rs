@@ -0,0 +1,148 @@
+use std::{collections::HashMap, sync::OnceLock};
+
+use serde::Deserialize;
+
+use crate::{
+    actions::{
+        action::{Action, ActionType, AreaAttack},
+        projectile::{ProjectileAttack, ProjectileKind},
+        shapes::AreaShape,
+        spell::Spell,
+    },
+    map::fluids::GasKind,
+};
+
+const SPELLS_FILE: &str = include_str!("../../data/spells.toml");
+
+/// how a spell affects the map
+#[derive(Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum SpellEffect {
+    Area { shape: AreaShape },
+    Projectile { projectile: ProjectileKind },
+}
+
+#[derive(Deserialize)]
+pub struct SpellDefinition {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub school: ActionType,
+    #[serde(flatten)]
+    pub effect: SpellEffect,
+    pub damage: u32,
+    pub range: u32,
+    pub mana_cost: u32,
+    #[serde(default)]
+    pub cooldown: u32,
+    #[serde(default)]
+    pub casting_time: u32,
+    /// gas left over the area of the spell
+    #[serde(default)]
+    pub gas: Option<GasKind>,
+}
+
+impl SpellDefinition {
+    pub fn action(&self) -> Box<dyn Action> {
+        match self.effect {
+            SpellEffect::Area { shape } => Box::new(AreaAttack::new(
+                &self.name,
+                self.damage,
+                self.range,
+                shape,
+                self.school,
+                self.mana_cost,
+                self.gas,
+            )),
+            SpellEffect::Projectile { projectile } => Box::new(ProjectileAttack::new(
+                &self.name,
+                self.damage,
+                self.range,
+                self.school,
+                self.mana_cost,
+                projectile,
+            )),
+        }
+    }
+
+    pub fn build(&self) -> Spell {
+        Spell::new(&self.id, self.action(), self.cooldown, self.casting_time)
+    }
+}
+
+/// spells a spell can turn into once it reached the level
+#[derive(Deserialize)]
+pub struct EvolutionDefinition {
+    pub spell: String,
+    pub level: u32,
+    pub branches: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct SpellsFile {
+    #[serde(default)]
+    spell: Vec<SpellDefinition>,
+    #[serde(default)]
+    evolution: Vec<EvolutionDefinition>,
+}
+
+pub struct SpellRegistry {
+    spells: HashMap<String, SpellDefinition>,
+    evolutions: Vec<EvolutionDefinition>,
+}
+
+impl SpellRegistry {
+    /// parses a spells file and checks that the evolutions only refer to known spells
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let file: SpellsFile = toml::from_str(content).map_err(|e| e.to_string())?;
+        let mut spells = HashMap::new();
+        for definition in file.spell {
+            if spells.contains_key(&definition.id) {
+                return Err(format!("spell {} is defined twice", definition.id));
+            }
+            spells.insert(definition.id.clone(), definition);
+        }
+        for evolution in &file.evolution {
+            for id in std::iter::once(&evolution.spell).chain(&evolution.branches) {
+                if !spells.contains_key(id) {
+                    return Err(format!(
+                        "evolution of {} refers to the unknown spell {}",
+                        evolution.spell, id
+                    ));
+                }
+            }
+        }
+        Ok(Self {
+            spells,
+            evolutions: file.evolution,
+        })
+    }
+
+    pub fn get(&self, id: &str) -> Option<&SpellDefinition> {
+        self.spells.get(id)
+    }
+
+    /// builds the spell, panics if the id is unknown since the ids come from the code
+    pub fn build(&self, id: &str) -> Spell {
+        match self.get(id) {
+            Some(definition) => definition.build(),
+            None => panic!("unknown spell {id}"),
+        }
+    }
+
+    /// returns the evolution of the spell unlocked at that level, if any
+    pub fn evolution(&self, id: &str, level: u32) -> Option<&EvolutionDefinition> {
+        self.evolutions
+            .iter()
+            .find(|evolution| evolution.spell == id && level >= evolution.level)
+    }
+}
+
+/// returns the spells described in data/spells.toml
+pub fn spells() -> &'static SpellRegistry {
+    static REGISTRY: OnceLock<SpellRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        SpellRegistry::parse(SPELLS_FILE)
+            .unwrap_or_else(|e| panic!("invalid data/spells.toml: {e}"))
+    })
+}
@@ -0,0 +1,144 @@
+use std::sync::OnceLock;
+
+use ratatui::style::Color;
+use serde::Deserialize;
+
+use crate::map::{structures::structure::StructureKind, tile::TileKind};
+
+use super::{
+    items::items,
+    mods::{self, Definition, Location, Source},
+};
+
+pub const STRUCTURES_FILE: &str = "data/structures.toml";
+pub const STRUCTURES_CONTENT: &str = include_str!("../../data/structures.toml");
+
+#[derive(Deserialize)]
+pub struct StructureDefinition {
+    pub id: StructureKind,
+    pub name: String,
+    pub symbol: String,
+    /// symbol of an open door
+    pub open_symbol: Option<String>,
+    /// [red, green, blue]
+    pub color: [u8; 3],
+    /// damage needed to destroy the structure, None if it can't be destroyed
+    pub durability: Option<u32>,
+    /// what the ground becomes once the structure is destroyed, unchanged if None
+    pub destroyed_into: Option<TileKind>,
+    /// ids of the items left when the structure is destroyed
+    #[serde(default)]
+    pub drops: Vec<String>,
+    /// turns the structure burns once on fire, 0 if it doesn't burn
+    #[serde(default)]
+    pub fuel: u32,
+    #[serde(skip)]
+    pub location: Location,
+}
+
+impl StructureDefinition {
+    pub fn color(&self) -> Color {
+        let [r, g, b] = self.color;
+        Color::Rgb(r, g, b)
+    }
+}
+
+impl Definition for StructureDefinition {
+    const TABLE: &'static str = "structure";
+
+    fn id(&self) -> &str {
+        self.id.id()
+    }
+
+    fn location(&self) -> &Location {
+        &self.location
+    }
+
+    fn set_location(&mut self, location: Location) {
+        self.location = location;
+    }
+}
+
+pub struct StructureRegistry {
+    /// the definitions in the order of StructureKind::ALL
+    structures: Vec<StructureDefinition>,
+}
+
+impl StructureRegistry {
+    /// merges the structures files of the game and the mods and checks that every structure is defined and that the drops exist
+    pub fn load(sources: &[Source]) -> Result<Self, Vec<String>> {
+        let mut definitions: Vec<Option<StructureDefinition>> =
+            mods::merge(sources)?.into_iter().map(Some).collect();
+        let mut errors = Vec::new();
+        for structure in definitions.iter().flatten() {
+            let symbols = std::iter::once(&structure.symbol).chain(&structure.open_symbol);
+            if symbols
+                .into_iter()
+                .any(|symbol| symbol.chars().count() != 1)
+            {
+                errors.push(format!(
+                    "{}: structure {} needs symbols of one character",
+                    structure.location,
+                    structure.id.id()
+                ));
+            }
+            for id in structure.drops.iter() {
+                if items().get(id).is_none() {
+                    errors.push(format!(
+                        "{}: structure {} drops the unknown item {}",
+                        structure.location,
+                        structure.id.id(),
+                        id
+                    ));
+                }
+            }
+        }
+        let mut structures = Vec::new();
+        for kind in StructureKind::ALL {
+            let definition = definitions
+                .iter_mut()
+                .find(|definition| {
+                    definition
+                        .as_ref()
+                        .is_some_and(|structure| structure.id == kind)
+                })
+                .and_then(Option::take);
+            match definition {
+                Some(definition) => structures.push(definition),
+                None => errors.push(format!(
+                    "{STRUCTURES_FILE}:1: the structure {} of the game is missing",
+                    kind.id()
+                )),
+            }
+        }
+        if errors.is_empty() {
+            Ok(Self { structures })
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn get(&self, kind: StructureKind) -> &StructureDefinition {
+        &self.structures[kind as usize]
+    }
+}
+
+static REGISTRY: OnceLock<StructureRegistry> = OnceLock::new();
+
+/// returns the structures described in data/structures.toml and in the loaded mods
+pub fn structures() -> &'static StructureRegistry {
+    REGISTRY.get_or_init(|| {
+        StructureRegistry::load(&[Source::game(STRUCTURES_FILE, STRUCTURES_CONTENT)])
+            .unwrap_or_else(|errors| panic!("invalid {STRUCTURES_FILE}: {}", errors.join(", ")))
+    })
+}
+
+/// replaces the structures of the game by the ones merged with the mods, once the items are installed
+pub fn install(sources: &[Source]) -> Result<(), Vec<String>> {
+    let registry = StructureRegistry::load(sources)?;
+    REGISTRY.set(registry).map_err(|_| {
+        vec![format!(
+            "{STRUCTURES_FILE}: the structures were read before the mods loaded"
+        )]
+    })
+}
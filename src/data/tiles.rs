@@ -0,0 +1,149 @@
+use std::sync::OnceLock;
+
+use ratatui::style::Color;
+use serde::Deserialize;
+
+use crate::{items::item::WeaponKind, map::tile::TileKind};
+
+use super::{
+    items::items,
+    mods::{self, Definition, Location, Source},
+};
+
+pub const TILES_FILE: &str = "data/tiles.toml";
+pub const TILES_CONTENT: &str = include_str!("../../data/tiles.toml");
+
+fn default_walkable() -> bool {
+    true
+}
+
+#[derive(Deserialize)]
+pub struct TileDefinition {
+    pub id: TileKind,
+    pub name: String,
+    pub symbol: String,
+    /// [red, green, blue]
+    pub color: [u8; 3],
+    #[serde(default = "default_walkable")]
+    pub walkable: bool,
+    #[serde(default)]
+    pub blocks_sight: bool,
+    /// height of the ground, the fluids flowing toward the lower tiles
+    #[serde(default)]
+    pub elevation: i32,
+    /// damage needed to destroy the tile, None if it can't be destroyed
+    pub durability: Option<u32>,
+    /// weapon to hold to damage the tile
+    pub tool: Option<WeaponKind>,
+    /// what the tile becomes once destroyed, unchanged if None
+    pub destroyed_into: Option<TileKind>,
+    /// ids of the items left when the tile is destroyed
+    #[serde(default)]
+    pub drops: Vec<String>,
+    /// turns the tile keeps burning once on fire, 0 if it doesn't burn
+    #[serde(default)]
+    pub fuel: u32,
+    /// what the tile becomes once burned out, unchanged if None
+    pub burns_into: Option<TileKind>,
+    #[serde(skip)]
+    pub location: Location,
+}
+
+impl TileDefinition {
+    pub fn color(&self) -> Color {
+        let [r, g, b] = self.color;
+        Color::Rgb(r, g, b)
+    }
+}
+
+impl Definition for TileDefinition {
+    const TABLE: &'static str = "tile";
+
+    fn id(&self) -> &str {
+        self.id.id()
+    }
+
+    fn location(&self) -> &Location {
+        &self.location
+    }
+
+    fn set_location(&mut self, location: Location) {
+        self.location = location;
+    }
+}
+
+pub struct TileRegistry {
+    /// the definitions in the order of TileKind::ALL
+    tiles: Vec<TileDefinition>,
+}
+
+impl TileRegistry {
+    /// merges the tiles files of the game and the mods and checks that every tile is defined and that the drops exist
+    pub fn load(sources: &[Source]) -> Result<Self, Vec<String>> {
+        let mut definitions: Vec<Option<TileDefinition>> =
+            mods::merge(sources)?.into_iter().map(Some).collect();
+        let mut errors = Vec::new();
+        for tile in definitions.iter().flatten() {
+            if tile.symbol.chars().count() != 1 {
+                errors.push(format!(
+                    "{}: tile {} needs a symbol of one character",
+                    tile.location,
+                    tile.id.id()
+                ));
+            }
+            for id in tile.drops.iter() {
+                if items().get(id).is_none() {
+                    errors.push(format!(
+                        "{}: tile {} drops the unknown item {}",
+                        tile.location,
+                        tile.id.id(),
+                        id
+                    ));
+                }
+            }
+        }
+        let mut tiles = Vec::new();
+        for kind in TileKind::ALL {
+            let definition = definitions
+                .iter_mut()
+                .find(|definition| definition.as_ref().is_some_and(|tile| tile.id == kind))
+                .and_then(Option::take);
+            match definition {
+                Some(definition) => tiles.push(definition),
+                None => errors.push(format!(
+                    "{TILES_FILE}:1: the tile {} of the game is missing",
+                    kind.id()
+                )),
+            }
+        }
+        if errors.is_empty() {
+            Ok(Self { tiles })
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn get(&self, kind: TileKind) -> &TileDefinition {
+        &self.tiles[kind as usize]
+    }
+}
+
+static REGISTRY: OnceLock<TileRegistry> = OnceLock::new();
+
+/// returns the tiles described in data/tiles.toml and in the loaded mods
+pub fn tiles() -> &'static TileRegistry {
+    REGISTRY.get_or_init(|| {
+        TileRegistry::load(&[Source::game(TILES_FILE, TILES_CONTENT)])
+            .unwrap_or_else(|errors| panic!("invalid {TILES_FILE}: {}", errors.join(", ")))
+    })
+}
+
+/// replaces the tiles of the game by the ones merged with the mods, once the items are installed
+pub fn install(sources: &[Source]) -> Result<(), Vec<String>> {
+    let registry = TileRegistry::load(sources)?;
+    REGISTRY.set(registry).map_err(|_| {
+        vec![format!(
+            "{TILES_FILE}: the tiles were read before the mods loaded"
+        )]
+    })
+}
@@ -1,6 +1,12 @@
-use crate::{map::map::Map, menu::Logger};
+use crate::{
+    actions::shapes::AreaShape,
+    map::map::{Direction, Map},
+    menu::Logger,
+    systems::scripting,
+};
 
-use super::entity::Entity;
+use super::{controller::Controller, entity::Entity};
+use crate::actions::action_bar::ActionSlot;
 
 pub trait AIBehavior {
     fn update(
@@ -18,6 +24,38 @@ pub trait AIBehavior {
         other_entities: &mut [&mut Entity],
     ) -> (i32, i32, i32);
 
+    /// returns the one action or spell the entity uses this turn against the target, or None if it should move instead; by default the most expensive one that is ready, that the entity can afford and has the ammunition for, and that reaches the target; nothing is used on a target out of sight
+    fn choose_action(
+        &self,
+        entity: &Entity,
+        target_coordinates: (i32, i32, i32),
+        map: &Map,
+    ) -> Option<ActionSlot> {
+        if !AreaShape::is_reachable_from(entity.position, target_coordinates, map) {
+            return None;
+        }
+        let actions = (0..entity.actions.len()).map(ActionSlot::Action);
+        let ready_spells = entity
+            .spellbook
+            .spells
+            .iter()
+            .enumerate()
+            .filter(|(_, spell)| spell.is_ready())
+            .map(|(index, _)| ActionSlot::Spell(index));
+        actions
+            .chain(ready_spells)
+            .filter_map(|slot| entity.action(slot).map(|action| (slot, action)))
+            .filter(|(_, action)| {
+                action.mana_cost() <= entity.stats.mana
+                    && action
+                        .ammunition()
+                        .is_none_or(|id| entity.find_item(id).is_some())
+                    && action.can_reach(entity, target_coordinates)
+            })
+            .max_by_key(|(_, action)| action.mana_cost())
+            .map(|(slot, _)| slot)
+    }
+
     /// clone this behavior into a fresh Box<dyn AiBehavior>
     fn box_clone(&self) -> Box<dyn AIBehavior>;
 }
@@ -39,6 +77,17 @@ impl AIBehavior for ChasePlayerBehavior {
         other_entities: &mut [&mut Entity],
         logger: &mut Logger,
     ) {
+        // attacks the player with one action if it is in reach
+        if let Some(player_position) = other_entities
+            .iter()
+            .find(|e| e.is_player())
+            .map(|player| player.position)
+            && let Some(slot) = self.choose_action(entity, player_position, map)
+        {
+            Controller::use_slot(entity, slot, player_position, other_entities, map, logger);
+            return;
+        }
+
         let (dx, dy, dz) = self.decide_movement(entity.position, map, other_entities);
         let controller = entity.controller.clone();
         controller.handle_entity_movement(entity, dx, dy, dz, map, other_entities, logger);
@@ -75,3 +124,61 @@ impl AIBehavior for ChasePlayerBehavior {
         Box::new(*self)
     }
 }
+
+/// creature deciding each turn with decide(ctx) of a script of data/scripts, chasing the player when it has no answer
+#[derive(Clone)]
+pub struct ScriptedBehavior {
+    pub script: String,
+}
+
+impl AIBehavior for ScriptedBehavior {
+    fn update(
+        &self,
+        entity: &mut Entity,
+        map: &mut Map,
+        other_entities: &mut [&mut Entity],
+        logger: &mut Logger,
+    ) {
+        let target = other_entities
+            .iter()
+            .find(|e| e.is_player())
+            .map_or(entity.position, |player| player.position);
+        let decision = scripting::decide(&self.script, entity, target, &mut map.scripts, logger);
+        let (dx, dy) = match decision.as_deref() {
+            Some("wait") => return,
+            Some("attack") => {
+                if let Some(slot) = self.choose_action(entity, target, map) {
+                    Controller::use_slot(entity, slot, target, other_entities, map, logger);
+                }
+                return;
+            }
+            Some("flee") => (
+                (entity.position.0 - target.0).signum(),
+                (entity.position.1 - target.1).signum(),
+            ),
+            Some("north") => Direction::North.delta(),
+            Some("south") => Direction::South.delta(),
+            Some("east") => Direction::East.delta(),
+            Some("west") => Direction::West.delta(),
+            _ => {
+                ChasePlayerBehavior.update(entity, map, other_entities, logger);
+                return;
+            }
+        };
+        let controller = entity.controller.clone();
+        controller.handle_entity_movement(entity, dx, dy, 0, map, other_entities, logger);
+    }
+
+    fn decide_movement(
+        &self,
+        entity_position: (i32, i32, i32),
+        map: &Map,
+        other_entities: &mut [&mut Entity],
+    ) -> (i32, i32, i32) {
+        ChasePlayerBehavior.decide_movement(entity_position, map, other_entities)
+    }
+
+    fn box_clone(&self) -> Box<dyn AIBehavior> {
+        Box::new(self.clone())
+    }
+}
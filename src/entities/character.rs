@@ -0,0 +1,318 @@
+use serde::Deserialize;
+
+use crate::{
+    actions::{
+        action::{Action, ActionType, MeleeAttack},
+        projectile::{ProjectileAttack, ProjectileKind},
+        resistances::Resistances,
+    },
+    items::item::{ArmorKind, ConsumableEffect, ConsumableKind, Item, WeaponKind},
+};
+
+use super::{entity::EntityStats, status_effect::StatusEffectKind};
+
+/// race chosen by the player, heteromorphs included
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Race {
+    Human,
+    Elf,
+    Undead,
+    DemiHuman,
+}
+
+impl Race {
+    pub const ALL: [Race; 4] = [Race::Human, Race::Elf, Race::Undead, Race::DemiHuman];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Race::Human => "Human",
+            Race::Elf => "Elf",
+            Race::Undead => "Undead",
+            Race::DemiHuman => "Demi-human",
+        }
+    }
+
+    /// describes the innate trait of the race
+    pub fn description(&self) -> &'static str {
+        match self {
+            Race::Human => "Ambitious: gains 20% more XP",
+            Race::Elf => "Attuned to magic: regenerates mana twice as fast",
+            Race::Undead => {
+                "Lifeless body: never hungry nor thirsty, can't be frozen, resists ice but fears fire"
+            }
+            Race::DemiHuman => "Beast blood: regenerates 1 PV each turn",
+        }
+    }
+
+    pub fn apply_modifiers(&self, stats: &mut EntityStats) {
+        let (hp, mana, defense, strength, magic) = match self {
+            Race::Human => (0, 0, 0, 0, 0),
+            Race::Elf => (-10, 30, 0, -1, 3),
+            Race::Undead => (20, 10, 2, 0, 1),
+            Race::DemiHuman => (30, -30, 1, 4, -3),
+        };
+        apply_modifiers(stats, hp, mana, defense, strength, magic);
+    }
+
+    /// innate resistances of the race
+    pub fn resistances(&self) -> Resistances {
+        match self {
+            Race::Undead => Resistances::new(10, -50, 50, 0),
+            Race::Elf => Resistances::new(0, 0, 0, 20),
+            Race::Human | Race::DemiHuman => Resistances::default(),
+        }
+    }
+
+    /// percentage of bonus XP
+    pub fn xp_bonus(&self) -> u32 {
+        match self {
+            Race::Human => 20,
+            _ => 0,
+        }
+    }
+
+    pub fn mana_regeneration_multiplier(&self) -> f32 {
+        match self {
+            Race::Elf => 2.0,
+            _ => 1.0,
+        }
+    }
+
+    /// PV regenerated each turn
+    pub fn hp_regeneration(&self) -> u32 {
+        match self {
+            Race::DemiHuman => 1,
+            _ => 0,
+        }
+    }
+
+    pub fn eats_and_drinks(&self) -> bool {
+        *self != Race::Undead
+    }
+
+    pub fn is_immune_to(&self, kind: StatusEffectKind) -> bool {
+        matches!((self, kind), (Race::Undead, StatusEffectKind::Frozen))
+    }
+}
+
+/// class chosen by the player, giving its starting equipment, actions and spells
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Class {
+    Warrior,
+    Mage,
+    Rogue,
+    Priest,
+}
+
+impl Class {
+    pub const ALL: [Class; 4] = [Class::Warrior, Class::Mage, Class::Rogue, Class::Priest];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Class::Warrior => "Warrior",
+            Class::Mage => "Mage",
+            Class::Rogue => "Rogue",
+            Class::Priest => "Priest",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Class::Warrior => "Fights in melee with a sword and a heavy armor",
+            Class::Mage => "Masters the elements with a staff and a spellbook",
+            Class::Rogue => "Strikes from afar with a bow and keeps a dagger",
+            Class::Priest => "Smites the enemies and heals with potions",
+        }
+    }
+
+    pub fn apply_modifiers(&self, stats: &mut EntityStats) {
+        let (hp, mana, defense, strength, magic) = match self {
+            Class::Warrior => (50, -50, 3, 5, -3),
+            Class::Mage => (-20, 50, 0, -2, 5),
+            Class::Rogue => (10, -20, 1, 3, 0),
+            Class::Priest => (0, 30, 2, 0, 3),
+        };
+        apply_modifiers(stats, hp, mana, defense, strength, magic);
+    }
+
+    pub fn actions(&self) -> Vec<Box<dyn Action>> {
+        let mut actions: Vec<Box<dyn Action>> = vec![Box::new(MeleeAttack::new(
+            "melee",
+            10,
+            ActionType::Physical,
+        ))];
+        if *self == Class::Rogue {
+            actions.push(Box::new(ProjectileAttack::new(
+                "shoot arrow",
+                5,
+                10,
+                ActionType::Physical,
+                0,
+                ProjectileKind::Arrow,
+            )));
+        }
+        actions.push(Box::new(ProjectileAttack::new(
+            "throw stone",
+            2,
+            4,
+            ActionType::Physical,
+            0,
+            ProjectileKind::Thrown,
+        )));
+        actions
+    }
+
+    /// ids of the starting spells, defined in data/spells.toml
+    pub fn spells(&self) -> &'static [&'static str] {
+        match self {
+            Class::Warrior => &[],
+            Class::Rogue => &["poison_cloud"],
+            Class::Mage => &["firebolt", "fireball", "frost_cone", "lightning_beam"],
+            Class::Priest => &["smite"],
+        }
+    }
+
+    /// starting items, the last weapon is the one equipped
+    pub fn starting_items(&self) -> Vec<Item> {
+        match self {
+            Class::Warrior => vec![
+                Item::new_weapon(
+                    "Iron sword".to_string(),
+                    "A plain but sturdy sword".to_string(),
+                    WeaponKind::Sword,
+                    10,
+                ),
+                Item::new_armor(
+                    "Iron chestplate".to_string(),
+                    "Heavy armor protecting the chest".to_string(),
+                    ArmorKind::Chestplate,
+                    5,
+                    Resistances::new(10, 0, 0, -10),
+                    3,
+                ),
+            ],
+            Class::Mage => vec![Item::new_weapon(
+                "Oak staff".to_string(),
+                "A staff channeling the magic of its bearer".to_string(),
+                WeaponKind::Staff,
+                5,
+            )],
+            Class::Rogue => vec![
+                Item::new_weapon(
+                    "Dagger".to_string(),
+                    "A sharp dagger for close fights".to_string(),
+                    WeaponKind::Dagger,
+                    6,
+                ),
+                Item::new_weapon(
+                    "Short bow".to_string(),
+                    "A light bow".to_string(),
+                    WeaponKind::Bow,
+                    5,
+                ),
+            ],
+            Class::Priest => vec![
+                Item::new_weapon(
+                    "Holy staff".to_string(),
+                    "A staff blessed by the temple".to_string(),
+                    WeaponKind::Staff,
+                    3,
+                ),
+                Item::new_consumable(
+                    "Healing potion".to_string(),
+                    "Heals 40 PV".to_string(),
+                    ConsumableKind::Potion,
+                    ConsumableEffect::Heal(40),
+                    3,
+                ),
+                Item::new_consumable(
+                    "Cleansing potion".to_string(),
+                    "Cures every status effect".to_string(),
+                    ConsumableKind::Potion,
+                    ConsumableEffect::CureAll,
+                    2,
+                ),
+            ],
+        }
+    }
+}
+
+/// stat raised by the attribute points gained at each level up
+#[derive(Clone, Copy, PartialEq, Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Attribute {
+    Vitality,
+    Spirit,
+    Defense,
+    Strength,
+    Magic,
+}
+
+impl Attribute {
+    pub const ALL: [Attribute; 5] = [
+        Attribute::Vitality,
+        Attribute::Spirit,
+        Attribute::Defense,
+        Attribute::Strength,
+        Attribute::Magic,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Attribute::Vitality => "Vitality",
+            Attribute::Spirit => "Spirit",
+            Attribute::Defense => "Defense",
+            Attribute::Strength => "Strength",
+            Attribute::Magic => "Magic",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Attribute::Vitality => "+10 max PV",
+            Attribute::Spirit => "+10 max mana",
+            Attribute::Defense => "+1 defense",
+            Attribute::Strength => "+1 strength",
+            Attribute::Magic => "+1 magic",
+        }
+    }
+
+    /// raises the stat by the number of points, or lowers it if negative; the current hp and mana follow their maximum
+    pub fn apply(&self, stats: &mut EntityStats, points: i32) {
+        let add = |value: u32, modifier: i32| value.saturating_add_signed(modifier);
+        match self {
+            Attribute::Vitality => {
+                stats.max_hp = add(stats.max_hp, points * 10).max(1);
+                stats.hp = add(stats.hp, points * 10).min(stats.max_hp);
+            }
+            Attribute::Spirit => {
+                stats.max_mana = add(stats.max_mana, points * 10);
+                stats.mana = add(stats.mana, points * 10).min(stats.max_mana);
+            }
+            Attribute::Defense => stats.defense = add(stats.defense, points),
+            Attribute::Strength => stats.strength = add(stats.strength, points),
+            Attribute::Magic => stats.magic = add(stats.magic, points),
+        }
+    }
+}
+
+/// adds the modifiers to the stats, the current hp and mana being refilled
+fn apply_modifiers(
+    stats: &mut EntityStats,
+    hp: i32,
+    mana: i32,
+    defense: i32,
+    strength: i32,
+    magic: i32,
+) {
+    let add = |value: u32, modifier: i32| value.saturating_add_signed(modifier);
+    stats.max_hp = add(stats.max_hp, hp).max(1);
+    stats.hp = stats.max_hp;
+    stats.max_mana = add(stats.max_mana, mana);
+    stats.mana = stats.max_mana;
+    stats.defense = add(stats.defense, defense);
+    stats.strength = add(stats.strength, strength);
+    stats.magic = add(stats.magic, magic);
+}
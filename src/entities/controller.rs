@@ -1,12 +1,65 @@
 use crossterm::event::KeyCode;
+use rand::Rng;
 
 use crate::{
     common::utils::Drawable,
-    map::map::{CHUNK_SIZE, Direction, Map},
+    data::{
+        items::items,
+        perks::{PerkStatus, perks},
+        progression::progression,
+        recipes::recipes,
+    },
+    items::crafting::{self, Crafting},
+    map::{
+        fluids::FluidKind,
+        map::{CHUNK_SIZE, Direction, Map},
+        weather::{SNOW_SLOW_CHANCE, WeatherKind},
+    },
     menu::Logger,
+    systems::{
+        clock::{SECONDS_PER_HOUR, SECONDS_PER_MINUTE},
+        random::Stream,
+        scripting,
+    },
 };
 
-use super::{AIbehavior::AIBehavior, entity::Entity};
+use super::{AIbehavior::AIBehavior, character::Attribute, entity::Entity, needs::MAX_NEED};
+use crate::actions::{
+    action_bar::ActionSlot,
+    spell::{Casting, SPELL_XP_PER_CAST},
+};
+
+/// seconds of game time taken by a step
+const MOVE_DURATION: u64 = 1;
+/// seconds of game time taken by the other actions
+const ACTION_DURATION: u64 = 2;
+/// seconds of game time taken by each turn of crafting
+const CRAFTING_DURATION: u64 = SECONDS_PER_MINUTE;
+/// seconds of game time taken by resting
+const REST_DURATION: u64 = SECONDS_PER_HOUR;
+
+/// what the player asks its Entity to do
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PlayerInput {
+    Key(KeyCode),
+    /// uses the item at the index of the inventory
+    UseItem(usize),
+    /// uses the action or casts the spell of the slot on the target coordinates
+    UseAction {
+        slot: ActionSlot,
+        target: (i32, i32, i32),
+    },
+    /// replaces the spell at the index by a branch of its evolution
+    EvolveSpell {
+        spell_index: usize,
+        branch: usize,
+    },
+    SpendAttributePoint(Attribute),
+    /// takes the perk at the index of the perk registry
+    TakePerk(usize),
+    /// crafts the recipe at the index of the recipe registry
+    Craft(usize),
+}
 
 /// who controls the Entity
 #[derive(Clone)]
@@ -15,20 +68,67 @@ pub enum Controller {
     AI(Box<dyn AIBehavior>),
 }
 
+impl PlayerInput {
+    /// true for the choices about the progression and the action bar, which don't take a turn
+    pub fn is_choice(&self) -> bool {
+        matches!(
+            self,
+            PlayerInput::EvolveSpell { .. }
+                | PlayerInput::SpendAttributePoint(_)
+                | PlayerInput::TakePerk(_)
+                | PlayerInput::Key(KeyCode::Char('1'..='9' | 'n' | 'm'))
+        )
+    }
+}
+
 impl Controller {
+    /// true for the input resting, which lasts several turns
+    pub fn is_rest(input: PlayerInput) -> bool {
+        matches!(input, PlayerInput::Key(KeyCode::Char('r')))
+    }
+
+    /// returns the seconds of game time the input of the player takes
+    pub fn duration(entity: &Entity, input: PlayerInput) -> u64 {
+        if entity.crafting.is_some() {
+            return CRAFTING_DURATION;
+        }
+        match input {
+            input if Self::is_rest(input) => REST_DURATION,
+            PlayerInput::Key(
+                KeyCode::Up
+                | KeyCode::Down
+                | KeyCode::Left
+                | KeyCode::Right
+                | KeyCode::Char('y')
+                | KeyCode::Char('u'),
+            ) => MOVE_DURATION,
+            PlayerInput::Craft(_) => CRAFTING_DURATION,
+            input if input.is_choice() => 0,
+            _ => ACTION_DURATION,
+        }
+    }
+
     pub fn update_entity(
         &self,
         entity: &mut Entity,
-        input: KeyCode,
+        input: PlayerInput,
         map: &mut Map,
         other_entities: &mut [&mut Entity],
         logger: &mut Logger,
     ) {
+        // a spell being cast or an item being crafted takes the whole turn
+        if Self::continue_casting(entity, other_entities, map, logger)
+            || Self::continue_crafting(entity, logger)
+        {
+            return;
+        }
         match self {
             Controller::Player => {
                 self.handle_player_input(entity, input, map, other_entities, logger);
             }
             Controller::AI(behavior) => {
+                Self::evolve_spells(entity, logger);
+                Self::allocate_points(entity, logger);
                 behavior.update(entity, map, other_entities, logger);
             }
         }
@@ -38,11 +138,34 @@ impl Controller {
     fn handle_player_input(
         &self,
         entity: &mut Entity,
-        key_code: KeyCode,
+        input: PlayerInput,
         map: &mut Map,
         other_entities: &mut [&mut Entity],
         logger: &mut Logger,
     ) {
+        if input.is_choice() {
+            Self::apply_choice(entity, input, logger);
+            return;
+        }
+        let key_code = match input {
+            PlayerInput::Key(key_code) => key_code,
+            PlayerInput::UseItem(index) => {
+                entity.use_item(index, map, logger);
+                return;
+            }
+            PlayerInput::UseAction { slot, target } => {
+                Self::use_slot(entity, slot, target, other_entities, map, logger);
+                return;
+            }
+            PlayerInput::Craft(recipe_index) => {
+                Self::start_crafting(entity, recipe_index, map, logger);
+                return;
+            }
+            PlayerInput::EvolveSpell { .. }
+            | PlayerInput::SpendAttributePoint(_)
+            | PlayerInput::TakePerk(_) => return,
+        };
+
         match key_code {
             KeyCode::Char('e') => {
                 let coordinates_tile_entity_looks_at =
@@ -58,9 +181,52 @@ impl Controller {
                 {
                     if let Some(structure) = tile_entity_looks_at.structure.as_mut() {
                         structure.interact(logger);
+                        scripting::on_interact(entity, structure.name(), &mut map.scripts, logger);
+                    } else if tile_entity_looks_at
+                        .fluid
+                        .is_some_and(|fluid| fluid.kind == FluidKind::Water)
+                        && let Some(needs) = entity.needs.as_mut()
+                    {
+                        needs.drink(MAX_NEED);
+                        logger.push_message(format!("{} drinks from the water", entity.symbol()));
                     }
                 }
             }
+            KeyCode::Char('r') => {
+                entity.resting = true;
+                logger.push_message(format!("{} rests", entity.symbol()));
+            }
+            KeyCode::Char('g') => {
+                let items = map
+                    .get_tile_mut(entity.position)
+                    .map(|tile| tile.items.take_all())
+                    .unwrap_or_default();
+                if !items.is_empty() {
+                    let names: Vec<&str> = items.iter().map(|item| item.name.as_str()).collect();
+                    logger.push_message(format!(
+                        "{} picks up {}",
+                        entity.symbol(),
+                        names.join(", ")
+                    ));
+                }
+                for item in items {
+                    entity.inventory.add(item);
+                }
+            }
+            KeyCode::Char('a') => match entity.action_bar.selected_action() {
+                Some(slot) => {
+                    let target_coordinates = entity.direction.coordinates_in_front(entity.position);
+                    Self::use_slot(
+                        entity,
+                        slot,
+                        target_coordinates,
+                        other_entities,
+                        map,
+                        logger,
+                    );
+                }
+                None => logger.push_message("no action in the selected slot".to_string()),
+            },
             _ => {}
         }
 
@@ -78,6 +244,66 @@ impl Controller {
         self.handle_entity_movement(entity, dx, dy, dz, map, other_entities, logger);
     }
 
+    /// applies the choices of the player about its progression and its action bar, which don't take a turn; returns false for the other inputs
+    pub fn apply_choice(entity: &mut Entity, input: PlayerInput, logger: &mut Logger) -> bool {
+        match input {
+            PlayerInput::EvolveSpell {
+                spell_index,
+                branch,
+            } => Self::evolve_spell(entity, spell_index, branch, logger),
+            PlayerInput::SpendAttributePoint(attribute) => {
+                if entity.spend_attribute_point(attribute) {
+                    logger.push_message(format!(
+                        "{} raises its {}",
+                        entity.symbol(),
+                        attribute.name().to_lowercase()
+                    ));
+                }
+            }
+            PlayerInput::TakePerk(index) => entity.take_perk(index, logger),
+            PlayerInput::Key(KeyCode::Char(c @ '1'..='9')) => {
+                let slot = c as usize - '1' as usize;
+                entity.action_bar.select(slot);
+                let action_name = entity
+                    .action_bar
+                    .selected_action()
+                    .and_then(|slot| entity.action(slot))
+                    .map_or("empty", |action| action.name());
+                logger.push_message(format!("slot {} selected: {}", slot + 1, action_name));
+            }
+            PlayerInput::Key(KeyCode::Char('n')) => {
+                entity
+                    .action_bar
+                    .cycle_selected_slot(entity.actions.len(), entity.spellbook.spells.len());
+                let action_name = entity
+                    .action_bar
+                    .selected_action()
+                    .and_then(|slot| entity.action(slot))
+                    .map_or("empty", |action| action.name());
+                logger.push_message(format!(
+                    "slot {} now holds {}",
+                    entity.action_bar.selected_slot + 1,
+                    action_name
+                ));
+            }
+            PlayerInput::Key(KeyCode::Char('m')) => match entity.action_bar.selected_action() {
+                Some(ActionSlot::Action(index)) => {
+                    entity.action_bar.default_action = index;
+                    logger.push_message(format!(
+                        "{} is now the default action",
+                        entity.actions[index].name()
+                    ));
+                }
+                Some(ActionSlot::Spell(_)) => {
+                    logger.push_message("a spell can't be the default action".to_string())
+                }
+                None => {}
+            },
+            _ => return false,
+        }
+        true
+    }
+
     /// moves the entity, changes its direction and attack the entity at the new position by adding the delta in the 3 directions, and handle xp gain and load map around new position
     pub fn handle_entity_movement(
         &self,
@@ -105,43 +331,39 @@ impl Controller {
         let new_y = entity.position.1 + dy;
         let new_z = entity.position.2 + dz;
 
-        // if an entity is on new position, attacks it, else move to new position
-        if let Some(target) = other_entities
-            .iter_mut()
-            .find(|e| e.position == (new_x, new_y, new_z))
+        // if an entity is on new position, attacks it with the default action, else move to new position
+        if other_entities
+            .iter()
+            .any(|e| e.position == (new_x, new_y, new_z))
         {
-            let target_was_alive = !target.is_dead();
-
-            // attacks the entity if collision
-            let target_coordinates = (new_x, new_y, new_z);
-            for action in &entity.actions {
-                if action.handle_mana_cost(&mut entity.stats) {
-                    action.affect(entity, target_coordinates, other_entities, logger);
-                }
-            }
-
-            // if collided with a living target
-            if target_was_alive {
-                if let Some(target) = other_entities
-                    .iter_mut()
-                    .find(|e| e.position == (new_x, new_y, new_z))
-                {
-                    // if the target is now dead
-                    if target_was_alive && target.is_dead() {
-                        logger.push_message(format!(
-                            "{} xp needed for next level",
-                            entity.level_manager.xp_to_next_level()
-                        ));
-                        Self::handle_xp_gain(entity, target, logger);
-                    }
-                }
-            }
+            let default_action = entity.action_bar.default_action;
+            Self::use_action(
+                entity,
+                default_action,
+                (new_x, new_y, new_z),
+                other_entities,
+                map,
+                logger,
+            );
         } else {
             map.load_around((
                 new_x.div_euclid(CHUNK_SIZE as i32),
                 new_y.div_euclid(CHUNK_SIZE as i32),
                 new_z,
             ));
+            // the snow may hold back the creature for this turn
+            if (dx, dy) != (0, 0)
+                && map.weather.at(entity.position) == WeatherKind::Snow
+                && map
+                    .random
+                    .stream(Stream::Environment)
+                    .random_bool(SNOW_SLOW_CHANCE)
+            {
+                if entity.is_player() {
+                    logger.push_message(format!("{} struggles through the snow", entity.symbol()));
+                }
+                return;
+            }
             if let Some(tile) = map.get_tile((new_x, new_y, new_z)) {
                 if tile.walkable() {
                     entity.position = (new_x, new_y, new_z);
@@ -150,8 +372,349 @@ impl Controller {
         }
     }
 
+    /// uses the action or casts the spell of the slot on the target coordinates
+    pub fn use_slot(
+        entity: &mut Entity,
+        slot: ActionSlot,
+        target_coordinates: (i32, i32, i32),
+        other_entities: &mut [&mut Entity],
+        map: &mut Map,
+        logger: &mut Logger,
+    ) {
+        match slot {
+            ActionSlot::Action(index) => Self::use_action(
+                entity,
+                index,
+                target_coordinates,
+                other_entities,
+                map,
+                logger,
+            ),
+            ActionSlot::Spell(index) => Self::cast_spell(
+                entity,
+                index,
+                target_coordinates,
+                other_entities,
+                map,
+                logger,
+            ),
+        }
+    }
+
+    /// uses the action at the index on the target coordinates if the entity can pay its mana cost
+    pub fn use_action(
+        entity: &mut Entity,
+        action_index: usize,
+        target_coordinates: (i32, i32, i32),
+        other_entities: &mut [&mut Entity],
+        map: &mut Map,
+        logger: &mut Logger,
+    ) {
+        if !Self::has_ammunition(entity, ActionSlot::Action(action_index), logger) {
+            return;
+        }
+        let Some(action) = entity.actions.get(action_index) else {
+            return;
+        };
+        if !action.handle_mana_cost(&mut entity.stats) {
+            logger.push_message(format!(
+                "{} doesn't have enough mana for {}",
+                entity.symbol(),
+                action.name()
+            ));
+            return;
+        }
+        Self::take_ammunition(entity, ActionSlot::Action(action_index));
+        Self::perform(
+            entity,
+            ActionSlot::Action(action_index),
+            target_coordinates,
+            other_entities,
+            map,
+            logger,
+        );
+    }
+
+    /// casts the spell at the index if it is ready and the entity can pay its mana cost; spells with a casting time only take effect once the casting is over
+    pub fn cast_spell(
+        entity: &mut Entity,
+        spell_index: usize,
+        target_coordinates: (i32, i32, i32),
+        other_entities: &mut [&mut Entity],
+        map: &mut Map,
+        logger: &mut Logger,
+    ) {
+        let symbol = entity.symbol();
+        if entity
+            .spellbook
+            .spells
+            .get(spell_index)
+            .is_some_and(|spell| spell.is_ready())
+            && !Self::has_ammunition(entity, ActionSlot::Spell(spell_index), logger)
+        {
+            return;
+        }
+        let Some(spell) = entity.spellbook.spells.get_mut(spell_index) else {
+            return;
+        };
+        if !spell.is_ready() {
+            logger.push_message(format!(
+                "{} is not ready ({} turns left)",
+                spell.action.name(),
+                spell.remaining_cooldown
+            ));
+            return;
+        }
+        if !spell.action.handle_mana_cost(&mut entity.stats) {
+            logger.push_message(format!(
+                "{} doesn't have enough mana for {}",
+                symbol,
+                spell.action.name()
+            ));
+            return;
+        }
+        spell.remaining_cooldown = spell.cooldown;
+        let casting_time = spell.casting_time;
+        if casting_time > 0 {
+            logger.push_message(format!("{} starts casting {}", symbol, spell.action.name()));
+        }
+        Self::take_ammunition(entity, ActionSlot::Spell(spell_index));
+
+        if casting_time > 0 {
+            entity.spellbook.casting = Some(Casting {
+                spell_index,
+                target: target_coordinates,
+                remaining_turns: casting_time,
+            });
+            return;
+        }
+        Self::perform(
+            entity,
+            ActionSlot::Spell(spell_index),
+            target_coordinates,
+            other_entities,
+            map,
+            logger,
+        );
+    }
+
+    /// returns false and logs it if the action in the slot uses up an item the entity doesn't have
+    fn has_ammunition(entity: &Entity, slot: ActionSlot, logger: &mut Logger) -> bool {
+        let Some(id) = entity.action(slot).and_then(|action| action.ammunition()) else {
+            return true;
+        };
+        if entity.find_item(id).is_some() {
+            return true;
+        }
+        let name = items().get(id).map_or(id, |definition| &definition.name);
+        logger.push_message(format!("{} has no {} to throw", entity.symbol(), name));
+        false
+    }
+
+    /// removes from the inventory the item used up by the action in the slot
+    fn take_ammunition(entity: &mut Entity, slot: ActionSlot) {
+        if let Some(index) = entity
+            .action(slot)
+            .and_then(|action| action.ammunition())
+            .and_then(|id| entity.find_item(id))
+        {
+            entity.inventory.remove(index);
+        }
+    }
+
+    /// spends the turn casting; returns false if the entity isn't casting anything
+    fn continue_casting(
+        entity: &mut Entity,
+        other_entities: &mut [&mut Entity],
+        map: &mut Map,
+        logger: &mut Logger,
+    ) -> bool {
+        let Some(casting) = entity.spellbook.casting.as_mut() else {
+            return false;
+        };
+        casting.remaining_turns = casting.remaining_turns.saturating_sub(1);
+        if casting.remaining_turns == 0 {
+            let (spell_index, target) = (casting.spell_index, casting.target);
+            entity.spellbook.casting = None;
+            Self::perform(
+                entity,
+                ActionSlot::Spell(spell_index),
+                target,
+                other_entities,
+                map,
+                logger,
+            );
+        }
+        true
+    }
+
+    /// consumes the components of the recipe if the Entity meets its requirements; recipes with a crafting time only give the item once it is over
+    pub fn start_crafting(
+        entity: &mut Entity,
+        recipe_index: usize,
+        map: &mut Map,
+        logger: &mut Logger,
+    ) {
+        let Some(recipe) = recipes().get(recipe_index) else {
+            return;
+        };
+        if !entity.recipes.contains(&recipe.id) {
+            logger.push_message(format!(
+                "{} doesn't know how to craft {}",
+                entity.symbol(),
+                recipe.name
+            ));
+            return;
+        }
+        let missing = crafting::missing_requirements(recipe, entity, map);
+        if !missing.is_empty() {
+            logger.push_message(format!(
+                "{} can't craft {}, missing {}",
+                entity.symbol(),
+                recipe.name,
+                missing.join(", ")
+            ));
+            return;
+        }
+        crafting::take_components(recipe, entity, map);
+
+        if recipe.time > 0 {
+            logger.push_message(format!(
+                "{} starts crafting {} ({} turns)",
+                entity.symbol(),
+                recipe.name,
+                recipe.time
+            ));
+            entity.crafting = Some(Crafting {
+                recipe_index,
+                remaining_turns: recipe.time,
+            });
+            return;
+        }
+        Self::finish_crafting(entity, recipe_index, logger);
+    }
+
+    /// spends the turn crafting; returns false if the entity isn't crafting anything
+    fn continue_crafting(entity: &mut Entity, logger: &mut Logger) -> bool {
+        let Some(crafting) = entity.crafting.as_mut() else {
+            return false;
+        };
+        crafting.remaining_turns = crafting.remaining_turns.saturating_sub(1);
+        if crafting.remaining_turns == 0 {
+            let recipe_index = crafting.recipe_index;
+            entity.crafting = None;
+            Self::finish_crafting(entity, recipe_index, logger);
+        }
+        true
+    }
+
+    fn finish_crafting(entity: &mut Entity, recipe_index: usize, logger: &mut Logger) {
+        let Some(recipe) = recipes().get(recipe_index) else {
+            return;
+        };
+        let item = items().build(&recipe.result);
+        logger.push_message(format!("{} crafted {}", entity.symbol(), item.name));
+        entity.inventory.add(item);
+    }
+
+    /// applies the effect of the action, already paid for, and handles xp gain for every entity it killed
+    fn perform(
+        entity: &mut Entity,
+        slot: ActionSlot,
+        target_coordinates: (i32, i32, i32),
+        other_entities: &mut [&mut Entity],
+        map: &mut Map,
+        logger: &mut Logger,
+    ) {
+        // turns toward the target
+        if let Some(direction) = Direction::towards(entity.position, target_coordinates) {
+            entity.direction = direction;
+        }
+
+        let alive_before: Vec<bool> = other_entities.iter().map(|e| !e.is_dead()).collect();
+        if let Some(action) = entity.action(slot) {
+            action.affect(entity, target_coordinates, other_entities, map, logger);
+        }
+        if let ActionSlot::Spell(spell_index) = slot {
+            Self::handle_spell_xp_gain(entity, spell_index, logger);
+        }
+
+        for (target, was_alive) in other_entities.iter_mut().zip(alive_before) {
+            // if the target is now dead
+            if was_alive && target.is_dead() {
+                logger.push_message(format!(
+                    "{} xp needed for next level",
+                    entity.level_manager.xp_to_next_level()
+                ));
+                Self::handle_xp_gain(entity, target, logger);
+            }
+        }
+    }
+
+    /// the spell gains xp each time it takes effect and may unlock an evolution
+    fn handle_spell_xp_gain(entity: &mut Entity, spell_index: usize, logger: &mut Logger) {
+        let Some(spell) = entity.spellbook.spells.get_mut(spell_index) else {
+            return;
+        };
+        if spell.mastery.add_xp(SPELL_XP_PER_CAST) > 0 {
+            logger.push_message(format!(
+                "{} reached level {}",
+                spell.action.name(),
+                spell.mastery.level
+            ));
+            if !spell.evolution_branches().is_empty() {
+                logger.push_message(format!("{} can evolve", spell.action.name()));
+            }
+        }
+    }
+
+    pub fn evolve_spell(
+        entity: &mut Entity,
+        spell_index: usize,
+        branch: usize,
+        logger: &mut Logger,
+    ) {
+        if let Some((old_name, new_name)) = entity.spellbook.evolve(spell_index, branch) {
+            logger.push_message(format!(
+                "{} of {} evolved into {}",
+                old_name,
+                entity.symbol(),
+                new_name
+            ));
+        }
+    }
+
+    /// AI entities always take the first branch of the evolutions they unlocked
+    fn evolve_spells(entity: &mut Entity, logger: &mut Logger) {
+        while let Some(spell_index) = entity.spellbook.pending_evolution() {
+            Self::evolve_spell(entity, spell_index, 0, logger);
+        }
+    }
+
+    /// AI entities spread their attribute points over every attribute and take the first perks available
+    pub fn allocate_points(entity: &mut Entity, logger: &mut Logger) {
+        let mut attributes = Attribute::ALL.iter().cycle();
+        while entity.level_manager.attribute_points > 0
+            && let Some(&attribute) = attributes.next()
+        {
+            entity.spend_attribute_point(attribute);
+        }
+        while entity.level_manager.perk_points > 0 {
+            let Some(index) = entity
+                .allowed_perks()
+                .into_iter()
+                .find(|&index| perks().all()[index].status(&entity.perks) == PerkStatus::Available)
+            else {
+                break;
+            };
+            entity.take_perk(index, logger);
+        }
+    }
+
     fn handle_xp_gain(attacker: &mut Entity, target: &mut Entity, logger: &mut Logger) {
-        let xp_gained = target.xp_drop;
+        let xp_drop =
+            progression().xp_drop(target.level_manager.level, attacker.level_manager.level);
+        let xp_gained = xp_drop * (100 + attacker.xp_bonus()) / 100;
 
         let levels_gained = attacker
             .level_manager
@@ -1,24 +1,50 @@
 use std::collections::HashMap;
 
-use crossterm::event::KeyCode;
 use ratatui::{
     buffer::Buffer,
     layout::{Position, Rect},
-    style::{Color, Style},
+    style::Color,
 };
+use serde::Deserialize;
 
 use crate::{
-    actions::action::{Action, ActionType, MeleeAttack},
-    common::utils::Drawable,
-    items::item::{EquipmentSlot, Item, ItemKind, WeaponData},
+    actions::{
+        action::{Action, ActionType, MeleeAttack},
+        action_bar::{ActionBar, ActionSlot},
+        projectile::{ProjectileAttack, ProjectileKind, THROWN_ITEM},
+        resistances::Resistances,
+        spell::{Spell, Spellbook},
+    },
+    common::{inventory::Inventory, utils::Drawable},
+    data::{
+        creatures::{CreatureDefinition, creatures},
+        items::items,
+        perks::{PerkEffect, PerkStatus, perks},
+        progression::progression,
+        recipes::recipes,
+        spells::spells,
+    },
+    items::{
+        crafting::Crafting,
+        item::{
+            ConsumableEffect, ConsumableKind, EquipmentSlot, Item, ItemKind, Usable, WeaponData,
+        },
+    },
     map::map::{Direction, Map},
     menu::Logger,
     systems::{camera::Camera, level_manager::LevelManager},
 };
 
-use super::controller::Controller;
+use super::{
+    AIbehavior::ChasePlayerBehavior,
+    character::{Attribute, Class, Race},
+    controller::{Controller, PlayerInput},
+    needs::{NeedLevel, Needs},
+    status_effect::{StatusEffectKind, StatusEffects},
+};
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum EntityKind {
     Human,
     Dragon,
@@ -26,66 +52,14 @@ pub enum EntityKind {
 }
 
 impl EntityKind {
-    pub fn name(&self) -> &'static str {
-        match self {
-            EntityKind::Human => "Human",
-            EntityKind::Dragon => "Dragon",
-            EntityKind::Sheep => "Sheep",
-        }
-    }
-    fn symbol(&self) -> &'static str {
-        match self {
-            EntityKind::Human => "@",
-            EntityKind::Dragon => "D",
-            EntityKind::Sheep => "S",
-        }
-    }
-
-    fn color(&self) -> Color {
-        match self {
-            EntityKind::Human => Color::Rgb(255, 255, 255),
-            EntityKind::Dragon => Color::Rgb(255, 0, 0),
-            EntityKind::Sheep => Color::Rgb(255, 209, 223),
-        }
-    }
-
-    fn style(&self) -> Style {
-        match self {
-            EntityKind::Human => Style::default().fg(Color::Rgb(255, 255, 255)),
-            EntityKind::Dragon => Style::default().fg(Color::Rgb(255, 0, 0)),
-            EntityKind::Sheep => Style::default().fg(Color::Rgb(255, 209, 223)),
-        }
-    }
+    pub const ALL: [EntityKind; 3] = [EntityKind::Human, EntityKind::Dragon, EntityKind::Sheep];
 
-    fn stats(&self) -> EntityStats {
+    /// id of the creature of the kind in data/creatures.toml
+    pub fn id(&self) -> &'static str {
         match self {
-            EntityKind::Human => EntityStats {
-                max_hp: 100,
-                hp: 100,
-                max_mana: 100,
-                mana: 100,
-                defense: 5,
-                strength: 5,
-                magic: 5,
-            },
-            EntityKind::Dragon => EntityStats {
-                max_hp: 1000,
-                hp: 1000,
-                max_mana: 200,
-                mana: 200,
-                defense: 50,
-                strength: 50,
-                magic: 20,
-            },
-            EntityKind::Sheep => EntityStats {
-                max_hp: 30,
-                hp: 30,
-                max_mana: 0,
-                mana: 0,
-                defense: 5,
-                strength: 1,
-                magic: 0,
-            },
+            EntityKind::Human => "human",
+            EntityKind::Dragon => "dragon",
+            EntityKind::Sheep => "sheep",
         }
     }
 
@@ -94,14 +68,22 @@ impl EntityKind {
             EntityKind::Human => {
                 vec![
                     Box::new(MeleeAttack::new("melee human", 10, ActionType::Physical)),
-                    /*Box::new(AreaAttack::new(
-                        "area human",
+                    Box::new(ProjectileAttack::new(
+                        "shoot arrow",
+                        5,
                         10,
-                        100,
-                        100,
                         ActionType::Physical,
                         0,
-                    )),*/
+                        ProjectileKind::Arrow,
+                    )),
+                    Box::new(ProjectileAttack::new(
+                        "throw stone",
+                        2,
+                        4,
+                        ActionType::Physical,
+                        0,
+                        ProjectileKind::Thrown,
+                    )),
                 ]
             }
             EntityKind::Dragon => {
@@ -120,6 +102,15 @@ impl EntityKind {
             }
         }
     }
+
+    /// ids of the spells known by the kind, defined in data/spells.toml
+    fn spells(&self) -> &'static [&'static str] {
+        match self {
+            EntityKind::Human => &["firebolt", "fireball", "frost_cone", "lightning_beam"],
+            EntityKind::Dragon => &["fire_breath"],
+            EntityKind::Sheep => &[],
+        }
+    }
 }
 
 #[derive(Default)]
@@ -133,54 +124,89 @@ pub struct EntityStats {
     pub magic: u32,
 }
 
-struct Inventory {
-    items: Vec<Item>,
-}
-
-impl Inventory {
-    fn new() -> Self {
-        Self { items: Vec::new() }
-    }
-
-    fn add(&mut self, item: Item) {
-        self.items.push(item);
-    }
-}
-
 pub struct Entity {
     pub kind: EntityKind,
+    /// what the Entity looks like, with its stats before the levels
+    pub creature: &'static CreatureDefinition,
     pub name: String,
     pub position: (i32, i32, i32),
     pub controller: Controller,
     pub stats: EntityStats,
-    pub xp_drop: u32,
+    /// innate resistances, without the ones granted by the equipment
+    pub resistances: Resistances,
     pub level_manager: LevelManager,
     pub actions: Vec<Box<dyn Action>>,
+    pub action_bar: ActionBar,
+    pub spellbook: Spellbook,
+    /// only the player has a race and a class
+    pub race: Option<Race>,
+    pub class: Option<Class>,
+    /// ids of the perks taken, defined in data/perks.toml
+    pub perks: Vec<String>,
+    /// only the player has to eat, drink, sleep and keep warm
+    pub needs: Option<Needs>,
+    /// true if the Entity spent the turn resting
+    pub resting: bool,
+    /// ids of the recipes known, defined in data/recipes.toml
+    pub recipes: Vec<String>,
+    pub crafting: Option<Crafting>,
+    /// fraction of mana regenerated but not yet added to the stats
+    mana_regeneration_progress: f32,
     pub equipment: HashMap<EquipmentSlot, Item>,
-    inventory: Inventory,
+    pub inventory: Inventory,
+    pub status_effects: StatusEffects,
     /// where the Entity is looking at
     pub direction: Direction,
+    /// god mode of the debug console, the Entity losing no PV
+    pub invulnerable: bool,
 }
 
 impl Entity {
     pub fn new(
-        kind: EntityKind,
+        creature: &'static CreatureDefinition,
         name: String,
         position: (i32, i32, i32),
         controller: Controller,
     ) -> Self {
+        let kind = creature.kind;
+        let actions = kind.actions();
+        let spells: Vec<Spell> = match &creature.spells {
+            Some(ids) => ids.iter().map(|id| spells().build(id)).collect(),
+            None => kind.spells().iter().map(|id| spells().build(id)).collect(),
+        };
         Self {
             name,
             position,
             controller,
-            stats: kind.stats(),
-            xp_drop: 10,
-            level_manager: LevelManager::default(),
-            actions: kind.actions(),
+            stats: EntityStats {
+                max_hp: creature.hp,
+                hp: creature.hp,
+                max_mana: creature.mana,
+                mana: creature.mana,
+                defense: creature.defense,
+                strength: creature.strength,
+                magic: creature.magic,
+            },
+            resistances: creature.resistances,
+            level_manager: LevelManager::new(1, 0, progression().curve_for(None, None)),
+            action_bar: ActionBar::new(actions.len(), spells.len()),
+            actions,
+            spellbook: Spellbook::new(spells),
+            race: None,
+            class: None,
+            perks: Vec::new(),
+            needs: None,
+            resting: false,
+            recipes: Vec::new(),
+            crafting: None,
+            mana_regeneration_progress: 0.0,
             kind,
+            creature,
             equipment: HashMap::new(),
             inventory: Inventory::new(),
+            status_effects: StatusEffects::default(),
             direction: Direction::North,
+            invulnerable: false,
         }
     }
 
@@ -191,42 +217,396 @@ impl Entity {
         }
     }
 
-    pub fn player(position: (i32, i32, i32)) -> Self {
-        let god_sword = Item::new_weapon(
-            "GodSword".to_string(),
-            "GodSword to test items".to_string(),
-            crate::items::item::WeaponKind::Sword,
-            1000,
-        );
+    /// creates the player from the race and class chosen at the start of the game
+    pub fn character(name: String, race: Race, class: Class, position: (i32, i32, i32)) -> Self {
         let mut player = Self::new(
-            EntityKind::Human,
-            "Player".to_string(),
+            creatures().of_kind(EntityKind::Human),
+            name,
             position,
             Controller::Player,
         );
-        player.stats.max_hp = 1000000;
-        player.stats.hp = 1000000;
-        player.equip_item(god_sword);
+        race.apply_modifiers(&mut player.stats);
+        class.apply_modifiers(&mut player.stats);
+        player.resistances = race.resistances();
+        player.actions = class.actions();
+        player.spellbook =
+            Spellbook::new(class.spells().iter().map(|id| spells().build(id)).collect());
+        player.action_bar = ActionBar::new(player.actions.len(), player.spellbook.spells.len());
+        for item in class.starting_items() {
+            if item.get_equipment_slot().is_some() {
+                player.equip_item(item);
+            } else {
+                player.inventory.add(item);
+            }
+        }
+        player.level_manager =
+            LevelManager::new(1, 0, progression().curve_for(Some(race), Some(class)));
+        player.inventory.add(Item::new_consumable(
+            "Ration".to_string(),
+            "Dried meat and bread".to_string(),
+            ConsumableKind::Food,
+            ConsumableEffect::Food(400),
+            3,
+        ));
+        player.inventory.add(Item::new_consumable(
+            "Waterskin".to_string(),
+            "Fresh water, refilled at any river".to_string(),
+            ConsumableKind::Potion,
+            ConsumableEffect::Drink(400),
+            3,
+        ));
+        // a few stones for the throw stone action
+        for _ in 0..3 {
+            player.inventory.add(items().build(THROWN_ITEM));
+        }
+        player.needs = Some(Needs::new(race.eats_and_drinks()));
+        player.recipes = recipes()
+            .all()
+            .iter()
+            .filter(|recipe| recipe.known)
+            .map(|recipe| recipe.id.clone())
+            .collect();
+        player.race = Some(race);
+        player.class = Some(class);
         player
     }
 
+    /// creates the creature of the game of the kind
+    pub fn monster(kind: EntityKind, position: (i32, i32, i32)) -> Self {
+        Self::spawn(creatures().of_kind(kind), position)
+    }
+
+    /// creates a creature chasing the player, its level scaled with the position where it appears
+    pub fn spawn(creature: &'static CreatureDefinition, position: (i32, i32, i32)) -> Self {
+        let mut monster = Self::new(
+            creature,
+            creature.name.clone(),
+            position,
+            Controller::AI(Box::new(ChasePlayerBehavior)),
+        );
+        let level = progression().monster_level(creature.kind, position);
+        monster.level_manager.grant_levels(level.saturating_sub(1));
+        // the choices of a creature being created aren't logged
+        Controller::allocate_points(&mut monster, &mut Logger::new());
+        monster
+    }
+
+    /// plays one turn of game time for the Entity: its status effects, regeneration, cooldowns and needs, and its action unless the input is None, the Entity going on with what it started (a rest)
     pub fn update(
         &mut self,
-        input: KeyCode,
+        input: Option<PlayerInput>,
         map: &mut Map,
         other_entities: &mut [&mut Entity],
         logger: &mut Logger,
     ) {
-        let controller = self.controller.clone();
-        controller.update_entity(self, input, map, other_entities, logger);
+        let can_act = self.tick_status_effects(logger);
+        self.regenerate_mana();
+        self.heal(self.hp_regeneration());
+        let recharging = self.spellbook.recharging();
+        if let Some(input) = input
+            && can_act
+            && !self.is_dead()
+        {
+            let controller = self.controller.clone();
+            controller.update_entity(self, input, map, other_entities, logger);
+        }
+        self.spellbook.decrease_cooldowns(&recharging);
+        if !self.is_dead() {
+            self.update_needs(map, logger);
+        }
+    }
+
+    /// applies the damage of the status effects and decreases their durations; returns false if the Entity can't act this turn
+    fn tick_status_effects(&mut self, logger: &mut Logger) -> bool {
+        let can_act = self.status_effects.can_act();
+
+        let damages: Vec<(StatusEffectKind, u32)> = self
+            .status_effects
+            .iter()
+            .map(|effect| (effect.kind, effect.damage_per_turn()))
+            .filter(|(_, damage)| *damage > 0)
+            .collect();
+        for (kind, damage) in damages {
+            let actual_damage = match kind {
+                StatusEffectKind::Burning => self.take_damage(damage, ActionType::Fire),
+                // poison ignores resistances
+                _ => self.lose_hp(damage),
+            };
+            logger.push_message(format!(
+                "{} is {} (-{} PV){}",
+                self.symbol(),
+                kind.name(),
+                actual_damage,
+                if self.is_dead() { " and died" } else { "" }
+            ));
+        }
+
+        for kind in self.status_effects.decrease_durations() {
+            logger.push_message(format!("{} is no longer {}", self.symbol(), kind.name()));
+        }
+        if !can_act && self.is_player() {
+            logger.push_message(format!("{} can't act", self.symbol()));
+        }
+        can_act
+    }
+
+    /// regenerates a tenth of the magic stat in mana each turn, more for the races attuned to magic
+    fn regenerate_mana(&mut self) {
+        let bonus: u32 = self
+            .perk_effects()
+            .map(|effect| match effect {
+                PerkEffect::ManaRegeneration { percent } => *percent,
+                _ => 0,
+            })
+            .sum();
+        let multiplier = self
+            .race
+            .map_or(1.0, |race| race.mana_regeneration_multiplier())
+            * (100 + bonus) as f32
+            / 100.0;
+        self.mana_regeneration_progress += self.magic() as f32 / 10.0 * multiplier;
+        let regenerated = self.mana_regeneration_progress.floor();
+        self.mana_regeneration_progress -= regenerated;
+        self.stats.mana = (self.stats.mana + regenerated as u32).min(self.stats.max_mana);
+    }
+
+    /// returns the action or the spell in the slot
+    pub fn action(&self, slot: ActionSlot) -> Option<&dyn Action> {
+        match slot {
+            ActionSlot::Action(index) => self.actions.get(index).map(|action| action.as_ref()),
+            ActionSlot::Spell(index) => self
+                .spellbook
+                .spells
+                .get(index)
+                .map(|spell| spell.action.as_ref()),
+        }
     }
 
-    pub fn take_damage(&mut self, amount: u32) -> u32 {
+    /// reduces the damage with the resistances and returns the damage actually taken
+    pub fn take_damage(&mut self, amount: u32, damage_type: ActionType) -> u32 {
+        let amount = self.total_resistances().apply(amount, damage_type);
+        self.lose_hp(amount)
+    }
+
+    /// removes hp without the resistances and returns the hp actually lost
+    pub fn lose_hp(&mut self, amount: u32) -> u32 {
+        if self.invulnerable {
+            return 0;
+        }
         let damage = std::cmp::min(amount, self.stats.hp);
         self.stats.hp -= damage;
         damage
     }
 
+    /// returns the effects of every perk taken
+    fn perk_effects(&self) -> impl Iterator<Item = &'static PerkEffect> + '_ {
+        self.perks
+            .iter()
+            .filter_map(|id| perks().find(id))
+            .flat_map(|perk| perk.effects.iter())
+    }
+
+    /// PV regenerated each turn thanks to the race and the perks
+    pub fn hp_regeneration(&self) -> u32 {
+        let from_perks: u32 = self
+            .perk_effects()
+            .map(|effect| match effect {
+                PerkEffect::HpRegeneration { amount } => *amount,
+                _ => 0,
+            })
+            .sum();
+        self.race.map_or(0, |race| race.hp_regeneration()) + from_perks
+    }
+
+    /// percentage of bonus XP granted by the race and the perks
+    pub fn xp_bonus(&self) -> u32 {
+        let from_perks: u32 = self
+            .perk_effects()
+            .map(|effect| match effect {
+                PerkEffect::XpBonus { percent } => *percent,
+                _ => 0,
+            })
+            .sum();
+        self.race.map_or(0, |race| race.xp_bonus()) + from_perks
+    }
+
+    /// spends an attribute point to raise the attribute; returns false if there is no point left
+    pub fn spend_attribute_point(&mut self, attribute: Attribute) -> bool {
+        if self.level_manager.attribute_points == 0 {
+            return false;
+        }
+        self.level_manager.attribute_points -= 1;
+        attribute.apply(&mut self.stats, 1);
+        true
+    }
+
+    /// returns the indices of the perks the race and class of the Entity allow, in the order of the registry
+    pub fn allowed_perks(&self) -> Vec<usize> {
+        perks()
+            .all()
+            .iter()
+            .enumerate()
+            .filter(|(_, perk)| perk.is_allowed_for(self.race, self.class))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// spends a perk point on the perk at the index of the registry and applies its effects
+    pub fn take_perk(&mut self, index: usize, logger: &mut Logger) {
+        let Some(perk) = perks().get(index) else {
+            return;
+        };
+        if !perk.is_allowed_for(self.race, self.class) {
+            return;
+        }
+        match perk.status(&self.perks) {
+            PerkStatus::Taken => return,
+            PerkStatus::Locked => {
+                logger.push_message(format!(
+                    "{} requires {}",
+                    perk.name,
+                    perk.requires.join(", ")
+                ));
+                return;
+            }
+            PerkStatus::Available => {}
+        }
+        if self.level_manager.perk_points == 0 {
+            logger.push_message(format!("{} has no perk point left", self.symbol()));
+            return;
+        }
+        self.level_manager.perk_points -= 1;
+        self.perks.push(perk.id.clone());
+
+        // the passive effects are read from the perks when needed
+        for effect in perk.effects.iter() {
+            match effect {
+                PerkEffect::Stat { attribute, amount } => attribute.apply(&mut self.stats, *amount),
+                PerkEffect::Resistance { element, amount } => {
+                    self.resistances = self
+                        .resistances
+                        .combine(&Resistances::single(*element, *amount))
+                }
+                PerkEffect::Spell { spell } => {
+                    self.spellbook.learn(spells().build(spell));
+                    let spell_index = self.spellbook.spells.len() - 1;
+                    self.action_bar
+                        .assign_to_empty_slot(ActionSlot::Spell(spell_index));
+                }
+                PerkEffect::HpRegeneration { .. }
+                | PerkEffect::ManaRegeneration { .. }
+                | PerkEffect::XpBonus { .. } => {}
+            }
+        }
+        logger.push_message(format!("{} takes the perk {}", self.symbol(), perk.name));
+    }
+
+    pub fn is_immune_to(&self, kind: StatusEffectKind) -> bool {
+        self.race.is_some_and(|race| race.is_immune_to(kind))
+    }
+
+    /// strength reduced by the status effects and the unsatisfied needs
+    pub fn strength(&self) -> u32 {
+        self.stats
+            .strength
+            .saturating_sub(self.status_effects.strength_penalty())
+            .saturating_sub(self.needs_penalty())
+    }
+
+    /// magic reduced by the unsatisfied needs
+    pub fn magic(&self) -> u32 {
+        self.stats.magic.saturating_sub(self.needs_penalty())
+    }
+
+    /// value of the attribute, vitality and spirit counting one point for every 10 PV or mana
+    pub fn attribute_value(&self, attribute: Attribute) -> u32 {
+        match attribute {
+            Attribute::Vitality => self.stats.max_hp / 10,
+            Attribute::Spirit => self.stats.max_mana / 10,
+            Attribute::Defense => self.stats.defense,
+            Attribute::Strength => self.strength(),
+            Attribute::Magic => self.magic(),
+        }
+    }
+
+    fn needs_penalty(&self) -> u32 {
+        self.needs.as_ref().map_or(0, |needs| needs.stat_penalty())
+    }
+
+    /// warmth of the equipment, in degrees
+    pub fn warmth(&self) -> i32 {
+        self.equipment.values().map(|item| item.warmth()).sum()
+    }
+
+    /// drains the needs for the turn and applies the damage of the critical ones
+    fn update_needs(&mut self, map: &Map, logger: &mut Logger) {
+        let felt_temperature = map.ambient_temperature(self.position) + self.warmth();
+        let resting = std::mem::take(&mut self.resting);
+        let Some(needs) = self.needs.as_mut() else {
+            return;
+        };
+        needs.update(felt_temperature, resting);
+        let damage = needs.damage();
+        if damage > 0 {
+            let names: Vec<&str> = [
+                (needs.hunger_level(), needs.hunger_name()),
+                (needs.thirst_level(), needs.thirst_name()),
+                (needs.fatigue_level(), needs.fatigue_name()),
+                (needs.temperature_level(), needs.temperature_name()),
+            ]
+            .into_iter()
+            .filter(|(level, _)| *level == NeedLevel::Critical)
+            .map(|(_, name)| name)
+            .collect();
+            let lost = self.lose_hp(damage);
+            logger.push_message(format!(
+                "{} is {} (-{} PV){}",
+                self.symbol(),
+                names.join(" and "),
+                lost,
+                if self.is_dead() { " and died" } else { "" }
+            ));
+        }
+    }
+
+    /// uses the item at the index of the inventory, and removes it once it has no charges left
+    pub fn use_item(&mut self, index: usize, map: &mut Map, logger: &mut Logger) {
+        if let Some(item) = self.inventory.remove(index) {
+            item.use_on(self, map, logger);
+            if let Some(item) = item.consume_charge() {
+                self.inventory.insert(index, item);
+            }
+        }
+    }
+
+    /// returns the index in the inventory of the first item built from the definition with the id
+    pub fn find_item(&self, id: &str) -> Option<usize> {
+        let definition = items().get(id)?;
+        self.inventory
+            .items()
+            .iter()
+            .position(|item| definition.matches(item))
+    }
+
+    /// restores hp without exceeding max_hp and returns the hp actually restored; dead entities can't be healed
+    pub fn heal(&mut self, amount: u32) -> u32 {
+        if self.is_dead() {
+            return 0;
+        }
+        let healed = std::cmp::min(amount, self.stats.max_hp - self.stats.hp);
+        self.stats.hp += healed;
+        healed
+    }
+
+    /// returns the innate resistances combined with the ones granted by the equipment
+    pub fn total_resistances(&self) -> Resistances {
+        self.equipment
+            .values()
+            .fold(self.resistances, |total, item| {
+                total.combine(&item.resistances())
+            })
+    }
+
     pub fn is_dead(&self) -> bool {
         self.stats.hp == 0
     }
@@ -265,8 +645,8 @@ impl Entity {
                 y: screen_y as u16,
             };
 
-            let mut style = self.kind.style();
-            let mut symbol = self.kind.symbol();
+            let mut style = self.style();
+            let mut symbol = self.symbol();
 
             // changes the style and symbol if the entity is dead
             if self.is_dead() {
@@ -282,14 +662,10 @@ impl Entity {
 
 impl Drawable for Entity {
     fn symbol(&self) -> &'static str {
-        self.kind.symbol()
+        &self.creature.symbol
     }
 
     fn color(&self) -> Color {
-        self.kind.color()
-    }
-
-    fn style(&self) -> Style {
-        self.kind.style()
+        self.creature.color()
     }
 }
@@ -1,3 +1,6 @@
 pub mod AIbehavior;
+pub mod character;
 pub mod controller;
 pub mod entity;
+pub mod needs;
+pub mod status_effect;
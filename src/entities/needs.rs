@@ -0,0 +1,185 @@
+use ratatui::style::Color;
+
+/// hunger, thirst and fatigue go from 0 (satisfied) to this value (starving, dehydrated, exhausted)
+pub const MAX_NEED: u32 = 1000;
+/// body temperature of a healthy creature, in tenths of degree
+pub const NORMAL_BODY_TEMPERATURE: i32 = 370;
+/// range of felt temperatures, in degrees, in which the body temperature goes back to normal, the caves (12°C) being in it
+const COMFORT_RANGE: (i32, i32) = (8, 25);
+
+/// how badly a need is felt, each level giving harsher penalties
+#[derive(Clone, Copy, PartialEq, PartialOrd, Debug)]
+pub enum NeedLevel {
+    Fine,
+    Mild,
+    Severe,
+    Critical,
+}
+
+impl NeedLevel {
+    fn from_value(value: u32) -> Self {
+        match value {
+            0..400 => NeedLevel::Fine,
+            400..700 => NeedLevel::Mild,
+            700..900 => NeedLevel::Severe,
+            _ => NeedLevel::Critical,
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            NeedLevel::Fine => Color::Green,
+            NeedLevel::Mild => Color::Yellow,
+            NeedLevel::Severe => Color::Rgb(255, 140, 0),
+            NeedLevel::Critical => Color::Red,
+        }
+    }
+
+    /// reduction of strength and magic
+    fn stat_penalty(&self) -> u32 {
+        match self {
+            NeedLevel::Fine | NeedLevel::Mild => 0,
+            NeedLevel::Severe => 2,
+            NeedLevel::Critical => 5,
+        }
+    }
+}
+
+/// survival needs of an Entity, drained each turn
+pub struct Needs {
+    pub hunger: u32,
+    pub thirst: u32,
+    pub fatigue: u32,
+    /// in tenths of degree
+    pub body_temperature: i32,
+    /// an undead body neither eats nor drinks
+    eats_and_drinks: bool,
+}
+
+impl Needs {
+    pub fn new(eats_and_drinks: bool) -> Self {
+        Self {
+            hunger: 0,
+            thirst: 0,
+            fatigue: 0,
+            body_temperature: NORMAL_BODY_TEMPERATURE,
+            eats_and_drinks,
+        }
+    }
+
+    /// drains the needs for one turn and moves the body temperature toward the felt temperature, in degrees
+    pub fn update(&mut self, felt_temperature: i32, resting: bool) {
+        if self.eats_and_drinks {
+            self.hunger = (self.hunger + 1).min(MAX_NEED);
+            self.thirst = (self.thirst + 2).min(MAX_NEED);
+        }
+        self.fatigue = if resting {
+            self.fatigue.saturating_sub(10)
+        } else {
+            (self.fatigue + 1).min(MAX_NEED)
+        };
+
+        let (comfort_min, comfort_max) = COMFORT_RANGE;
+        if felt_temperature < comfort_min {
+            // the colder, the faster the body cools down
+            self.body_temperature -= 1 + (comfort_min - felt_temperature) / 10;
+        } else if felt_temperature > comfort_max {
+            self.body_temperature += 1 + (felt_temperature - comfort_max) / 10;
+        } else {
+            self.body_temperature += (NORMAL_BODY_TEMPERATURE - self.body_temperature).signum();
+        }
+        self.body_temperature = self.body_temperature.clamp(250, 450);
+    }
+
+    pub fn eat(&mut self, amount: u32) {
+        self.hunger = self.hunger.saturating_sub(amount);
+    }
+
+    pub fn drink(&mut self, amount: u32) {
+        self.thirst = self.thirst.saturating_sub(amount);
+    }
+
+    pub fn hunger_level(&self) -> NeedLevel {
+        NeedLevel::from_value(self.hunger)
+    }
+
+    pub fn thirst_level(&self) -> NeedLevel {
+        NeedLevel::from_value(self.thirst)
+    }
+
+    pub fn fatigue_level(&self) -> NeedLevel {
+        NeedLevel::from_value(self.fatigue)
+    }
+
+    /// each degree away from the normal body temperature is a step toward hypothermia or heatstroke
+    pub fn temperature_level(&self) -> NeedLevel {
+        match (self.body_temperature - NORMAL_BODY_TEMPERATURE).abs() {
+            0..10 => NeedLevel::Fine,
+            10..20 => NeedLevel::Mild,
+            20..30 => NeedLevel::Severe,
+            _ => NeedLevel::Critical,
+        }
+    }
+
+    pub fn hunger_name(&self) -> &'static str {
+        match self.hunger_level() {
+            NeedLevel::Fine => "sated",
+            NeedLevel::Mild => "hungry",
+            NeedLevel::Severe => "famished",
+            NeedLevel::Critical => "starving",
+        }
+    }
+
+    pub fn thirst_name(&self) -> &'static str {
+        match self.thirst_level() {
+            NeedLevel::Fine => "hydrated",
+            NeedLevel::Mild => "thirsty",
+            NeedLevel::Severe => "parched",
+            NeedLevel::Critical => "dehydrated",
+        }
+    }
+
+    pub fn fatigue_name(&self) -> &'static str {
+        match self.fatigue_level() {
+            NeedLevel::Fine => "rested",
+            NeedLevel::Mild => "tired",
+            NeedLevel::Severe => "weary",
+            NeedLevel::Critical => "exhausted",
+        }
+    }
+
+    pub fn temperature_name(&self) -> &'static str {
+        let cold = self.body_temperature < NORMAL_BODY_TEMPERATURE;
+        match (self.temperature_level(), cold) {
+            (NeedLevel::Fine, _) => "comfortable",
+            (NeedLevel::Mild, true) => "chilly",
+            (NeedLevel::Severe, true) => "freezing",
+            (NeedLevel::Critical, true) => "hypothermic",
+            (NeedLevel::Mild, false) => "warm",
+            (NeedLevel::Severe, false) => "overheating",
+            (NeedLevel::Critical, false) => "heatstroke",
+        }
+    }
+
+    fn levels(&self) -> [NeedLevel; 4] {
+        [
+            self.hunger_level(),
+            self.thirst_level(),
+            self.fatigue_level(),
+            self.temperature_level(),
+        ]
+    }
+
+    /// strength and magic lost because of the needs, the penalties of every need adding up
+    pub fn stat_penalty(&self) -> u32 {
+        self.levels().iter().map(|level| level.stat_penalty()).sum()
+    }
+
+    /// PV lost this turn, one for each critical need
+    pub fn damage(&self) -> u32 {
+        self.levels()
+            .iter()
+            .filter(|&&level| level == NeedLevel::Critical)
+            .count() as u32
+    }
+}
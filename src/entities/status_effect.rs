@@ -0,0 +1,167 @@
+use ratatui::style::Color;
+use serde::Deserialize;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusEffectKind {
+    Burning,
+    Frozen,
+    Poisoned,
+    Stunned,
+}
+
+/// how a new application of an effect combines with the one already active
+enum StackingRule {
+    /// resets the duration and keeps the strongest potency
+    Refresh,
+    /// adds a stack up to the maximum and resets the duration
+    Stack(u32),
+    /// the new application is ignored while the effect is active
+    Ignore,
+}
+
+impl StatusEffectKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            StatusEffectKind::Burning => "burning",
+            StatusEffectKind::Frozen => "frozen",
+            StatusEffectKind::Poisoned => "poisoned",
+            StatusEffectKind::Stunned => "stunned",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            StatusEffectKind::Burning => Color::Rgb(255, 120, 0),
+            StatusEffectKind::Frozen => Color::Rgb(150, 220, 255),
+            StatusEffectKind::Poisoned => Color::Rgb(120, 200, 60),
+            StatusEffectKind::Stunned => Color::Rgb(255, 255, 100),
+        }
+    }
+
+    fn stacking_rule(&self) -> StackingRule {
+        match self {
+            StatusEffectKind::Burning => StackingRule::Refresh,
+            StatusEffectKind::Frozen => StackingRule::Refresh,
+            StatusEffectKind::Poisoned => StackingRule::Stack(5),
+            // stuns can't be chained to lock an entity forever
+            StatusEffectKind::Stunned => StackingRule::Ignore,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct StatusEffect {
+    pub kind: StatusEffectKind,
+    /// remaining turns
+    pub duration: u32,
+    /// damage per turn for damaging effects
+    pub potency: u32,
+    pub stacks: u32,
+}
+
+impl StatusEffect {
+    pub fn new(kind: StatusEffectKind, duration: u32, potency: u32) -> Self {
+        Self {
+            kind,
+            duration,
+            potency,
+            stacks: 1,
+        }
+    }
+
+    /// damage taken at the start of each turn
+    pub fn damage_per_turn(&self) -> u32 {
+        match self.kind {
+            StatusEffectKind::Burning | StatusEffectKind::Poisoned => self.potency * self.stacks,
+            StatusEffectKind::Frozen | StatusEffectKind::Stunned => 0,
+        }
+    }
+}
+
+/// the status effects currently active on an Entity
+#[derive(Default)]
+pub struct StatusEffects {
+    effects: Vec<StatusEffect>,
+}
+
+impl StatusEffects {
+    /// adds the effect following its StackingRule; returns true if the effect was not active before
+    pub fn apply(&mut self, effect: StatusEffect) -> bool {
+        if let Some(active) = self.effects.iter_mut().find(|e| e.kind == effect.kind) {
+            match effect.kind.stacking_rule() {
+                StackingRule::Refresh => {
+                    active.duration = active.duration.max(effect.duration);
+                    active.potency = active.potency.max(effect.potency);
+                }
+                StackingRule::Stack(max_stacks) => {
+                    active.duration = active.duration.max(effect.duration);
+                    active.stacks = (active.stacks + 1).min(max_stacks);
+                }
+                StackingRule::Ignore => {}
+            }
+            return false;
+        }
+        self.effects.push(effect);
+        true
+    }
+
+    pub fn has(&self, kind: StatusEffectKind) -> bool {
+        self.effects.iter().any(|e| e.kind == kind)
+    }
+
+    /// removes the effect and returns true if it was active
+    pub fn cure(&mut self, kind: StatusEffectKind) -> bool {
+        let count = self.effects.len();
+        self.effects.retain(|e| e.kind != kind);
+        count != self.effects.len()
+    }
+
+    pub fn cure_all(&mut self) {
+        self.effects.clear();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &StatusEffect> {
+        self.effects.iter()
+    }
+
+    /// returns false if the effects prevent the entity from acting this turn
+    pub fn can_act(&self) -> bool {
+        if self.has(StatusEffectKind::Stunned) {
+            return false;
+        }
+        // frozen entities are slowed and only act every other turn
+        if let Some(frozen) = self
+            .effects
+            .iter()
+            .find(|e| e.kind == StatusEffectKind::Frozen)
+        {
+            return frozen.duration % 2 == 1;
+        }
+        true
+    }
+
+    /// strength lost because of the effects
+    pub fn strength_penalty(&self) -> u32 {
+        self.effects
+            .iter()
+            .filter(|e| e.kind == StatusEffectKind::Poisoned)
+            .map(|e| e.stacks)
+            .sum()
+    }
+
+    /// decreases the durations and returns the kinds of the effects that expired
+    pub fn decrease_durations(&mut self) -> Vec<StatusEffectKind> {
+        for effect in self.effects.iter_mut() {
+            effect.duration = effect.duration.saturating_sub(1);
+        }
+        let expired = self
+            .effects
+            .iter()
+            .filter(|e| e.duration == 0)
+            .map(|e| e.kind)
+            .collect();
+        self.effects.retain(|e| e.duration > 0);
+        expired
+    }
+}
@@ -0,0 +1,130 @@
+use crate::{
+    data::{
+        items::{ItemDefinition, items},
+        recipes::RecipeDefinition,
+    },
+    entities::entity::Entity,
+    map::{map::Map, structures::structure::WorkbenchKind},
+};
+
+/// distance in tiles from which the workbenches and the containers are used
+pub const CRAFTING_RANGE: i32 = 1;
+
+/// recipe being crafted, the item is added to the inventory once the remaining turns reach 0
+#[derive(Clone, Copy)]
+pub struct Crafting {
+    /// index in the recipe registry
+    pub recipe_index: usize,
+    pub remaining_turns: u32,
+}
+
+/// positions on the layer of the Entity within the crafting range
+fn positions_in_range(position: (i32, i32, i32)) -> impl Iterator<Item = (i32, i32, i32)> {
+    (-CRAFTING_RANGE..=CRAFTING_RANGE).flat_map(move |dx| {
+        (-CRAFTING_RANGE..=CRAFTING_RANGE)
+            .map(move |dy| (position.0 + dx, position.1 + dy, position.2))
+    })
+}
+
+/// returns the workbenches next to the position
+pub fn nearby_workbenches(map: &Map, position: (i32, i32, i32)) -> Vec<WorkbenchKind> {
+    positions_in_range(position)
+        .filter_map(|position| map.get_tile(position)?.structure.as_ref()?.workbench())
+        .collect()
+}
+
+/// counts the items matching the definition in the inventory and the containers nearby, and in the equipment if they are tools
+fn count_available(
+    definition: &ItemDefinition,
+    entity: &Entity,
+    map: &Map,
+    include_equipment: bool,
+) -> u32 {
+    let in_containers = positions_in_range(entity.position)
+        .filter_map(|position| map.get_tile(position)?.structure.as_ref()?.inventory())
+        .flat_map(|inventory| inventory.items());
+    let in_equipment = entity.equipment.values().filter(|_| include_equipment);
+    entity
+        .inventory
+        .items()
+        .iter()
+        .chain(in_containers)
+        .chain(in_equipment)
+        .filter(|item| definition.matches(item))
+        .count() as u32
+}
+
+/// describes what the Entity lacks to craft the recipe, empty if it can be crafted
+pub fn missing_requirements(recipe: &RecipeDefinition, entity: &Entity, map: &Map) -> Vec<String> {
+    let mut missing = Vec::new();
+    if let Some(skill) = &recipe.skill
+        && entity.attribute_value(skill.attribute) < skill.value
+    {
+        missing.push(format!("{} {}", skill.attribute.name(), skill.value));
+    }
+    if let Some(workbench) = recipe.workbench
+        && !nearby_workbenches(map, entity.position).contains(&workbench)
+    {
+        missing.push(format!("an {}", workbench.name()));
+    }
+    // the ids of the items were checked when the recipes were parsed
+    for tool in &recipe.tools {
+        let Some(definition) = items().get(tool) else {
+            continue;
+        };
+        if count_available(definition, entity, map, true) == 0 {
+            missing.push(definition.name.clone());
+        }
+    }
+    for component in &recipe.components {
+        let Some(definition) = items().get(&component.item) else {
+            continue;
+        };
+        let available = count_available(definition, entity, map, false);
+        if available < component.count {
+            missing.push(format!(
+                "{} {}/{}",
+                definition.name, available, component.count
+            ));
+        }
+    }
+    missing
+}
+
+/// removes the components from the inventory of the Entity first, then from the containers nearby
+pub fn take_components(recipe: &RecipeDefinition, entity: &mut Entity, map: &mut Map) {
+    for component in &recipe.components {
+        let Some(definition) = items().get(&component.item) else {
+            continue;
+        };
+        let mut remaining = component.count;
+        while remaining > 0
+            && let Some(index) = entity
+                .inventory
+                .items()
+                .iter()
+                .position(|item| definition.matches(item))
+        {
+            entity.inventory.remove(index);
+            remaining -= 1;
+        }
+        for position in positions_in_range(entity.position) {
+            let Some(inventory) = map
+                .get_tile_mut(position)
+                .and_then(|tile| tile.structure.as_mut())
+                .and_then(|structure| structure.inventory_mut())
+            else {
+                continue;
+            };
+            while remaining > 0
+                && let Some(index) = inventory
+                    .items()
+                    .iter()
+                    .position(|item| definition.matches(item))
+            {
+                inventory.remove(index);
+                remaining -= 1;
+            }
+        }
+    }
+}
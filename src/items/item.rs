@@ -1,4 +1,14 @@
-use crate::entities::entity::Entity;
+use serde::Deserialize;
+
+use crate::{
+    actions::resistances::Resistances,
+    common::utils::Drawable,
+    data::recipes::recipes,
+    entities::{entity::Entity, status_effect::StatusEffectKind},
+    map::map::Map,
+    menu::Logger,
+    systems::scripting,
+};
 
 pub trait Equipable {
     /// returns the EquipmentSlot the Item can be equipped on
@@ -6,7 +16,7 @@ pub trait Equipable {
 }
 
 pub trait Usable {
-    fn use_on(&self, entity: &mut Entity);
+    fn use_on(&self, entity: &mut Entity, map: &mut Map, logger: &mut Logger);
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -18,7 +28,8 @@ pub enum EquipmentSlot {
     Feet,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum WeaponKind {
     Sword,
     Bow,
@@ -27,7 +38,20 @@ pub enum WeaponKind {
     Staff,
 }
 
-#[derive(Clone, Copy)]
+impl WeaponKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            WeaponKind::Sword => "sword",
+            WeaponKind::Bow => "bow",
+            WeaponKind::Axe => "axe",
+            WeaponKind::Dagger => "dagger",
+            WeaponKind::Staff => "staff",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ArmorKind {
     Helmet,
     Chestplate,
@@ -35,16 +59,28 @@ pub enum ArmorKind {
     Boots,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ConsumableKind {
     Potion,
     Scroll,
     Food,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ConsumableEffect {
     Heal(u32),
+    Cure(StatusEffectKind),
+    CureAll,
+    /// reduces the hunger
+    Food(u32),
+    /// reduces the thirst
+    Drink(u32),
+    /// teaches the recipe with the id, defined in data/recipes.toml
+    LearnRecipe(String),
+    /// runs use_item(ctx) of the script data/scripts/<name>.rhai
+    Script(String),
 }
 
 #[derive(Clone, Copy)]
@@ -53,24 +89,35 @@ pub struct WeaponData {
     pub strenght: u32,
 }
 
+impl WeaponData {
+    pub fn kind(&self) -> WeaponKind {
+        self.kind
+    }
+}
+
 #[derive(Clone, Copy)]
 pub struct ArmorData {
     kind: ArmorKind,
     defense: u32,
+    resistances: Resistances,
+    /// degrees added to the temperature felt by the wearer
+    warmth: i32,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub struct ConsumableData {
     kind: ConsumableKind,
     effect: ConsumableEffect,
     charges: u32,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub enum ItemKind {
     Weapon(WeaponData),
     Armor(ArmorData),
     Consumable(ConsumableData),
+    /// used to craft other items
+    Material,
 }
 
 pub struct Item {
@@ -88,6 +135,17 @@ impl Item {
         }
     }
 
+    /// heavy items are dropped by a swimming creature
+    pub fn is_heavy(&self) -> bool {
+        match &self.kind {
+            ItemKind::Weapon(weapon) => matches!(weapon.kind, WeaponKind::Sword | WeaponKind::Axe),
+            ItemKind::Armor(armor) => {
+                matches!(armor.kind, ArmorKind::Chestplate | ArmorKind::Leggings)
+            }
+            _ => false,
+        }
+    }
+
     pub fn new_weapon(name: String, description: String, kind: WeaponKind, strenght: u32) -> Self {
         Self {
             name,
@@ -96,11 +154,23 @@ impl Item {
         }
     }
 
-    pub fn new_armor(name: String, description: String, kind: ArmorKind, defense: u32) -> Self {
+    pub fn new_armor(
+        name: String,
+        description: String,
+        kind: ArmorKind,
+        defense: u32,
+        resistances: Resistances,
+        warmth: i32,
+    ) -> Self {
         Self {
             name,
             description,
-            kind: ItemKind::Armor(ArmorData { kind, defense }),
+            kind: ItemKind::Armor(ArmorData {
+                kind,
+                defense,
+                resistances,
+                warmth,
+            }),
         }
     }
 
@@ -122,6 +192,41 @@ impl Item {
         }
     }
 
+    /// returns the resistances granted when the Item is equipped
+    pub fn resistances(&self) -> Resistances {
+        match &self.kind {
+            ItemKind::Armor(armor_data) => armor_data.resistances,
+            _ => Resistances::default(),
+        }
+    }
+
+    /// returns the warmth granted when the Item is equipped
+    pub fn warmth(&self) -> i32 {
+        match &self.kind {
+            ItemKind::Armor(armor_data) => armor_data.warmth,
+            _ => 0,
+        }
+    }
+
+    /// returns the charges left if the Item is a consumable
+    pub fn charges(&self) -> Option<u32> {
+        match &self.kind {
+            ItemKind::Consumable(consumable_data) => Some(consumable_data.charges),
+            _ => None,
+        }
+    }
+
+    /// removes one charge from a consumable and returns None if it has no charges left
+    pub fn consume_charge(mut self) -> Option<Item> {
+        if let ItemKind::Consumable(consumable_data) = &mut self.kind {
+            consumable_data.charges = consumable_data.charges.saturating_sub(1);
+            if consumable_data.charges == 0 {
+                return None;
+            }
+        }
+        Some(self)
+    }
+
     pub fn get_equipment_slot(&self) -> Option<EquipmentSlot> {
         match &self.kind {
             ItemKind::Weapon(_) => Some(EquipmentSlot::Hand),
@@ -131,26 +236,100 @@ impl Item {
                 ArmorKind::Leggings => Some(EquipmentSlot::Legs),
                 ArmorKind::Boots => Some(EquipmentSlot::Feet),
             },
-            ItemKind::Consumable(_) => None,
+            ItemKind::Consumable(_) | ItemKind::Material => None,
         }
     }
 }
 
 impl Usable for Item {
-    fn use_on(&self, entity: &mut Entity) {
+    fn use_on(&self, entity: &mut Entity, map: &mut Map, logger: &mut Logger) {
         match &self.kind {
             ItemKind::Consumable(consumable_data) => {
-                match consumable_data.effect {
+                match &consumable_data.effect {
                     ConsumableEffect::Heal(amount) => {
-                        entity.stats.hp = (entity.stats.hp + amount).min(entity.stats.max_hp);
-                        println!(
+                        let healed = entity.heal(*amount);
+                        logger.push_message(format!(
                             "{} uses {} and recovers {} HP",
-                            entity.name, self.name, amount
-                        );
+                            entity.symbol(),
+                            self.name,
+                            healed
+                        ));
+                    }
+                    ConsumableEffect::Cure(kind) => {
+                        if entity.status_effects.cure(*kind) {
+                            logger.push_message(format!(
+                                "{} uses {} and is no longer {}",
+                                entity.symbol(),
+                                self.name,
+                                kind.name()
+                            ));
+                        } else {
+                            logger.push_message(format!(
+                                "{} uses {} but is not {}",
+                                entity.symbol(),
+                                self.name,
+                                kind.name()
+                            ));
+                        }
+                    }
+                    ConsumableEffect::CureAll => {
+                        entity.status_effects.cure_all();
+                        logger.push_message(format!(
+                            "{} uses {} and is cured of every effect",
+                            entity.symbol(),
+                            self.name
+                        ));
+                    }
+                    ConsumableEffect::Food(amount) | ConsumableEffect::Drink(amount) => {
+                        let Some(needs) = entity.needs.as_mut() else {
+                            logger.push_message(format!(
+                                "{} has no use for {}",
+                                entity.symbol(),
+                                self.name
+                            ));
+                            return;
+                        };
+                        let verb = match consumable_data.effect {
+                            ConsumableEffect::Food(_) => {
+                                needs.eat(*amount);
+                                "eats"
+                            }
+                            _ => {
+                                needs.drink(*amount);
+                                "drinks"
+                            }
+                        };
+                        logger.push_message(format!("{} {} {}", entity.symbol(), verb, self.name));
+                    }
+                    ConsumableEffect::LearnRecipe(id) => {
+                        let name = recipes()
+                            .find(id)
+                            .map_or(id.as_str(), |recipe| &recipe.name);
+                        if entity.recipes.contains(id) {
+                            logger.push_message(format!(
+                                "{} already knows how to craft {}",
+                                entity.symbol(),
+                                name
+                            ));
+                        } else {
+                            entity.recipes.push(id.clone());
+                            logger.push_message(format!(
+                                "{} reads {} and learns how to craft {}",
+                                entity.symbol(),
+                                self.name,
+                                name
+                            ));
+                        }
+                    }
+                    ConsumableEffect::Script(script) => {
+                        logger.push_message(format!("{} uses {}", entity.symbol(), self.name));
+                        if !scripting::use_item(script, entity, &mut map.scripts, logger) {
+                            logger.push_message(format!("nothing happens ({script} not found)"));
+                        }
                     } // other effects...
                 }
             }
-            _ => println!("{} is not a consumable", self.name),
+            _ => logger.push_message(format!("{} is not a consumable", self.name)),
         }
     }
 }
@@ -1 +1,2 @@
+pub mod crafting;
 pub mod item;
@@ -0,0 +1,12 @@
+//! the game without its terminal: systems::simulation plays the world and its entities headless, app draws them in a terminal
+
+pub mod actions;
+pub mod app;
+pub mod common;
+pub mod data;
+pub mod entities;
+pub mod items;
+pub mod map;
+pub mod menu;
+pub mod screens;
+pub mod systems;
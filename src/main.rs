@@ -1,20 +1,94 @@
 use std::io::stdout;
 
-use app::{App, Config};
-use color_eyre::Result;
+use color_eyre::{Result, eyre::eyre};
 use crossterm::{
     event::{DisableMouseCapture, EnableMouseCapture},
     execute,
 };
+use mireveil::{
+    app::{App, Config},
+    data::{
+        creatures::{CreatureDefinition, creatures},
+        mods,
+    },
+    entities::character::{Class, Race},
+    systems::{replay::Replay, simulation::FightStatistics},
+};
+
+/// fights played by --fight when --fights isn't given
+const DEFAULT_FIGHTS: u32 = 1000;
 
-mod actions;
-mod app;
-mod common;
-mod entities;
-mod items;
-mod map;
-mod menu;
-mod systems;
+/// options of the command line: `mireveil [--replay <file> [--fast] [--headless]]` or `mireveil --fight <creature> [--race <race>] [--class <class>] [--fights <count>]`
+#[derive(Default)]
+struct Options {
+    replay: Option<String>,
+    /// plays the replay without waiting between the inputs
+    fast: bool,
+    /// plays the replay without a terminal and only prints if the final state matches
+    headless: bool,
+    /// creature fought by a character in headless fights
+    fight: Option<&'static CreatureDefinition>,
+    race: Option<Race>,
+    class: Option<Class>,
+    fights: Option<u32>,
+    /// enables the debug console in a release build
+    wizard: bool,
+}
+
+impl Options {
+    fn parse() -> Result<Self> {
+        let mut options = Self::default();
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--replay" => {
+                    options.replay = Some(
+                        args.next()
+                            .ok_or_else(|| eyre!("--replay expects a file"))?,
+                    )
+                }
+                "--fast" => options.fast = true,
+                "--headless" => options.headless = true,
+                "--wizard" => options.wizard = true,
+                "--fight" => {
+                    let name = args.next().unwrap_or_default();
+                    options.fight = Some(
+                        creatures()
+                            .find(&name)
+                            .ok_or_else(|| eyre!("unknown creature {name}"))?,
+                    )
+                }
+                "--race" => {
+                    let name = args.next().unwrap_or_default();
+                    options.race = Some(
+                        Race::ALL
+                            .into_iter()
+                            .find(|race| race.name().eq_ignore_ascii_case(&name))
+                            .ok_or_else(|| eyre!("unknown race {name}"))?,
+                    )
+                }
+                "--class" => {
+                    let name = args.next().unwrap_or_default();
+                    options.class = Some(
+                        Class::ALL
+                            .into_iter()
+                            .find(|class| class.name().eq_ignore_ascii_case(&name))
+                            .ok_or_else(|| eyre!("unknown class {name}"))?,
+                    )
+                }
+                "--fights" => {
+                    options.fights = Some(
+                        args.next()
+                            .and_then(|count| count.parse().ok())
+                            .ok_or_else(|| eyre!("--fights expects a number"))?,
+                    )
+                }
+                _ => return Err(eyre!("unknown argument {arg}")),
+            }
+        }
+        Ok(options)
+    }
+}
 
 /// disables mouse capture even if app crash
 struct MouseGuard;
@@ -37,12 +111,94 @@ impl Drop for MouseGuard {
 fn main() -> Result<()> {
     color_eyre::install()?;
 
+    // the data is merged with the mods before anything reads it
+    if let Err(errors) = mods::load() {
+        for error in errors.iter() {
+            eprintln!("{error}");
+        }
+        return Err(eyre!(
+            "invalid data, fix it or disable the mods in {}",
+            mods::enabled_file().display()
+        ));
+    }
+    let options = Options::parse()?;
+    if let Some(creature) = options.fight {
+        run_fights(
+            options.race.unwrap_or(Race::Human),
+            options.class.unwrap_or(Class::Warrior),
+            creature,
+            options.fights.unwrap_or(DEFAULT_FIGHTS),
+        );
+        return Ok(());
+    }
+    let replay = match &options.replay {
+        Some(path) => {
+            let replay = Replay::load(path).map_err(|e| eyre!(e))?;
+            replay.check_mods().map_err(|e| eyre!(e))?;
+            Some(replay)
+        }
+        None => None,
+    };
+    let mut config = Config::default();
+    config.wizard |= options.wizard;
+    let mut app = App::new(config);
+
+    if let Some(replay) = &replay
+        && options.headless
+    {
+        return run_headless(app, replay);
+    }
+    if let Some(replay) = replay {
+        app.start_playback(replay, options.fast);
+    }
+
     let terminal = ratatui::init();
     let _mouse_guard = MouseGuard::new()?;
-    let config = Config::default();
-    let app = App::new(config);
     let app_result = app.run(terminal);
 
     ratatui::restore();
     app_result
 }
+
+/// replays the game and fails if its final state differs from the recorded one
+fn run_headless(app: App, replay: &Replay) -> Result<()> {
+    let hash = app.run_headless(replay).map_err(|e| eyre!(e))?;
+    match replay.hash {
+        Some(expected) if expected != hash => Err(eyre!(
+            "final state {hash:016x} differs from the recorded {expected:016x}"
+        )),
+        Some(_) => {
+            println!("final state {hash:016x} matches");
+            Ok(())
+        }
+        None => {
+            println!("final state {hash:016x}, the recorded game has no final state");
+            Ok(())
+        }
+    }
+}
+
+/// plays the fights headless and prints their statistics
+fn run_fights(race: Race, class: Class, creature: &'static CreatureDefinition, count: u32) {
+    let statistics = FightStatistics::run(race, class, creature, count);
+    println!(
+        "{} {} vs {}: {} fights",
+        race.name(),
+        class.name(),
+        creature.name,
+        statistics.fights
+    );
+    println!(
+        "win rate: {:.1}% ({} wins, {} losses, {} draws)",
+        statistics.win_rate() * 100.0,
+        statistics.wins,
+        statistics.losses,
+        statistics.fights - statistics.wins - statistics.losses
+    );
+    println!(
+        "average per fight: {:.1} turns, {:.1} damage dealt, {:.1} damage taken",
+        statistics.average(statistics.turns),
+        statistics.average(statistics.damage_dealt),
+        statistics.average(statistics.damage_taken)
+    );
+}
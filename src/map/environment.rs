@@ -0,0 +1,333 @@
+use rand::Rng;
+
+use crate::{
+    actions::action::ActionType,
+    common::utils::Drawable,
+    entities::{
+        entity::Entity,
+        needs::{MAX_NEED, NeedLevel},
+        status_effect::{StatusEffect, StatusEffectKind},
+    },
+    map::{
+        fluids::{FluidKind, GasKind},
+        map::{Direction, Map},
+        tile::TileKind,
+        weather::{self, FOG_SIGHT_RADIUS, WeatherKind},
+    },
+    menu::Logger,
+    systems::random::Stream,
+};
+
+/// density of the smoke given off each turn by a burning tile
+pub const SMOKE_DENSITY: u32 = 4;
+/// density above which a cloud of gas can't grow
+pub const MAX_GAS_DENSITY: u32 = 9;
+/// turns frozen water stays ice
+pub const THAW_TURNS: u32 = 30;
+/// damage taken each turn by an Entity standing in fire
+const FIRE_DAMAGE: u32 = 5;
+/// damage taken each turn by an Entity standing in lava
+const LAVA_DAMAGE: u32 = 15;
+/// damage taken each turn by an exhausted Entity swimming
+const DROWNING_DAMAGE: u32 = 8;
+/// fatigue added each turn spent swimming
+const SWIMMING_FATIGUE: u32 = 15;
+/// chance per turn and per unit of fuel for the fire to spread to a neighbour tile
+const SPREAD_CHANCE_PER_FUEL: f64 = 0.04;
+/// chance per turn for the lava to flow, much slower than the water
+const LAVA_FLOW_CHANCE: f64 = 0.25;
+/// sight radius of the player, reduced while swimming
+const SIGHT_RADIUS: i32 = 50;
+const SWIMMING_SIGHT_RADIUS: i32 = 6;
+/// sight radius on the surface at night
+const NIGHT_SIGHT_RADIUS: i32 = 15;
+
+/// runs one turn of the environment on the loaded chunks: the weather changes, the fire burns and spreads, the fluids flow, the gases spread and fade, the ice thaws, and the entities suffer from what they stand in
+pub fn update(map: &mut Map, entities: &mut [Entity], logger: &mut Logger) {
+    weather::update(map, entities, logger);
+
+    let mut burning = Vec::new();
+    for chunk in map.chunks.values_mut() {
+        for (&z, layer) in chunk.layers.iter_mut() {
+            for ((x, y), tile) in layer.tiles_mut() {
+                // a tile burning out this turn still spreads its fire
+                if tile.fire > 0 {
+                    burning.push((x, y, z));
+                }
+                if tile.update_environment() {
+                    chunk.changed_tiles.insert((x, y, z));
+                }
+            }
+        }
+    }
+
+    // the more fuel a neighbour has, the more likely it catches fire
+    for &position in burning.iter() {
+        for direction in Direction::ALL {
+            let neighbour = direction.coordinates_in_front(position);
+            let fuel = map
+                .get_tile(neighbour)
+                .filter(|tile| tile.fire == 0)
+                .map_or(0, |tile| tile.fuel());
+            if fuel > 0
+                && map
+                    .random
+                    .stream(Stream::Environment)
+                    .random_bool((fuel as f64 * SPREAD_CHANCE_PER_FUEL).min(1.0))
+            {
+                map.ignite(neighbour);
+            }
+        }
+    }
+
+    flow_fluids(map);
+    spread_gases(map);
+
+    for entity in entities.iter_mut().filter(|entity| !entity.is_dead()) {
+        suffer_fluid_and_gas(entity, map, logger);
+        if map
+            .get_tile(entity.position)
+            .is_some_and(|tile| tile.fire > 0)
+        {
+            let damage = entity.take_damage(FIRE_DAMAGE, ActionType::Fire);
+            if let Some(effect) = ActionType::Fire.status_effect(damage)
+                && damage > 0
+                && !entity.is_dead()
+                && !entity.is_immune_to(effect.kind)
+            {
+                entity.status_effects.apply(effect);
+            }
+            logger.push_message(format!(
+                "{} stands in the fire (-{} PV){}",
+                entity.symbol(),
+                damage,
+                if entity.is_dead() { " and died" } else { "" }
+            ));
+        }
+    }
+}
+
+/// moves one unit of fluid from each tile toward its lower neighbours, holes pouring their fluid to the layer below
+fn flow_fluids(map: &mut Map) {
+    let mut flowing = Vec::new();
+    let mut holes = Vec::new();
+    for chunk in map.chunks.values_mut() {
+        for (&z, layer) in chunk.layers.iter_mut() {
+            for ((x, y), tile) in layer.tiles_mut() {
+                match tile.fluid {
+                    Some(fluid) if tile.kind == TileKind::Hole => holes.push(((x, y, z), fluid)),
+                    Some(fluid) if fluid.kind != FluidKind::Ice => flowing.push((x, y, z)),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    for position in flowing {
+        for direction in Direction::ALL {
+            let Some(tile) = map.get_tile(position) else {
+                break;
+            };
+            let Some(fluid) = tile.fluid.filter(|fluid| fluid.kind != FluidKind::Ice) else {
+                break;
+            };
+            let surface = tile.surface();
+            let neighbour = direction.coordinates_in_front(position);
+            // the fluid only flows if the neighbour is at least two units lower, so a pool settles down
+            let flows = map
+                .get_tile(neighbour)
+                .is_some_and(|tile| tile.accepts_fluid() && surface - tile.surface() >= 2);
+            if !flows
+                || (fluid.kind == FluidKind::Lava
+                    && !map
+                        .random
+                        .stream(Stream::Environment)
+                        .random_bool(LAVA_FLOW_CHANCE))
+            {
+                continue;
+            }
+            map.change_tile(position, |tile| {
+                tile.drain(1);
+                true
+            });
+            map.change_tile(neighbour, |tile| {
+                tile.pour(fluid.kind, 1);
+                true
+            });
+        }
+    }
+
+    // the fluid stays in the hole until the layer below is loaded
+    for ((x, y, z), fluid) in holes {
+        let below = (x, y, z - 1);
+        if map.get_tile(below).is_some_and(|tile| tile.accepts_fluid()) {
+            map.change_tile((x, y, z), |tile| {
+                tile.fluid = None;
+                true
+            });
+            map.change_tile(below, |tile| {
+                tile.pour(fluid.kind, fluid.depth);
+                true
+            });
+        }
+    }
+}
+
+/// spreads the gases to their thinner neighbours and lets them fade
+fn spread_gases(map: &mut Map) {
+    let mut clouds = Vec::new();
+    for chunk in map.chunks.values_mut() {
+        for (&z, layer) in chunk.layers.iter_mut() {
+            for ((x, y), tile) in layer.tiles_mut() {
+                if tile.gas.is_some() {
+                    clouds.push((x, y, z));
+                }
+            }
+        }
+    }
+
+    for position in clouds {
+        for direction in Direction::ALL {
+            let Some(gas) = map.get_tile(position).and_then(|tile| tile.gas) else {
+                break;
+            };
+            let neighbour = direction.coordinates_in_front(position);
+            let spreads = map.get_tile(neighbour).is_some_and(|tile| {
+                tile.is_open() && tile.gas.map_or(0, |other| other.density) + 2 <= gas.density
+            });
+            if spreads {
+                map.change_tile(position, |tile| {
+                    tile.thin_gas(1);
+                    true
+                });
+                map.change_tile(neighbour, |tile| {
+                    tile.add_gas(gas.kind, 1);
+                    true
+                });
+            }
+        }
+        if let Some(gas) = map.get_tile(position).and_then(|tile| tile.gas)
+            && map
+                .random
+                .stream(Stream::Environment)
+                .random_bool(gas.kind.decay_chance())
+        {
+            map.change_tile(position, |tile| {
+                tile.thin_gas(1);
+                true
+            });
+        }
+    }
+}
+
+/// burns the Entity in lava, makes it swim in deep water and poisons it in the gases
+fn suffer_fluid_and_gas(entity: &mut Entity, map: &mut Map, logger: &mut Logger) {
+    let Some(tile) = map.get_tile(entity.position) else {
+        return;
+    };
+    let (fluid, gas) = (tile.fluid, tile.gas);
+
+    if let Some(fluid) = fluid {
+        if fluid.kind == FluidKind::Lava {
+            let damage = entity.take_damage(LAVA_DAMAGE, ActionType::Fire);
+            if !entity.is_dead() && !entity.is_immune_to(StatusEffectKind::Burning) {
+                entity
+                    .status_effects
+                    .apply(StatusEffect::new(StatusEffectKind::Burning, 3, 3));
+            }
+            logger.push_message(format!(
+                "{} is burned by the lava (-{} PV){}",
+                entity.symbol(),
+                damage,
+                if entity.is_dead() { " and died" } else { "" }
+            ));
+        } else if fluid.is_deep() {
+            swim(entity, map, logger);
+        }
+    }
+
+    if let Some(gas) = gas {
+        let potency = match gas.kind {
+            GasKind::Poison => gas.density / 2,
+            GasKind::Miasma => 1,
+            GasKind::Smoke => 0,
+        };
+        if potency > 0
+            && !entity.is_dead()
+            && !entity.is_immune_to(StatusEffectKind::Poisoned)
+            && entity.status_effects.apply(StatusEffect::new(
+                StatusEffectKind::Poisoned,
+                3,
+                potency,
+            ))
+        {
+            logger.push_message(format!(
+                "{} breathes the {}",
+                entity.symbol(),
+                gas.kind.name()
+            ));
+        }
+    }
+}
+
+/// the swimming Entity is put out, drops its heavy items, tires and drowns once exhausted
+fn swim(entity: &mut Entity, map: &mut Map, logger: &mut Logger) {
+    entity.status_effects.cure(StatusEffectKind::Burning);
+
+    let heavy_items = entity.inventory.take_if(|item| item.is_heavy());
+    if !heavy_items.is_empty() {
+        logger.push_message(format!(
+            "{} drops {} heavy item(s) to swim",
+            entity.symbol(),
+            heavy_items.len()
+        ));
+        map.change_tile(entity.position, |tile| {
+            heavy_items
+                .into_iter()
+                .for_each(|item| tile.items.add(item));
+            true
+        });
+    }
+
+    let Some(needs) = &mut entity.needs else {
+        return;
+    };
+    needs.fatigue = (needs.fatigue + SWIMMING_FATIGUE).min(MAX_NEED);
+    if needs.fatigue_level() == NeedLevel::Critical {
+        let damage = entity.take_damage(DROWNING_DAMAGE, ActionType::Physical);
+        logger.push_message(format!(
+            "{} is drowning (-{} PV){}",
+            entity.symbol(),
+            damage,
+            if entity.is_dead() { " and died" } else { "" }
+        ));
+    }
+}
+
+/// releases a cloud of gas on the tile
+pub fn release_gas(map: &mut Map, position: (i32, i32, i32), kind: GasKind, density: u32) {
+    map.change_tile(position, |tile| {
+        if !tile.is_open() {
+            return false;
+        }
+        tile.add_gas(kind, density);
+        true
+    });
+}
+
+/// how far can be seen from the position, the water blurring the sight of a swimmer and the fog and the night hiding the distance
+pub fn sight_radius(map: &Map, position: (i32, i32, i32)) -> i32 {
+    let swimming = map
+        .get_tile(position)
+        .and_then(|tile| tile.fluid)
+        .is_some_and(|fluid| fluid.is_deep());
+    if swimming {
+        SWIMMING_SIGHT_RADIUS
+    } else if map.weather.at(position) == WeatherKind::Fog {
+        FOG_SIGHT_RADIUS
+    } else if map.clock.is_night() && map.weather.reaches(position) {
+        NIGHT_SIGHT_RADIUS
+    } else {
+        SIGHT_RADIUS
+    }
+}
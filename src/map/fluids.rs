@@ -0,0 +1,115 @@
+use ratatui::style::Color;
+use serde::Deserialize;
+
+/// depth from which a creature has to swim
+pub const SWIMMING_DEPTH: u32 = 3;
+/// density from which a gas blocks the sight
+const OPAQUE_DENSITY: u32 = 3;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FluidKind {
+    Water,
+    Lava,
+    /// frozen water, it doesn't flow and can be walked on
+    Ice,
+}
+
+impl FluidKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            FluidKind::Water => "water",
+            FluidKind::Lava => "lava",
+            FluidKind::Ice => "ice",
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            FluidKind::Water => "~",
+            FluidKind::Lava => "~",
+            FluidKind::Ice => "≈",
+        }
+    }
+
+    pub fn color(&self, depth: u32) -> Color {
+        // deep water is darker
+        let shade = (depth.min(6) * 15) as u8;
+        match self {
+            FluidKind::Water => Color::Rgb(80 - shade, 140 - shade, 230 - shade),
+            FluidKind::Lava => Color::Rgb(255, 80, 0),
+            FluidKind::Ice => Color::Rgb(200, 230, 255),
+        }
+    }
+}
+
+/// fluid covering a tile, its depth adding to the height of the ground
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Fluid {
+    pub kind: FluidKind,
+    pub depth: u32,
+}
+
+impl Fluid {
+    pub fn new(kind: FluidKind, depth: u32) -> Self {
+        Self { kind, depth }
+    }
+
+    /// true if a creature has to swim in it
+    pub fn is_deep(&self) -> bool {
+        self.kind == FluidKind::Water && self.depth >= SWIMMING_DEPTH
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GasKind {
+    Poison,
+    Smoke,
+    /// rising from the corpses
+    Miasma,
+}
+
+impl GasKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            GasKind::Poison => "poison",
+            GasKind::Smoke => "smoke",
+            GasKind::Miasma => "miasma",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            GasKind::Poison => Color::Rgb(120, 200, 60),
+            GasKind::Smoke => Color::Rgb(120, 120, 120),
+            GasKind::Miasma => Color::Rgb(130, 100, 140),
+        }
+    }
+
+    /// chance each turn that the density of the gas goes down by one
+    pub fn decay_chance(&self) -> f64 {
+        match self {
+            GasKind::Poison => 0.15,
+            GasKind::Smoke => 0.3,
+            GasKind::Miasma => 0.1,
+        }
+    }
+}
+
+/// cloud of gas over a tile
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Gas {
+    pub kind: GasKind,
+    pub density: u32,
+}
+
+impl Gas {
+    pub fn new(kind: GasKind, density: u32) -> Self {
+        Self { kind, density }
+    }
+
+    /// thick smoke and miasma hide what is behind them
+    pub fn blocks_sight(&self) -> bool {
+        self.kind != GasKind::Poison && self.density >= OPAQUE_DENSITY
+    }
+}
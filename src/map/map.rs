@@ -1,19 +1,52 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashSet};
 
+use rand::Rng;
+use rand_chacha::ChaCha8Rng;
 use ratatui::{buffer::Buffer, layout::Rect};
 
-use crate::{common::utils::Drawable, systems::camera::Camera};
+use crate::{
+    actions::projectile::Projectile,
+    common::utils::Drawable,
+    data::items::items,
+    items::item::WeaponKind,
+    systems::{camera::Camera, clock::GameClock, random::Random, scripting::ScriptState},
+};
 
 use super::{
-    structures::structure::{Chest, Door},
-    tile::{Tile, TileKind},
+    fluids::{Fluid, FluidKind},
+    structures::structure::{Chest, Door, Wall, Workbench, WorkbenchKind},
+    tile::{TerrainHit, Tile, TileKind},
+    weather::{Biome, Weather},
 };
 
 /// size in tiles
 pub const CHUNK_SIZE: u16 = 32;
 /// distance in chunk chunks are loaded
 pub const LOAD_DISTANCE: i32 = 2;
+/// chance for a tile of a forest to hold a tree out of the rows
+const SCATTERED_TREE_CHANCE: f64 = 0.04;
+/// ids of the items put in every generated chest
+const CHEST_LOOT: [&str; 12] = [
+    "iron_ingot",
+    "iron_ingot",
+    "wood",
+    "leather",
+    "leather",
+    "herb",
+    "herb",
+    "flask",
+    "hammer",
+    "mortar",
+    "axe",
+    "sword_manual",
+];
+
+/// returns a chest filled with the loot of a generated chest
+fn generated_chest() -> Chest {
+    Chest::with_items(CHEST_LOOT.iter().map(|id| items().build(id)).collect())
+}
 
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum Direction {
     North,
     East,
@@ -22,6 +55,41 @@ pub enum Direction {
 }
 
 impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    /// returns the unit vector of the direction
+    pub fn delta(&self) -> (i32, i32) {
+        match self {
+            Direction::North => (0, -1),
+            Direction::East => (1, 0),
+            Direction::South => (0, 1),
+            Direction::West => (-1, 0),
+        }
+    }
+
+    /// returns the direction along the main axis from one position to another, or None if they are the same
+    pub fn towards(from: (i32, i32, i32), to: (i32, i32, i32)) -> Option<Direction> {
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        if dx == 0 && dy == 0 {
+            None
+        } else if dx.abs() > dy.abs() {
+            Some(if dx > 0 {
+                Direction::East
+            } else {
+                Direction::West
+            })
+        } else if dy > 0 {
+            Some(Direction::South)
+        } else {
+            Some(Direction::North)
+        }
+    }
+
     pub fn coordinates_in_front(&self, position: (i32, i32, i32)) -> (i32, i32, i32) {
         let (x, y, z) = position;
         match self {
@@ -53,6 +121,27 @@ impl Layer {
         }
     }
 
+    /// returns the global coordinates of the tiles of the layer
+    pub fn positions(&self) -> impl Iterator<Item = (i32, i32)> + use<> {
+        let (world_x, world_y) = self.position;
+        (0..CHUNK_SIZE as i32).flat_map(move |local_y| {
+            (0..CHUNK_SIZE as i32).map(move |local_x| (world_x + local_x, world_y + local_y))
+        })
+    }
+
+    /// returns the tiles with their global coordinates on the layer
+    pub fn tiles_mut(&mut self) -> impl Iterator<Item = ((i32, i32), &mut Tile)> {
+        let (world_x, world_y) = self.position;
+        self.tiles
+            .iter_mut()
+            .enumerate()
+            .flat_map(move |(local_y, row)| {
+                row.iter_mut().enumerate().map(move |(local_x, tile)| {
+                    ((world_x + local_x as i32, world_y + local_y as i32), tile)
+                })
+            })
+    }
+
     fn draw(&self, buffer: &mut Buffer, area: Rect, camera: &Camera, _map: &Map) {
         let (chunk_world_x, chunk_world_y) = self.position;
 
@@ -71,39 +160,19 @@ impl Layer {
             if let Some((buf_x, buf_y)) = camera.world_to_screen((global_x, global_y), area) {
                 let tile = &self.tiles[local_y][local_x];
                 let is_visible = self.visible_tiles.contains(&(global_x, global_y));
-                // Draw the tile first if no structure is present
-                if tile.structure.is_none() {
-                    //let style = Camera::grayed_out_style(tile, is_visible);
-                    let style = if is_visible {
-                        tile.style()
-                    } else {
-                        // Apply grayscale to tile style
-                        Camera::grayed_out_style(tile.style())
-                    };
-                    camera.draw_from_screen_coordinates(
-                        &tile.symbol(),
-                        style,
-                        (buf_x, buf_y).into(),
-                        buffer,
-                    );
+                // the tile draws its fire, smoke or structure over the ground
+                let style = if is_visible {
+                    tile.style()
                 } else {
-                    // If there's a structure, draw it instead of the tile
-                    if let Some(structure) = &tile.structure {
-                        let style = if is_visible {
-                            structure.style()
-                        } else {
-                            // Apply grayscale to structure style
-                            Camera::grayed_out_style(structure.style())
-                        };
-
-                        camera.draw_from_screen_coordinates(
-                            structure.symbol(),
-                            style,
-                            (buf_x, buf_y).into(),
-                            buffer,
-                        );
-                    }
-                }
+                    // Apply grayscale to tile style
+                    Camera::grayed_out_style(tile.style())
+                };
+                camera.draw_from_screen_coordinates(
+                    tile.symbol(),
+                    style,
+                    (buf_x, buf_y).into(),
+                    buffer,
+                );
             }
         }
     }
@@ -111,27 +180,54 @@ impl Layer {
 
 /// a Chunk is made up of several layers of CHUNK_SIZE*CHUNK_SIZE tiles
 pub struct Chunk {
-    pub layers: HashMap<i32, Layer>,
+    pub layers: BTreeMap<i32, Layer>,
     pub position: (i32, i32),
+    /// global coordinates of the tiles changed since the chunk was generated
+    pub changed_tiles: HashSet<(i32, i32, i32)>,
 }
 
 impl Chunk {
-    /// creates a new Chunk with one layer already loaded
-    pub fn new(chunk_x: i32, chunk_y: i32, layer: i32) -> Self {
+    /// creates a new Chunk with one layer already loaded, the generator scattering the trees of the forests
+    pub fn new(chunk_x: i32, chunk_y: i32, layer: i32, rng: &mut ChaCha8Rng) -> Self {
         let tiles = (0..CHUNK_SIZE)
             .map(|x| {
                 (0..CHUNK_SIZE)
                     .map(|y| {
                         if x % 32 == 0 && y % 32 == 0 {
                             let mut tile = Tile::new(TileKind::Grass);
-                            tile.add_structure(Box::new(Chest::new()));
+                            tile.add_structure(Box::new(generated_chest()));
                             tile
                         } else if x % 32 == 1 && y % 32 == 1 {
-                            Tile::new(TileKind::Water)
+                            Tile::with_fluid(TileKind::Basin, Fluid::new(basin_fluid(layer), 3))
                         } else if x % 32 == 2 && y % 32 == 2 {
                             let mut tile = Tile::new(TileKind::Grass);
                             tile.add_structure(Box::new(Door::new()));
                             tile
+                        } else if x % 32 == 3 && y % 32 == 3 {
+                            let mut tile = Tile::new(TileKind::Grass);
+                            tile.add_structure(Box::new(Workbench::new(WorkbenchKind::Anvil)));
+                            tile
+                        } else if x % 32 == 4 && y % 32 == 4 {
+                            let mut tile = Tile::new(TileKind::Grass);
+                            tile.add_structure(Box::new(Workbench::new(
+                                WorkbenchKind::AlchemyTable,
+                            )));
+                            tile
+                        } else if x % 32 == 20 && (2..10).contains(&(y % 32)) {
+                            let mut tile = Tile::new(TileKind::Grass);
+                            tile.add_structure(Box::new(Wall {}));
+                            tile
+                        } else if (24..28).contains(&(x % 32)) && (24..28).contains(&(y % 32)) {
+                            Tile::with_fluid(TileKind::Basin, Fluid::new(basin_fluid(layer), 3))
+                        } else if x % 32 == 28 && y % 32 == 26 {
+                            Tile::new(TileKind::Hole)
+                        } else if x % 32 >= 16
+                            && y % 32 >= 16
+                            && (x % 4 == 2 && y % 5 == 1 || rng.random_bool(SCATTERED_TREE_CHANCE))
+                        {
+                            Tile::new(TileKind::Tree)
+                        } else if x % 32 >= 24 && y % 32 < 8 && (x + y) % 3 == 0 {
+                            Tile::new(TileKind::Rock)
                         } else {
                             Tile::new(TileKind::Grass)
                         }
@@ -143,11 +239,12 @@ impl Chunk {
         let world_x = chunk_x * CHUNK_SIZE as i32;
         let world_y = chunk_y * CHUNK_SIZE as i32;
 
-        let mut layers = HashMap::new();
+        let mut layers = BTreeMap::new();
         layers.insert(layer, Layer::new(tiles, (world_x, world_y)));
         Self {
             layers,
             position: (chunk_x, chunk_y),
+            changed_tiles: HashSet::new(),
         }
     }
 
@@ -210,23 +307,84 @@ impl Chunk {
 }
 
 pub struct Map {
-    pub chunks: HashMap<(i32, i32), Chunk>,
+    pub chunks: BTreeMap<(i32, i32), Chunk>,
+    /// projectiles launched during the step, taken by the Simulation at its end
+    pub launched: Vec<Projectile>,
+    pub weather: Weather,
+    pub clock: GameClock,
+    pub random: Random,
+    /// quest flags and spawns of the scripts in this world
+    pub scripts: ScriptState,
+}
+
+/// fluid filling the basins of the layer, lava deep underground
+fn basin_fluid(layer: i32) -> FluidKind {
+    if layer < 0 {
+        FluidKind::Lava
+    } else {
+        FluidKind::Water
+    }
 }
 
+/// temperature of the underground layers, in degrees
+const UNDERGROUND_TEMPERATURE: i32 = 12;
+/// degrees lost on the surface at night, a clear winter night of the temperate biome staying bearable without warm clothes
+const NIGHT_TEMPERATURE_DROP: i32 = 5;
+
 impl Map {
-    pub fn new() -> Self {
+    /// returns the temperature of the air at the position, in degrees, given by the biome, the season, the weather and the night, and colder on the upper layers
+    pub fn ambient_temperature(&self, position: (i32, i32, i32)) -> i32 {
+        match position.2 {
+            z if z < 0 => UNDERGROUND_TEMPERATURE,
+            z => {
+                let night = if self.clock.is_night() {
+                    NIGHT_TEMPERATURE_DROP
+                } else {
+                    0
+                };
+                Biome::at(position).temperature()
+                    + self.clock.season().temperature_modifier()
+                    + self.weather.at(position).temperature_modifier()
+                    - night
+                    - 5 * z
+            }
+        }
+    }
+
+    /// creates an empty world, the seed deciding its generation and every random roll
+    pub fn new(seed: u64) -> Self {
         Self {
-            chunks: HashMap::new(),
+            chunks: BTreeMap::new(),
+            launched: Vec::new(),
+            weather: Weather::new(),
+            clock: GameClock::new(),
+            random: Random::new(seed),
+            scripts: ScriptState::new(),
         }
     }
 
+    /// creates the world and generates the 3x3 chunks around its center
+    pub fn generate(seed: u64) -> Self {
+        let mut map = Self::new(seed);
+        for x in -1..=1 {
+            for y in -1..=1 {
+                map.load_chunk(x, y, 0);
+            }
+        }
+        map
+    }
+
     /// generates one chunk at (x, y) in chunk coordinates with one layer of tiles at the current visible layer
     pub fn load_chunk(&mut self, chunk_x: i32, chunk_y: i32, layer: i32) {
         // finds the chunk or generates it
-        let chunk = self
-            .chunks
-            .entry((chunk_x, chunk_y))
-            .or_insert_with(|| Chunk::new(chunk_x, chunk_y, layer));
+        let chunk = self.chunks.entry((chunk_x, chunk_y)).or_insert_with(|| {
+            Chunk::new(
+                chunk_x,
+                chunk_y,
+                layer,
+                &mut self.random.chunk(chunk_x, chunk_y, layer),
+            )
+        });
 
         // add a layer to the chunk if it doesn't exist yet
         if !chunk.layers.contains_key(&layer) {
@@ -239,10 +397,10 @@ impl Map {
                             if x == 0 && y == 0 {
                                 //Tile::new(TileKind::Wall)
                                 let mut tile = Tile::new(TileKind::Grass);
-                                tile.add_structure(Box::new(Chest::new()));
+                                tile.add_structure(Box::new(generated_chest()));
                                 tile
                             } else if x == 1 && y == 1 {
-                                Tile::new(TileKind::Water)
+                                Tile::with_fluid(TileKind::Basin, Fluid::new(basin_fluid(layer), 3))
                             } else {
                                 Tile::new(TileKind::Grass)
                             }
@@ -284,6 +442,51 @@ impl Map {
             .and_then(|chunk| chunk.get_tile_mut(global_coordinates))
     }
 
+    /// damages what is on the tile and records the change in its chunk, the tool being the weapon held; None if the tile isn't loaded
+    pub fn hit_terrain(
+        &mut self,
+        global_coordinates: (i32, i32, i32),
+        amount: u32,
+        tool: Option<WeaponKind>,
+    ) -> Option<TerrainHit> {
+        let chunk_coordinates =
+            Self::convert_to_chunk_coordinates(global_coordinates.0, global_coordinates.1);
+        let chunk = self.chunks.get_mut(&chunk_coordinates)?;
+        let hit = chunk.get_tile_mut(global_coordinates)?.hit(amount, tool);
+        if let TerrainHit::Damaged { .. } | TerrainHit::Destroyed { .. } = hit {
+            chunk.changed_tiles.insert(global_coordinates);
+        }
+        Some(hit)
+    }
+
+    /// sets the tile on fire, or melts its ice; returns false if nothing changed
+    pub fn ignite(&mut self, global_coordinates: (i32, i32, i32)) -> bool {
+        self.change_tile(global_coordinates, |tile| tile.ignite())
+    }
+
+    /// freezes the water of the tile and puts out its fire; returns false if nothing changed
+    pub fn freeze(&mut self, global_coordinates: (i32, i32, i32)) -> bool {
+        self.change_tile(global_coordinates, |tile| tile.freeze())
+    }
+
+    /// applies the change to the tile and records it in its chunk if it returned true
+    pub fn change_tile(
+        &mut self,
+        global_coordinates: (i32, i32, i32),
+        change: impl FnOnce(&mut Tile) -> bool,
+    ) -> bool {
+        let chunk_coordinates =
+            Self::convert_to_chunk_coordinates(global_coordinates.0, global_coordinates.1);
+        let Some(chunk) = self.chunks.get_mut(&chunk_coordinates) else {
+            return false;
+        };
+        let changed = chunk.get_tile_mut(global_coordinates).is_some_and(change);
+        if changed {
+            chunk.changed_tiles.insert(global_coordinates);
+        }
+        changed
+    }
+
     /// returns chunks potentially visible to the camera
     pub fn get_visible_chunks(&self, area: Rect, camera: &Camera) -> Vec<&Chunk> {
         // Déterminer les chunks qui sont potentiellement visibles
@@ -312,13 +515,6 @@ impl Map {
 
 impl Default for Map {
     fn default() -> Self {
-        let mut map = Self::new();
-        // 3x3 chunks
-        for x in -1..=1 {
-            for y in -1..=1 {
-                map.load_chunk(x, y, 0);
-            }
-        }
-        map
+        Self::generate(Random::random_seed())
     }
 }
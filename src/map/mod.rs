@@ -1,3 +1,6 @@
+pub mod environment;
+pub mod fluids;
 pub mod map;
 pub mod structures;
 pub mod tile;
+pub mod weather;
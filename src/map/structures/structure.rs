@@ -1,11 +1,69 @@
 use ratatui::style::Color;
+use serde::Deserialize;
 
 use crate::{
     common::{inventory::Inventory, utils::Drawable},
+    data::{
+        items::items,
+        structures::{StructureDefinition, structures},
+    },
+    items::item::Item,
+    map::tile::TileKind,
     menu::Logger,
 };
 
+/// the structures of the game, described in data/structures.toml
+#[derive(Clone, Copy, PartialEq, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StructureKind {
+    Chest,
+    Wall,
+    Door,
+    Anvil,
+    AlchemyTable,
+}
+
+impl StructureKind {
+    pub const ALL: [StructureKind; 5] = [
+        StructureKind::Chest,
+        StructureKind::Wall,
+        StructureKind::Door,
+        StructureKind::Anvil,
+        StructureKind::AlchemyTable,
+    ];
+
+    /// id of the structure in data/structures.toml
+    pub fn id(&self) -> &'static str {
+        match self {
+            StructureKind::Chest => "chest",
+            StructureKind::Wall => "wall",
+            StructureKind::Door => "door",
+            StructureKind::Anvil => "anvil",
+            StructureKind::AlchemyTable => "alchemy_table",
+        }
+    }
+
+    pub fn definition(&self) -> &'static StructureDefinition {
+        structures().get(*self)
+    }
+
+    /// builds the items left by the structure once destroyed
+    fn drops(&self) -> Vec<Item> {
+        self.definition()
+            .drops
+            .iter()
+            .map(|id| items().build(id))
+            .collect()
+    }
+}
+
 pub trait Structure: Drawable {
+    fn kind(&self) -> StructureKind;
+
+    fn name(&self) -> &'static str {
+        &self.kind().definition().name
+    }
+
     /// true by default
     fn block_sight(&self) -> bool {
         true
@@ -16,6 +74,40 @@ pub trait Structure: Drawable {
         false
     }
     fn interact(&mut self, logger: &mut Logger);
+
+    /// the items stored in the structure, None if it can't store any
+    fn inventory(&self) -> Option<&Inventory> {
+        None
+    }
+
+    fn inventory_mut(&mut self) -> Option<&mut Inventory> {
+        None
+    }
+
+    /// the kind of workbench the structure is, None if nothing can be crafted on it
+    fn workbench(&self) -> Option<WorkbenchKind> {
+        None
+    }
+
+    /// damage needed to destroy the structure, None if it can't be destroyed
+    fn durability(&self) -> Option<u32> {
+        self.kind().definition().durability
+    }
+
+    /// what the ground becomes once the structure is destroyed, unchanged if None
+    fn destroyed_into(&self) -> Option<TileKind> {
+        self.kind().definition().destroyed_into
+    }
+
+    /// items left when the structure is destroyed
+    fn into_drops(self: Box<Self>) -> Vec<Item> {
+        self.kind().drops()
+    }
+
+    /// turns the structure burns once on fire, 0 if it doesn't burn
+    fn fuel(&self) -> u32 {
+        self.kind().definition().fuel
+    }
 }
 
 pub struct Chest {
@@ -30,41 +122,83 @@ impl Chest {
             is_open: false,
         }
     }
+
+    pub fn with_items(items: Vec<Item>) -> Self {
+        let mut chest = Self::new();
+        for item in items {
+            chest.inventory.add(item);
+        }
+        chest
+    }
 }
 
 impl Structure for Chest {
+    fn kind(&self) -> StructureKind {
+        StructureKind::Chest
+    }
+
     fn block_sight(&self) -> bool {
         false
     }
 
+    /// the content of the chest is spilled on the ground
+    fn into_drops(mut self: Box<Self>) -> Vec<Item> {
+        let mut drops = self.inventory.take_all();
+        drops.extend(self.kind().drops());
+        drops
+    }
+
     fn interact(&mut self, logger: &mut Logger) {
         logger.push_message(format!("open the chest"));
+        let names: Vec<&str> = self
+            .inventory
+            .items()
+            .iter()
+            .map(|item| item.name.as_str())
+            .collect();
+        if names.is_empty() {
+            logger.push_message("the chest is empty".to_string());
+        } else {
+            logger.push_message(format!("the chest holds {}", names.join(", ")));
+        }
+    }
+
+    fn inventory(&self) -> Option<&Inventory> {
+        Some(&self.inventory)
+    }
+
+    fn inventory_mut(&mut self) -> Option<&mut Inventory> {
+        Some(&mut self.inventory)
     }
 }
 
 impl Drawable for Chest {
     fn symbol(&self) -> &'static str {
-        "c"
+        &self.kind().definition().symbol
     }
 
     fn color(&self) -> Color {
-        Color::Rgb(95, 65, 33)
+        self.kind().definition().color()
     }
 }
 
 pub struct Wall {}
 
 impl Structure for Wall {
+    fn kind(&self) -> StructureKind {
+        StructureKind::Wall
+    }
+
     fn interact(&mut self, logger: &mut Logger) {}
 }
 
 impl Drawable for Wall {
     fn symbol(&self) -> &'static str {
-        "#"
+        &self.kind().definition().symbol
     }
 
     fn color(&self) -> Color {
-        Color::Rgb(150, 150, 150)
+        self.kind().definition().color()
     }
 }
 
@@ -79,6 +213,10 @@ impl Door {
 }
 
 impl Structure for Door {
+    fn kind(&self) -> StructureKind {
+        StructureKind::Door
+    }
+
     fn block_sight(&self) -> bool {
         !self.is_open
     }
@@ -99,13 +237,73 @@ impl Structure for Door {
 
 impl Drawable for Door {
     fn symbol(&self) -> &'static str {
-        if self.is_open {
-            return "=";
+        let definition = self.kind().definition();
+        match &definition.open_symbol {
+            Some(open_symbol) if self.is_open => open_symbol,
+            _ => &definition.symbol,
+        }
+    }
+
+    fn color(&self) -> Color {
+        self.kind().definition().color()
+    }
+}
+
+/// the stations on which some recipes are crafted
+#[derive(Clone, Copy, PartialEq, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkbenchKind {
+    Anvil,
+    AlchemyTable,
+}
+
+impl WorkbenchKind {
+    pub fn structure(&self) -> StructureKind {
+        match self {
+            WorkbenchKind::Anvil => StructureKind::Anvil,
+            WorkbenchKind::AlchemyTable => StructureKind::AlchemyTable,
         }
-        "|"
+    }
+
+    pub fn name(&self) -> &'static str {
+        &self.structure().definition().name
+    }
+}
+
+pub struct Workbench {
+    kind: WorkbenchKind,
+}
+
+impl Workbench {
+    pub fn new(kind: WorkbenchKind) -> Self {
+        Self { kind }
+    }
+}
+
+impl Structure for Workbench {
+    fn kind(&self) -> StructureKind {
+        self.kind.structure()
+    }
+
+    fn block_sight(&self) -> bool {
+        false
+    }
+
+    fn interact(&mut self, logger: &mut Logger) {
+        logger.push_message(format!("use the {}", self.kind.name()));
+    }
+
+    fn workbench(&self) -> Option<WorkbenchKind> {
+        Some(self.kind)
+    }
+}
+
+impl Drawable for Workbench {
+    fn symbol(&self) -> &'static str {
+        &self.kind().definition().symbol
     }
 
     fn color(&self) -> Color {
-        Color::Rgb(95, 65, 33)
+        self.kind().definition().color()
     }
 }
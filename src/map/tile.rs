@@ -1,28 +1,76 @@
 use ratatui::style::{Color, Style};
+use serde::Deserialize;
 
-use crate::{common::utils::Drawable, menu::Logger};
+use crate::{
+    common::{inventory::Inventory, utils::Drawable},
+    data::{
+        items::items,
+        tiles::{TileDefinition, tiles},
+    },
+    items::item::{Item, WeaponKind},
+    menu::Logger,
+};
 
-use super::structures::structure::Structure;
+use super::{
+    environment::{MAX_GAS_DENSITY, SMOKE_DENSITY, THAW_TURNS},
+    fluids::{Fluid, FluidKind, Gas, GasKind},
+    structures::structure::Structure,
+};
 
-#[derive(Clone)]
+#[derive(Clone, Copy, PartialEq, Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum TileKind {
     Grass,
-    Water,
+    /// hollow in the ground where the fluids gather
+    Basin,
+    Tree,
+    Rock,
+    /// what is left of a rock or a wall
+    Rubble,
+    /// burned ground
+    Ash,
+    /// opening to the layer below, the fluids falling through it
+    Hole,
 }
 
 impl TileKind {
-    pub fn symbol(&self) -> &'static str {
+    pub const ALL: [TileKind; 7] = [
+        TileKind::Grass,
+        TileKind::Basin,
+        TileKind::Tree,
+        TileKind::Rock,
+        TileKind::Rubble,
+        TileKind::Ash,
+        TileKind::Hole,
+    ];
+
+    /// id of the tile in data/tiles.toml
+    pub fn id(&self) -> &'static str {
         match self {
-            TileKind::Grass => ",",
-            TileKind::Water => "~",
+            TileKind::Grass => "grass",
+            TileKind::Basin => "basin",
+            TileKind::Tree => "tree",
+            TileKind::Rock => "rock",
+            TileKind::Rubble => "rubble",
+            TileKind::Ash => "ash",
+            TileKind::Hole => "hole",
         }
     }
 
+    fn definition(&self) -> &'static TileDefinition {
+        tiles().get(*self)
+    }
+
+    pub fn name(&self) -> &'static str {
+        &self.definition().name
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        &self.definition().symbol
+    }
+
     pub fn color(&self) -> Color {
-        match self {
-            TileKind::Grass => Color::Rgb(0, 102, 0),
-            TileKind::Water => Color::Rgb(51, 102, 204),
-        }
+        self.definition().color()
     }
 
     pub fn style(&self) -> Style {
@@ -30,14 +78,83 @@ impl TileKind {
     }
 
     pub fn is_walkable(&self) -> bool {
-        true
+        self.definition().walkable
+    }
+
+    pub fn blocks_sight(&self) -> bool {
+        self.definition().blocks_sight
+    }
+
+    /// height of the ground, the fluids flowing toward the lower tiles
+    pub fn elevation(&self) -> i32 {
+        self.definition().elevation
+    }
+
+    /// damage needed to destroy the tile, None if it can't be destroyed
+    pub fn durability(&self) -> Option<u32> {
+        self.definition().durability
+    }
+
+    /// weapon to hold to damage the tile
+    pub fn required_tool(&self) -> Option<WeaponKind> {
+        self.definition().tool
+    }
+
+    /// what the tile becomes once destroyed
+    pub fn destroyed_into(&self) -> TileKind {
+        self.definition().destroyed_into.unwrap_or(*self)
+    }
+
+    /// ids of the items left when the tile is destroyed
+    pub fn drops(&self) -> &'static [String] {
+        &self.definition().drops
+    }
+
+    /// turns the tile keeps burning once on fire, 0 if it doesn't burn
+    pub fn fuel(&self) -> u32 {
+        self.definition().fuel
     }
+
+    /// what the tile becomes once burned out
+    pub fn burns_into(&self) -> TileKind {
+        self.definition().burns_into.unwrap_or(*self)
+    }
+}
+
+/// what happened to a tile hit by an Entity
+pub enum TerrainHit {
+    /// nothing on the tile can be damaged
+    Indestructible,
+    /// the tile can only be damaged with this weapon
+    NeedsTool {
+        name: &'static str,
+        tool: WeaponKind,
+    },
+    Damaged {
+        name: &'static str,
+        durability_left: u32,
+    },
+    /// the items left are on the ground of the tile
+    Destroyed {
+        name: &'static str,
+        drops: Vec<String>,
+    },
 }
 
 pub struct Tile {
     pub kind: TileKind,
     /// the structure on the tile
     pub structure: Option<Box<dyn Structure>>,
+    /// items lying on the ground
+    pub items: Inventory,
+    /// damage taken by the structure, or by the tile if there is no structure
+    damage: u32,
+    /// turns of fire left, 0 if the tile isn't burning
+    pub fire: u32,
+    pub fluid: Option<Fluid>,
+    pub gas: Option<Gas>,
+    /// turns before the ice melts back into water
+    pub thaw: u32,
 }
 
 impl Tile {
@@ -45,6 +162,246 @@ impl Tile {
         Self {
             structure: None,
             kind,
+            items: Inventory::new(),
+            damage: 0,
+            fire: 0,
+            fluid: None,
+            gas: None,
+            thaw: 0,
+        }
+    }
+
+    pub fn with_fluid(kind: TileKind, fluid: Fluid) -> Self {
+        let mut tile = Self::new(kind);
+        tile.fluid = Some(fluid);
+        tile
+    }
+
+    /// damages the structure, or the tile itself if there is no structure, the tool being the weapon held
+    pub fn hit(&mut self, amount: u32, tool: Option<WeaponKind>) -> TerrainHit {
+        let (name, durability) = match &self.structure {
+            Some(structure) => (structure.name(), structure.durability()),
+            None => (self.kind.name(), self.kind.durability()),
+        };
+        let Some(durability) = durability else {
+            return TerrainHit::Indestructible;
+        };
+        if self.structure.is_none()
+            && let Some(required_tool) = self.kind.required_tool()
+            && tool != Some(required_tool)
+        {
+            return TerrainHit::NeedsTool {
+                name,
+                tool: required_tool,
+            };
+        }
+
+        self.damage += amount;
+        if self.damage < durability {
+            return TerrainHit::Damaged {
+                name,
+                durability_left: durability - self.damage,
+            };
+        }
+        self.damage = 0;
+        let drops: Vec<Item> = match self.structure.take() {
+            Some(structure) => {
+                if let Some(kind) = structure.destroyed_into() {
+                    self.kind = kind;
+                }
+                structure.into_drops()
+            }
+            None => {
+                let drops = self
+                    .kind
+                    .drops()
+                    .iter()
+                    .map(|id| items().build(id))
+                    .collect();
+                self.kind = self.kind.destroyed_into();
+                drops
+            }
+        };
+        let names = drops.iter().map(|item| item.name.clone()).collect();
+        for item in drops {
+            self.items.add(item);
+        }
+        TerrainHit::Destroyed { name, drops: names }
+    }
+
+    /// fuel of the structure, or of the tile if there is no structure
+    pub fn fuel(&self) -> u32 {
+        match &self.structure {
+            Some(structure) => structure.fuel(),
+            None => self.kind.fuel(),
+        }
+    }
+
+    /// sets the tile on fire for as long as its fuel lasts, ice melting instead; returns false if nothing changed
+    pub fn ignite(&mut self) -> bool {
+        match &mut self.fluid {
+            Some(fluid) if fluid.kind == FluidKind::Ice => {
+                fluid.kind = FluidKind::Water;
+                self.thaw = 0;
+                return true;
+            }
+            // nothing burns under water
+            Some(fluid) if fluid.kind == FluidKind::Water => return false,
+            _ => {}
+        }
+        let fuel = self.fuel();
+        if self.fire > 0 || fuel == 0 {
+            return false;
+        }
+        self.fire = fuel;
+        true
+    }
+
+    /// freezes the water and puts out the fire; returns false if nothing changed
+    pub fn freeze(&mut self) -> bool {
+        let extinguished = self.fire > 0;
+        self.fire = 0;
+        match &mut self.fluid {
+            Some(fluid) if fluid.kind != FluidKind::Lava => {
+                fluid.kind = FluidKind::Ice;
+                self.thaw = THAW_TURNS;
+                true
+            }
+            _ => extinguished,
+        }
+    }
+
+    /// puts out the fire; returns false if the tile wasn't burning
+    pub fn extinguish(&mut self) -> bool {
+        let burning = self.fire > 0;
+        self.fire = 0;
+        burning
+    }
+
+    /// name of what is seen on the tile
+    pub fn name(&self) -> &'static str {
+        match (&self.structure, &self.fluid) {
+            (Some(structure), _) => structure.name(),
+            (None, Some(fluid)) => fluid.kind.name(),
+            (None, None) => self.kind.name(),
+        }
+    }
+
+    /// trees and solid structures rise above the ground and draw the lightning
+    pub fn is_tall(&self) -> bool {
+        match &self.structure {
+            Some(structure) => !structure.walkable(),
+            None => self.kind == TileKind::Tree,
+        }
+    }
+
+    /// height of the ground plus the depth of the fluid
+    pub fn surface(&self) -> i32 {
+        self.kind.elevation() + self.fluid.map_or(0, |fluid| fluid.depth as i32)
+    }
+
+    /// true if a fluid or a gas can spread on the tile
+    pub fn is_open(&self) -> bool {
+        !matches!(self.kind, TileKind::Tree | TileKind::Rock)
+            && self
+                .structure
+                .as_ref()
+                .is_none_or(|structure| structure.walkable())
+    }
+
+    /// true if a fluid can flow on the tile, the ice stopping it
+    pub fn accepts_fluid(&self) -> bool {
+        self.is_open() && self.fluid.is_none_or(|fluid| fluid.kind != FluidKind::Ice)
+    }
+
+    /// adds a fluid to the tile; lava and water meeting harden into rock
+    pub fn pour(&mut self, kind: FluidKind, depth: u32) {
+        match &mut self.fluid {
+            Some(fluid) if fluid.kind == kind => fluid.depth += depth,
+            Some(_) => {
+                self.fluid = None;
+                self.kind = TileKind::Rock;
+                self.add_gas(GasKind::Smoke, SMOKE_DENSITY);
+            }
+            None => self.fluid = Some(Fluid::new(kind, depth)),
+        }
+    }
+
+    /// adds one unit of fluid if it can flow on the tile; returns false otherwise
+    pub fn pour_if_open(&mut self, kind: FluidKind) -> bool {
+        if !self.accepts_fluid() {
+            return false;
+        }
+        self.pour(kind, 1);
+        true
+    }
+
+    /// removes some depth of the fluid
+    pub fn drain(&mut self, depth: u32) {
+        if let Some(fluid) = &mut self.fluid {
+            fluid.depth = fluid.depth.saturating_sub(depth);
+            if fluid.depth == 0 {
+                self.fluid = None;
+            }
+        }
+    }
+
+    /// thickens the cloud of the same gas, a denser cloud pushing out a different gas
+    pub fn add_gas(&mut self, kind: GasKind, density: u32) {
+        match &mut self.gas {
+            Some(gas) if gas.kind == kind => {
+                gas.density = (gas.density + density).min(MAX_GAS_DENSITY)
+            }
+            Some(gas) if gas.density > density => {}
+            _ => self.gas = Some(Gas::new(kind, density.min(MAX_GAS_DENSITY))),
+        }
+    }
+
+    /// thins the cloud of gas, removing it once its density reaches 0
+    pub fn thin_gas(&mut self, density: u32) {
+        if let Some(gas) = &mut self.gas {
+            gas.density = gas.density.saturating_sub(density);
+            if gas.density == 0 {
+                self.gas = None;
+            }
+        }
+    }
+
+    /// the fire consumes the structure and leaves the ground burned
+    fn burn_out(&mut self) {
+        self.structure = None;
+        self.kind = self.kind.burns_into();
+        self.damage = 0;
+    }
+
+    /// advances the fire and the ice by one turn, the fire giving off smoke; returns true if the tile changed
+    pub fn update_environment(&mut self) -> bool {
+        let mut changed = false;
+        if self.fire > 0 {
+            self.fire -= 1;
+            self.add_gas(GasKind::Smoke, SMOKE_DENSITY);
+            if self.fire == 0 {
+                self.burn_out();
+            }
+            changed = true;
+        }
+        if let Some(fluid) = &mut self.fluid
+            && fluid.kind == FluidKind::Ice
+        {
+            self.thaw = self.thaw.saturating_sub(1);
+            if self.thaw == 0 {
+                fluid.kind = FluidKind::Water;
+            }
+            changed = true;
+        }
+        changed
+    }
+
+    /// arrows and bolts fly over water and ground but stop on trees, rocks and solid structures
+    pub fn blocks_projectiles(&self) -> bool {
+        match &self.structure {
+            Some(structure) => !structure.walkable(),
+            None => self.kind.blocks_sight(),
         }
     }
 
@@ -68,10 +425,13 @@ impl Tile {
     }
 
     pub fn block_sight(&self) -> bool {
+        if self.gas.is_some_and(|gas| gas.blocks_sight()) {
+            return true;
+        }
         if let Some(structure) = &self.structure {
             return structure.block_sight();
         }
-        false
+        self.kind.blocks_sight()
     }
 
     pub fn draw(
@@ -98,24 +458,54 @@ impl Tile {
 
 impl Drawable for Tile {
     fn symbol(&self) -> &'static str {
-        if let Some(structure) = &self.structure {
+        if self.fire > 0 {
+            "^"
+        } else if self.gas.is_some() {
+            "░"
+        } else if let Some(structure) = &self.structure {
             structure.symbol()
+        } else if let Some(fluid) = &self.fluid {
+            fluid.kind.symbol()
+        } else if !self.items.is_empty() {
+            "*"
         } else {
             self.kind.symbol()
         }
     }
 
     fn color(&self) -> Color {
-        if let Some(structure) = &self.structure {
+        if self.fire > 0 {
+            // the flames flicker from one turn to the next
+            if self.fire.is_multiple_of(2) {
+                Color::Rgb(255, 90, 0)
+            } else {
+                Color::Rgb(255, 190, 0)
+            }
+        } else if let Some(gas) = &self.gas {
+            gas.kind.color()
+        } else if let Some(structure) = &self.structure {
             structure.color()
+        } else if let Some(fluid) = &self.fluid {
+            fluid.kind.color(fluid.depth)
+        } else if !self.items.is_empty() {
+            Color::Yellow
         } else {
             self.kind.style().fg.unwrap_or(Color::Reset)
         }
     }
 
     fn style(&self) -> Style {
-        if let Some(structure) = &self.structure {
+        if let Some(structure) = &self.structure
+            && self.fire == 0
+            && self.gas.is_none()
+        {
             structure.style()
+        } else if self.fire > 0
+            || self.gas.is_some()
+            || self.fluid.is_some()
+            || !self.items.is_empty()
+        {
+            Style::default().fg(self.color())
         } else {
             self.kind.style()
         }
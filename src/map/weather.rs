@@ -0,0 +1,348 @@
+use rand::Rng;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+};
+
+use crate::{
+    actions::action::ActionType,
+    common::utils::Drawable,
+    entities::entity::Entity,
+    map::{fluids::FluidKind, map::Map, tile::TileKind},
+    menu::Logger,
+    systems::{
+        camera::Camera,
+        clock::{SECONDS_PER_HOUR, SECONDS_PER_MINUTE, Season},
+        random::Stream,
+    },
+};
+
+/// shortest and longest game time before the weather changes, in seconds
+const WEATHER_DURATION: (u64, u64) = (30 * SECONDS_PER_MINUTE, 4 * SECONDS_PER_HOUR);
+/// chunks away from the center of the world, north or south, where the temperate lands end
+const TEMPERATE_HALF_HEIGHT: i32 = 3;
+/// chance per turn for the rain to put out a burning tile
+const RAIN_EXTINGUISH_CHANCE: f64 = 0.5;
+/// chance per turn for the rain to add water to a basin
+const RAIN_FILL_CHANCE: f64 = 0.02;
+/// chance per turn for the rain to leave a puddle on open ground
+const PUDDLE_CHANCE: f64 = 0.0005;
+/// chance per turn for a puddle to dry up or freeze out of the rain
+const PUDDLE_DRY_CHANCE: f64 = 0.005;
+/// chance per turn for the lightning to strike a tall tile during a storm
+const LIGHTNING_CHANCE: f64 = 0.08;
+const LIGHTNING_DAMAGE: u32 = 15;
+/// chance for a creature to get stuck in the snow instead of moving
+pub const SNOW_SLOW_CHANCE: f64 = 0.4;
+/// sight radius in the fog
+pub const FOG_SIGHT_RADIUS: i32 = 8;
+
+/// lands of the surface, each with its own climate
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Biome {
+    Temperate,
+    /// cold lands of the north
+    Tundra,
+    /// dry lands of the south
+    Desert,
+}
+
+impl Biome {
+    /// returns the biome of the position, depending on how far north or south it is
+    pub fn at(position: (i32, i32, i32)) -> Self {
+        let (_, chunk_y) = Map::convert_to_chunk_coordinates(position.0, position.1);
+        if chunk_y < -TEMPERATE_HALF_HEIGHT {
+            Biome::Tundra
+        } else if chunk_y > TEMPERATE_HALF_HEIGHT {
+            Biome::Desert
+        } else {
+            Biome::Temperate
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Biome::Temperate => "temperate",
+            Biome::Tundra => "tundra",
+            Biome::Desert => "desert",
+        }
+    }
+
+    /// temperature of the air on clear weather, in degrees
+    pub fn temperature(&self) -> i32 {
+        match self {
+            Biome::Temperate => 18,
+            Biome::Tundra => -5,
+            Biome::Desert => 32,
+        }
+    }
+
+    /// weights of each kind of weather, the fog rising in the early hours and the rain turning to snow in winter
+    fn weather_chances(&self, hour: u64, season: Season) -> [(WeatherKind, u32); 5] {
+        let fog = if hour < 8 { 4 } else { 1 };
+        let (rain, snow) = if season == Season::Winter {
+            (0, 3)
+        } else {
+            (3, 0)
+        };
+        match self {
+            Biome::Temperate => [
+                (WeatherKind::Clear, 6),
+                (WeatherKind::Rain, rain),
+                (WeatherKind::Snow, snow),
+                (WeatherKind::Fog, fog),
+                (WeatherKind::Storm, 1),
+            ],
+            Biome::Tundra => [
+                (WeatherKind::Clear, 4),
+                (WeatherKind::Rain, 0),
+                (WeatherKind::Snow, 5),
+                (WeatherKind::Fog, fog),
+                (WeatherKind::Storm, 0),
+            ],
+            Biome::Desert => [
+                (WeatherKind::Clear, 12),
+                (WeatherKind::Rain, 1),
+                (WeatherKind::Snow, 0),
+                (WeatherKind::Fog, 0),
+                (WeatherKind::Storm, 1),
+            ],
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum WeatherKind {
+    Clear,
+    Rain,
+    Snow,
+    Fog,
+    /// heavy rain with lightning striking the tall tiles
+    Storm,
+}
+
+impl WeatherKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            WeatherKind::Clear => "clear",
+            WeatherKind::Rain => "rain",
+            WeatherKind::Snow => "snow",
+            WeatherKind::Fog => "fog",
+            WeatherKind::Storm => "storm",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            WeatherKind::Clear => Color::Yellow,
+            WeatherKind::Rain => Color::Rgb(100, 150, 255),
+            WeatherKind::Snow => Color::White,
+            WeatherKind::Fog => Color::Rgb(170, 170, 170),
+            WeatherKind::Storm => Color::Rgb(160, 120, 255),
+        }
+    }
+
+    /// degrees added to the temperature of the biome
+    pub fn temperature_modifier(&self) -> i32 {
+        match self {
+            WeatherKind::Clear => 0,
+            WeatherKind::Rain => -3,
+            WeatherKind::Snow => -8,
+            WeatherKind::Fog => -1,
+            WeatherKind::Storm => -5,
+        }
+    }
+
+    pub fn is_raining(&self) -> bool {
+        matches!(self, WeatherKind::Rain | WeatherKind::Storm)
+    }
+
+    /// symbol of the particles drawn over the map and percent of the tiles covered
+    fn particles(&self) -> Option<(&'static str, u64)> {
+        match self {
+            WeatherKind::Clear => None,
+            WeatherKind::Rain => Some(("'", 12)),
+            WeatherKind::Snow => Some(("*", 8)),
+            WeatherKind::Fog => Some(("░", 30)),
+            WeatherKind::Storm => Some(("/", 25)),
+        }
+    }
+}
+
+/// weather over the surface, changing after a while depending on the biome, the season and the hour
+pub struct Weather {
+    pub kind: WeatherKind,
+    /// game time when the weather changes, in seconds
+    changes_at: u64,
+}
+
+impl Weather {
+    pub fn new() -> Self {
+        Self {
+            kind: WeatherKind::Clear,
+            changes_at: WEATHER_DURATION.0,
+        }
+    }
+
+    /// the weather only reaches the surface and the layers above it
+    pub fn reaches(&self, position: (i32, i32, i32)) -> bool {
+        position.2 >= 0
+    }
+
+    /// weather felt at the position, clear underground
+    pub fn at(&self, position: (i32, i32, i32)) -> WeatherKind {
+        if self.reaches(position) {
+            self.kind
+        } else {
+            WeatherKind::Clear
+        }
+    }
+
+    /// draws the rain, snow or fog over the visible tiles, the particles moving as the time passes
+    pub fn draw(&self, buffer: &mut Buffer, area: Rect, camera: &Camera, map: &Map) {
+        let Some((symbol, percent)) = self.kind.particles() else {
+            return;
+        };
+        if !self.reaches(camera.position) {
+            return;
+        }
+        let color = self.kind.color();
+        for y in 0..area.height as i32 {
+            for x in 0..area.width as i32 {
+                let position = (
+                    camera.position.0 + x,
+                    camera.position.1 + y,
+                    camera.position.2,
+                );
+                if particle_noise(position, map.clock.seconds()) % 100 < percent
+                    && camera.is_visible_tile(position, map)
+                {
+                    camera.draw_from_global_coordinates(
+                        symbol,
+                        Style::default().fg(color),
+                        position,
+                        buffer,
+                        area,
+                        map,
+                    );
+                }
+            }
+        }
+    }
+}
+
+impl Default for Weather {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// pseudo random number for the position and the time, so the particles stay still until the next action
+fn particle_noise(position: (i32, i32, i32), time: u64) -> u64 {
+    let hash = (position.0 as i64 as u64).wrapping_mul(73_856_093)
+        ^ (position.1 as i64 as u64).wrapping_mul(19_349_663)
+        ^ time.wrapping_mul(83_492_791);
+    hash.wrapping_mul(2_654_435_761) >> 16
+}
+
+/// changes the weather around the player once its time is up and plays one turn of it: the rain puts out the fires and fills the basins, the puddles dry or freeze, and the storm strikes the tall tiles
+pub fn update(map: &mut Map, entities: &mut [Entity], logger: &mut Logger) {
+    let player_position = entities
+        .iter()
+        .find(|entity| entity.is_player())
+        .map_or((0, 0, 0), |entity| entity.position);
+
+    let now = map.clock.seconds();
+    let chances = Biome::at(player_position).weather_chances(map.clock.hour(), map.clock.season());
+    let rng = map.random.stream(Stream::Environment);
+    let weather = &mut map.weather;
+    if now >= weather.changes_at {
+        let total: u32 = chances.iter().map(|(_, weight)| weight).sum();
+        let mut roll = rng.random_range(0..total);
+        for (kind, weight) in chances {
+            if roll < weight {
+                if kind != weather.kind && weather.reaches(player_position) {
+                    logger.push_message(format!("the weather turns to {}", kind.name()));
+                }
+                weather.kind = kind;
+                break;
+            }
+            roll -= weight;
+        }
+        weather.changes_at = now + rng.random_range(WEATHER_DURATION.0..=WEATHER_DURATION.1);
+    }
+    let kind = weather.kind;
+
+    let mut tall_tiles = Vec::new();
+    for chunk in map.chunks.values_mut() {
+        for (&z, layer) in chunk.layers.iter_mut().filter(|(z, _)| **z >= 0) {
+            for ((x, y), tile) in layer.tiles_mut() {
+                let puddle = tile.kind != TileKind::Basin
+                    && tile
+                        .fluid
+                        .is_some_and(|fluid| fluid.kind == FluidKind::Water && fluid.depth == 1);
+                let changed = match kind {
+                    _ if kind.is_raining() && tile.fire > 0 => {
+                        rng.random_bool(RAIN_EXTINGUISH_CHANCE) && tile.extinguish()
+                    }
+                    _ if kind.is_raining() && tile.kind == TileKind::Basin => {
+                        rng.random_bool(RAIN_FILL_CHANCE) && tile.pour_if_open(FluidKind::Water)
+                    }
+                    _ if kind.is_raining() => {
+                        rng.random_bool(PUDDLE_CHANCE) && tile.pour_if_open(FluidKind::Water)
+                    }
+                    WeatherKind::Snow if puddle => {
+                        rng.random_bool(PUDDLE_DRY_CHANCE) && tile.freeze()
+                    }
+                    WeatherKind::Clear if puddle => {
+                        let dries = rng.random_bool(PUDDLE_DRY_CHANCE);
+                        if dries {
+                            tile.drain(1);
+                        }
+                        dries
+                    }
+                    _ => false,
+                };
+                if changed {
+                    chunk.changed_tiles.insert((x, y, z));
+                }
+                if kind == WeatherKind::Storm && tile.is_tall() {
+                    tall_tiles.push((x, y, z));
+                }
+            }
+        }
+    }
+
+    let rng = map.random.stream(Stream::Environment);
+    if !tall_tiles.is_empty() && rng.random_bool(LIGHTNING_CHANCE) {
+        let position = tall_tiles[rng.random_range(0..tall_tiles.len())];
+        strike(map, position, entities, logger);
+    }
+}
+
+/// the lightning sets the tile on fire and hurts the creatures around it
+fn strike(map: &mut Map, position: (i32, i32, i32), entities: &mut [Entity], logger: &mut Logger) {
+    map.ignite(position);
+    let name = map.get_tile(position).map_or("ground", |tile| tile.name());
+    logger.push_message(format!(
+        "lightning strikes the {} at ({}, {})",
+        name, position.0, position.1
+    ));
+    for entity in entities.iter_mut().filter(|entity| !entity.is_dead()) {
+        let (dx, dy) = (
+            entity.position.0 - position.0,
+            entity.position.1 - position.1,
+        );
+        if entity.position.2 != position.2 || dx.abs() > 1 || dy.abs() > 1 {
+            continue;
+        }
+        let damage = entity.take_damage(LIGHTNING_DAMAGE, ActionType::Lightning);
+        logger.push_message(format!(
+            "{} is struck by the lightning (-{} PV){}",
+            entity.symbol(),
+            damage,
+            if entity.is_dead() { " and died" } else { "" }
+        ));
+    }
+}
@@ -3,7 +3,9 @@ use ratatui::{
     widgets::{Block, Borders, Clear, Paragraph, Wrap},
 };
 
-use crate::app::App;
+use crate::{
+    actions::action_bar::ActionSlot, app::App, data::recipes::recipes, map::weather::Biome,
+};
 
 pub struct Logger {
     pub logs: Vec<String>,
@@ -27,7 +29,8 @@ pub struct Menu {
     pub visible: bool,
     pub selected_tile_info: Option<String>,
     pub selected_entity_info: Option<String>,
-    pub logger: Logger,
+    /// tile last clicked, where the console spawns the creatures
+    pub selected_position: Option<(i32, i32, i32)>,
     pub fps: String,
 }
 
@@ -63,6 +66,7 @@ impl Menu {
 
         // add player coordinates at the top
         let (x, y, z) = app
+            .simulation
             .entity_manager
             .get_player_position()
             .unwrap_or((0, 0, 0));
@@ -70,6 +74,154 @@ impl Menu {
             format!("Player: ({}, {}, {})", x, y, z),
             Style::default().fg(Color::Cyan),
         )));
+        let clock = &app.simulation.map.clock;
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("Date: {}", clock.date()),
+                Style::default().fg(clock.season().color()),
+            ),
+            Span::styled(
+                format!(
+                    "  {}{}",
+                    clock.time(),
+                    if clock.is_night() { " (night)" } else { "" }
+                ),
+                Style::default().fg(Color::Cyan),
+            ),
+        ]));
+        let weather = app.simulation.map.weather.at((x, y, z));
+        lines.push(Line::from(vec![
+            Span::styled("Weather:", Style::default().fg(Color::Cyan)),
+            Span::styled(
+                format!(
+                    " {} ({}, {}°C)",
+                    weather.name(),
+                    Biome::at((x, y, z)).name(),
+                    app.simulation.map.ambient_temperature((x, y, z))
+                ),
+                Style::default().fg(weather.color()),
+            ),
+        ]));
+        if let Some(player) = app.simulation.entity_manager.get_player() {
+            lines.push(Line::from(Span::styled(
+                format!(
+                    "HP: {}/{}  Mana: {}/{}",
+                    player.stats.hp, player.stats.max_hp, player.stats.mana, player.stats.max_mana
+                ),
+                Style::default().fg(Color::Cyan),
+            )));
+            let level_manager = &player.level_manager;
+            lines.push(Line::from(Span::styled(
+                format!(
+                    "Level: {}  XP: {}/{}",
+                    level_manager.level,
+                    level_manager.current_xp(),
+                    level_manager.xp_to_next_level()
+                ),
+                Style::default().fg(Color::Cyan),
+            )));
+            if level_manager.attribute_points > 0 || level_manager.perk_points > 0 {
+                lines.push(Line::from(Span::styled(
+                    format!(
+                        "{} attribute and {} perk points to spend (k)",
+                        level_manager.attribute_points, level_manager.perk_points
+                    ),
+                    Style::default().fg(Color::Yellow),
+                )));
+            }
+            if let Some(needs) = &player.needs {
+                lines.push(Line::from(vec![
+                    Span::styled("Needs:", Style::default().fg(Color::Cyan)),
+                    Span::styled(
+                        format!(" {}", needs.hunger_name()),
+                        Style::default().fg(needs.hunger_level().color()),
+                    ),
+                    Span::styled(
+                        format!(" {}", needs.thirst_name()),
+                        Style::default().fg(needs.thirst_level().color()),
+                    ),
+                    Span::styled(
+                        format!(" {}", needs.fatigue_name()),
+                        Style::default().fg(needs.fatigue_level().color()),
+                    ),
+                    Span::styled(
+                        format!(
+                            " {}({:.1}°C)",
+                            needs.temperature_name(),
+                            needs.body_temperature as f32 / 10.0
+                        ),
+                        Style::default().fg(needs.temperature_level().color()),
+                    ),
+                ]));
+            }
+            let mut spans = vec![Span::styled("Status:", Style::default().fg(Color::Cyan))];
+            for effect in player.status_effects.iter() {
+                spans.push(Span::styled(
+                    format!(" {}({})", effect.kind.name(), effect.duration),
+                    Style::default().fg(effect.kind.color()),
+                ));
+            }
+            if spans.len() == 1 {
+                spans.push(Span::styled(" normal", Style::default().fg(Color::Gray)));
+            }
+            lines.push(Line::from(spans));
+
+            // action bar, the selected slot is highlighted and the default action is marked with *
+            let mut spans = vec![Span::styled("Actions:", Style::default().fg(Color::Cyan))];
+            for (slot, entry) in player.action_bar.slots() {
+                let Some(action) = entry.and_then(|entry| player.action(entry)) else {
+                    continue;
+                };
+                let mut style = Style::default().fg(Color::Gray);
+                if slot == player.action_bar.selected_slot {
+                    style = style.fg(Color::Yellow).bold();
+                }
+                spans.push(Span::styled(
+                    format!(
+                        " {}:{}{}",
+                        slot + 1,
+                        action.name(),
+                        match entry {
+                            Some(ActionSlot::Action(index))
+                                if index == player.action_bar.default_action =>
+                            {
+                                "*".to_string()
+                            }
+                            Some(ActionSlot::Spell(index))
+                                if player.spellbook.spells[index].remaining_cooldown > 0 =>
+                            {
+                                format!("({})", player.spellbook.spells[index].remaining_cooldown)
+                            }
+                            _ => String::new(),
+                        }
+                    ),
+                    style,
+                ));
+            }
+            lines.push(Line::from(spans));
+
+            if let Some(casting) = &player.spellbook.casting {
+                lines.push(Line::from(Span::styled(
+                    format!(
+                        "Casting {} ({} turns)",
+                        player.spellbook.spells[casting.spell_index].action.name(),
+                        casting.remaining_turns
+                    ),
+                    Style::default().fg(Color::Magenta),
+                )));
+            }
+            if let Some(crafting) = &player.crafting
+                && let Some(recipe) = recipes().get(crafting.recipe_index)
+            {
+                lines.push(Line::from(Span::styled(
+                    format!(
+                        "Crafting {} ({} turns)",
+                        recipe.name, crafting.remaining_turns
+                    ),
+                    Style::default().fg(Color::Magenta),
+                )));
+            }
+        }
         let (x, y, z) = app.camera.position;
         lines.push(Line::from(Span::styled(
             format!("Camera: ({}, {}, {})", x, y, z),
@@ -107,12 +259,12 @@ impl Menu {
             "Lastest actions:",
             Style::default().fg(Color::White).bold(),
         )));
-        for log in self
-            .logger
+        let logger = &app.simulation.logger;
+        for log in logger
             .logs
             .iter()
             .rev()
-            .take(self.logger.max_displayed_logs as usize)
+            .take(logger.max_displayed_logs as usize)
         {
             lines.push(Line::from(Span::raw(log)));
         }
@@ -130,7 +282,7 @@ impl Default for Menu {
             visible: true,
             selected_tile_info: None,
             selected_entity_info: None,
-            logger: Logger::new(),
+            selected_position: None,
             fps: "".to_string(),
         }
     }
@@ -0,0 +1,243 @@
+use crossterm::event::KeyCode;
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+};
+
+use crate::{
+    entities::{
+        character::{Class, Race},
+        entity::Entity,
+    },
+    systems::random::Random,
+};
+
+use super::{
+    centered_area,
+    mods::{ModSelection, ModsEvent},
+};
+
+const MAX_NAME_LENGTH: usize = 20;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Step {
+    Race,
+    Class,
+    Name,
+    /// seed of the world, filled with a random one
+    Seed,
+}
+
+/// choices of the player on the new game screen
+pub struct CharacterCreation {
+    step: Step,
+    race_index: usize,
+    class_index: usize,
+    name: String,
+    seed: String,
+    /// the mod selection opened from the first step
+    mods: Option<ModSelection>,
+}
+
+/// what the key pressed on the screen leads to
+pub enum CreationEvent {
+    None,
+    /// the player left the screen from the first step
+    Cancel,
+    Done,
+}
+
+impl CharacterCreation {
+    pub fn new() -> Self {
+        Self {
+            step: Step::Race,
+            race_index: 0,
+            class_index: 0,
+            name: String::new(),
+            seed: Random::random_seed().to_string(),
+            mods: None,
+        }
+    }
+
+    pub fn race(&self) -> Race {
+        Race::ALL[self.race_index]
+    }
+
+    pub fn class(&self) -> Class {
+        Class::ALL[self.class_index]
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.trim()
+    }
+
+    /// returns the seed entered, only valid once the screen is done
+    pub fn seed(&self) -> u64 {
+        self.seed.parse().unwrap_or_default()
+    }
+
+    pub fn process_key(&mut self, key_code: KeyCode) -> CreationEvent {
+        if let Some(mods) = &mut self.mods {
+            if let ModsEvent::Close = mods.process_key(key_code) {
+                self.mods = None;
+            }
+            return CreationEvent::None;
+        }
+        match (self.step, key_code) {
+            (Step::Race, KeyCode::Esc) => return CreationEvent::Cancel,
+            (Step::Race, KeyCode::Char('m')) => self.mods = Some(ModSelection::new()),
+            (Step::Race, KeyCode::Up) => {
+                self.race_index = (self.race_index + Race::ALL.len() - 1) % Race::ALL.len()
+            }
+            (Step::Race, KeyCode::Down) => {
+                self.race_index = (self.race_index + 1) % Race::ALL.len()
+            }
+            (Step::Race, KeyCode::Enter) => self.step = Step::Class,
+            (Step::Class, KeyCode::Esc) => self.step = Step::Race,
+            (Step::Class, KeyCode::Up) => {
+                self.class_index = (self.class_index + Class::ALL.len() - 1) % Class::ALL.len()
+            }
+            (Step::Class, KeyCode::Down) => {
+                self.class_index = (self.class_index + 1) % Class::ALL.len()
+            }
+            (Step::Class, KeyCode::Enter) => self.step = Step::Name,
+            (Step::Name, KeyCode::Esc) => self.step = Step::Class,
+            (Step::Name, KeyCode::Backspace) => {
+                self.name.pop();
+            }
+            (Step::Name, KeyCode::Char(c))
+                if !c.is_control() && self.name.chars().count() < MAX_NAME_LENGTH =>
+            {
+                self.name.push(c)
+            }
+            (Step::Name, KeyCode::Enter) if !self.name.trim().is_empty() => self.step = Step::Seed,
+            (Step::Seed, KeyCode::Esc) => self.step = Step::Name,
+            (Step::Seed, KeyCode::Backspace) => {
+                self.seed.pop();
+            }
+            (Step::Seed, KeyCode::Char(c)) if c.is_ascii_digit() => {
+                // keeps only the digits that still fit in the seed
+                let seed = format!("{}{}", self.seed, c);
+                if seed.parse::<u64>().is_ok() {
+                    self.seed = seed;
+                }
+            }
+            (Step::Seed, KeyCode::Enter) if self.seed.parse::<u64>().is_ok() => {
+                return CreationEvent::Done;
+            }
+            _ => {}
+        }
+        CreationEvent::None
+    }
+
+    /// creates the player with the chosen race, class and name
+    pub fn build_player(&self, position: (i32, i32, i32)) -> Entity {
+        Entity::character(self.name().to_string(), self.race(), self.class(), position)
+    }
+
+    pub fn draw(&self, frame: &mut Frame, area: Rect) {
+        if let Some(mods) = &self.mods {
+            mods.draw(frame, area);
+            return;
+        }
+        let popup_area = centered_area(area, 70, 27);
+        frame.render_widget(Clear, popup_area);
+
+        let block = Block::default()
+            .title(" New character ")
+            .borders(Borders::ALL)
+            .border_style(Style::new().light_red())
+            .title_style(Style::new().white().bold())
+            .style(Style::new().bg(Color::Rgb(30, 30, 40)));
+
+        let mut lines = Vec::new();
+        lines.push(Self::title("Race", self.step == Step::Race));
+        for (index, race) in Race::ALL.iter().enumerate() {
+            lines.push(Self::choice(
+                race.name(),
+                race.description(),
+                index == self.race_index,
+                self.step == Step::Race,
+            ));
+        }
+        lines.push(Line::from(""));
+        lines.push(Self::title("Class", self.step == Step::Class));
+        for (index, class) in Class::ALL.iter().enumerate() {
+            lines.push(Self::choice(
+                class.name(),
+                class.description(),
+                index == self.class_index,
+                self.step == Step::Class,
+            ));
+        }
+        lines.push(Line::from(""));
+        lines.push(Self::title("Name", self.step == Step::Name));
+        let cursor = if self.step == Step::Name { "_" } else { "" };
+        lines.push(Line::from(Span::styled(
+            format!("  {}{}", self.name, cursor),
+            Style::default().fg(Color::White),
+        )));
+        lines.push(Line::from(""));
+        lines.push(Self::title("World seed", self.step == Step::Seed));
+        let cursor = if self.step == Step::Seed { "_" } else { "" };
+        lines.push(Line::from(Span::styled(
+            format!("  {}{}", self.seed, cursor),
+            Style::default().fg(Color::White),
+        )));
+
+        // stats of the character with the current choices
+        let preview = self.build_player((0, 0, 0));
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            format!(
+                "HP {}  Mana {}  Defense {}  Strength {}  Magic {}",
+                preview.stats.max_hp,
+                preview.stats.max_mana,
+                preview.stats.defense,
+                preview.stats.strength,
+                preview.stats.magic
+            ),
+            Style::default().fg(Color::Cyan),
+        )));
+        lines.push(Line::from(""));
+        let help = if self.step == Step::Race {
+            "Up/Down: choose  Enter: next  Esc: quit  m: mods"
+        } else {
+            "Up/Down: choose  Enter: next  Esc: back"
+        };
+        lines.push(Line::from(help.dim()));
+
+        let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+        frame.render_widget(paragraph, popup_area);
+    }
+
+    fn title(text: &str, active: bool) -> Line<'static> {
+        let style = if active {
+            Style::default().fg(Color::Yellow).bold()
+        } else {
+            Style::default().fg(Color::Gray)
+        };
+        Line::from(Span::styled(text.to_uppercase(), style))
+    }
+
+    fn choice(name: &str, description: &str, selected: bool, active: bool) -> Line<'static> {
+        let (marker, name_style) = match (selected, active) {
+            (true, true) => ("> ", Style::default().fg(Color::Yellow).bold()),
+            (true, false) => ("  ", Style::default().fg(Color::White).bold()),
+            _ => ("  ", Style::default().fg(Color::Gray)),
+        };
+        Line::from(vec![
+            Span::styled(format!("{}{}", marker, name), name_style),
+            Span::styled(
+                format!(" - {}", description),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ])
+    }
+}
+
+impl Default for CharacterCreation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,138 @@
+use crossterm::event::KeyCode;
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+use crate::systems::wizard;
+
+/// commands kept in the history
+const MAX_HISTORY: usize = 50;
+const HEIGHT: u16 = 8;
+
+/// line where the debug commands are typed, with the history of the commands run
+#[derive(Default)]
+pub struct Console {
+    line: String,
+    history: Vec<String>,
+    /// position in the history browsed with Up and Down, None on a new line
+    history_index: Option<usize>,
+    /// completions of the last word shown after Tab
+    completions: Vec<String>,
+}
+
+/// what the key pressed in the console leads to
+pub enum ConsoleEvent {
+    None,
+    Close,
+    /// the line to run as a command
+    Run(String),
+}
+
+impl Console {
+    pub fn process_key(&mut self, key_code: KeyCode) -> ConsoleEvent {
+        match key_code {
+            KeyCode::Esc => return ConsoleEvent::Close,
+            KeyCode::Enter => {
+                let line = std::mem::take(&mut self.line).trim().to_string();
+                self.history_index = None;
+                self.completions.clear();
+                if line.is_empty() {
+                    return ConsoleEvent::None;
+                }
+                if self.history.last() != Some(&line) {
+                    self.history.push(line.clone());
+                    if self.history.len() > MAX_HISTORY {
+                        self.history.remove(0);
+                    }
+                }
+                return ConsoleEvent::Run(line);
+            }
+            KeyCode::Tab => self.complete(),
+            KeyCode::Up if !self.history.is_empty() => {
+                let index = self
+                    .history_index
+                    .map_or(self.history.len() - 1, |index| index.saturating_sub(1));
+                self.history_index = Some(index);
+                self.line = self.history[index].clone();
+            }
+            KeyCode::Down => match self.history_index {
+                Some(index) if index + 1 < self.history.len() => {
+                    self.history_index = Some(index + 1);
+                    self.line = self.history[index + 1].clone();
+                }
+                Some(_) => {
+                    self.history_index = None;
+                    self.line.clear();
+                }
+                None => {}
+            },
+            KeyCode::Backspace => {
+                self.line.pop();
+                self.completions.clear();
+            }
+            KeyCode::Char(c) => {
+                self.line.push(c);
+                self.completions.clear();
+            }
+            _ => {}
+        }
+        ConsoleEvent::None
+    }
+
+    /// completes the last word if only one completion fits, shows the ones fitting otherwise
+    fn complete(&mut self) {
+        let completions = wizard::completions(&self.line);
+        if let [completion] = completions.as_slice() {
+            let start = if self.line.ends_with(' ') {
+                self.line.len()
+            } else {
+                self.line.rfind(' ').map_or(0, |index| index + 1)
+            };
+            self.line.truncate(start);
+            self.line.push_str(completion);
+            self.line.push(' ');
+            self.completions.clear();
+        } else {
+            self.completions = completions;
+        }
+    }
+
+    /// draws the console at the bottom of the screen
+    pub fn draw(&self, frame: &mut Frame, area: Rect) {
+        let height = HEIGHT.min(area.height);
+        let console_area = Rect::new(area.x, area.bottom() - height, area.width, height);
+        frame.render_widget(Clear, console_area);
+
+        let block = Block::default()
+            .title(" Console ")
+            .borders(Borders::ALL)
+            .border_style(Style::new().light_red())
+            .title_style(Style::new().white().bold())
+            .style(Style::new().bg(Color::Rgb(30, 30, 40)));
+
+        let mut lines: Vec<Line> = self
+            .history
+            .iter()
+            .rev()
+            .take(HEIGHT as usize - 4)
+            .rev()
+            .map(|line| {
+                Line::from(Span::styled(
+                    line.as_str(),
+                    Style::default().fg(Color::Gray),
+                ))
+            })
+            .collect();
+        lines.push(Line::from(Span::styled(
+            format!("> {}_", self.line),
+            Style::default().fg(Color::White),
+        )));
+        if !self.completions.is_empty() {
+            lines.push(Line::from(self.completions.join("  ").dim()));
+        }
+
+        let paragraph = Paragraph::new(lines).block(block);
+        frame.render_widget(paragraph, console_area);
+    }
+}
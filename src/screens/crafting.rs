@@ -0,0 +1,108 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+};
+
+use crate::{
+    data::{items::items, recipes::recipes},
+    entities::entity::Entity,
+    items::crafting::{missing_requirements, nearby_workbenches},
+    map::map::Map,
+};
+
+use super::centered_area;
+
+/// returns the indexes in the recipe registry of the recipes known by the Entity, in the order they were learned
+pub fn known_recipes(entity: &Entity) -> Vec<usize> {
+    entity
+        .recipes
+        .iter()
+        .filter_map(|id| recipes().index_of(id))
+        .collect()
+}
+
+/// draws the pop-up listing the recipes known by the Entity, the craftable ones first being highlighted
+pub fn draw(frame: &mut Frame, area: Rect, entity: &Entity, map: &Map) {
+    let popup_area = centered_area(area, 70, 24);
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(" Crafting ")
+        .borders(Borders::ALL)
+        .border_style(Style::new().light_red())
+        .title_style(Style::new().white().bold())
+        .style(Style::new().bg(Color::Rgb(30, 30, 40)));
+
+    let mut lines = Vec::new();
+    let workbenches: Vec<&str> = nearby_workbenches(map, entity.position)
+        .iter()
+        .map(|workbench| workbench.name())
+        .collect();
+    lines.push(Line::from(Span::styled(
+        if workbenches.is_empty() {
+            "Crafting by hand".to_string()
+        } else {
+            format!("Next to: {}", workbenches.join(", "))
+        },
+        Style::default().fg(Color::Cyan),
+    )));
+    if let Some(crafting) = &entity.crafting
+        && let Some(recipe) = recipes().get(crafting.recipe_index)
+    {
+        lines.push(Line::from(Span::styled(
+            format!(
+                "Crafting {} ({} turns)",
+                recipe.name, crafting.remaining_turns
+            ),
+            Style::default().fg(Color::Magenta),
+        )));
+    }
+    lines.push(Line::from(""));
+
+    let known = known_recipes(entity);
+    if known.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "No recipe known",
+            Style::default().fg(Color::Gray),
+        )));
+    }
+    for (letter_index, recipe) in known
+        .iter()
+        .filter_map(|&index| recipes().get(index))
+        .enumerate()
+        .take(26)
+    {
+        let letter = (b'a' + letter_index as u8) as char;
+        let components: Vec<String> = recipe
+            .components
+            .iter()
+            .filter_map(|component| {
+                let name = &items().get(&component.item)?.name;
+                Some(format!("{} {}", component.count, name))
+            })
+            .collect();
+        let missing = missing_requirements(recipe, entity, map);
+        let (name_style, status) = if missing.is_empty() {
+            (Style::default().fg(Color::Green), String::new())
+        } else {
+            (
+                Style::default().fg(Color::DarkGray),
+                format!(" missing {}", missing.join(", ")),
+            )
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("{}) ", letter), Style::default().fg(Color::Yellow)),
+            Span::styled(recipe.name.clone(), name_style.bold()),
+            Span::styled(
+                format!(" - {} ({} turns)", components.join(", "), recipe.time),
+                Style::default().fg(Color::Gray),
+            ),
+            Span::styled(status, Style::default().fg(Color::Red)),
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from("a-z: craft, Esc: close".dim()));
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+    frame.render_widget(paragraph, popup_area);
+}
@@ -0,0 +1,73 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+};
+
+use crate::{data::spells::SpellEffect, entities::entity::Entity};
+
+use super::centered_area;
+
+/// draws the pop-up listing the branches the spell can evolve into, each one with the letter used to select it
+pub fn draw(frame: &mut Frame, area: Rect, entity: &Entity, spell_index: usize) {
+    let Some(spell) = entity.spellbook.spells.get(spell_index) else {
+        return;
+    };
+    let popup_area = centered_area(area, 60, 20);
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(format!(" {} evolves ", spell.action.name()))
+        .borders(Borders::ALL)
+        .border_style(Style::new().light_red())
+        .title_style(Style::new().white().bold())
+        .style(Style::new().bg(Color::Rgb(30, 30, 40)));
+
+    let mut lines = Vec::new();
+    lines.push(Line::from(Span::styled(
+        format!(
+            "{} reached level {}, choose what it becomes:",
+            spell.action.name(),
+            spell.mastery.level
+        ),
+        Style::default().fg(Color::Cyan),
+    )));
+    for (index, branch) in spell.evolution_branches().iter().enumerate() {
+        let letter = (b'a' + index as u8) as char;
+        let effect = match branch.effect {
+            SpellEffect::Area { .. } => "area",
+            SpellEffect::Projectile { .. } => "projectile",
+        };
+        lines.push(Line::from(""));
+        lines.push(Line::from(vec![
+            Span::styled(format!("{}) ", letter), Style::default().fg(Color::Yellow)),
+            Span::styled(
+                branch.name.clone(),
+                Style::default().fg(Color::White).bold(),
+            ),
+        ]));
+        lines.push(Line::from(Span::styled(
+            format!("   {}", branch.description),
+            Style::default().fg(Color::Gray),
+        )));
+        lines.push(Line::from(Span::styled(
+            format!(
+                "   {} {}, damage {}, {} mana, range {}, cooldown {}, cast {}",
+                branch.school.name(),
+                effect,
+                branch.damage,
+                branch.mana_cost,
+                branch.range,
+                branch.cooldown,
+                branch.casting_time
+            ),
+            Style::default().fg(Color::Gray),
+        )));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(
+        "a-z: choose  Esc: later, from the spellbook".dim(),
+    ));
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+    frame.render_widget(paragraph, popup_area);
+}
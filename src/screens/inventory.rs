@@ -0,0 +1,62 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+};
+
+use crate::entities::entity::Entity;
+
+use super::centered_area;
+
+/// returns the inventory index selected by a letter key
+pub fn index_from_key(c: char) -> Option<usize> {
+    if c.is_ascii_lowercase() {
+        Some((c as u8 - b'a') as usize)
+    } else {
+        None
+    }
+}
+
+/// draws the pop-up listing the items of the Entity, each one with the letter used to select it
+pub fn draw(frame: &mut Frame, area: Rect, entity: &Entity) {
+    let popup_area = centered_area(area, 60, 20);
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(" Inventory ")
+        .borders(Borders::ALL)
+        .border_style(Style::new().light_red())
+        .title_style(Style::new().white().bold())
+        .style(Style::new().bg(Color::Rgb(30, 30, 40)));
+
+    let mut lines = Vec::new();
+    let items = entity.inventory.items();
+    if items.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "Empty",
+            Style::default().fg(Color::Gray),
+        )));
+    }
+    for (index, item) in items.iter().enumerate().take(26) {
+        let letter = (b'a' + index as u8) as char;
+        let charges = match item.charges() {
+            Some(charges) => format!(" ({})", charges),
+            None => String::new(),
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("{}) ", letter), Style::default().fg(Color::Yellow)),
+            Span::styled(
+                format!("{}{}", item.name, charges),
+                Style::default().fg(Color::White),
+            ),
+            Span::styled(
+                format!(" - {}", item.description),
+                Style::default().fg(Color::Gray),
+            ),
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from("a-z: use, Esc: close".dim()));
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+    frame.render_widget(paragraph, popup_area);
+}
@@ -0,0 +1,22 @@
+use ratatui::layout::Rect;
+
+pub mod character_creation;
+pub mod console;
+pub mod crafting;
+pub mod evolution;
+pub mod inventory;
+pub mod mods;
+pub mod skill_tree;
+pub mod spellbook;
+
+/// returns a rect of the given size centered in the area, shrunk if the area is too small
+pub fn centered_area(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
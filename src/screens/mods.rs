@@ -0,0 +1,175 @@
+use crossterm::event::KeyCode;
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+};
+
+use crate::data::mods::{self, Manifest};
+
+use super::centered_area;
+
+/// the mods installed in the mods directory, enabled or disabled from the new game screen
+pub struct ModSelection {
+    mods: Vec<Manifest>,
+    /// ids of the enabled mods
+    enabled: Vec<String>,
+    index: usize,
+    /// errors of the manifests and of the enabled file
+    errors: Vec<String>,
+    /// true once the selection changed, the mods only loading at startup
+    changed: bool,
+}
+
+/// what the key pressed on the screen leads to
+pub enum ModsEvent {
+    None,
+    Close,
+}
+
+impl ModSelection {
+    pub fn new() -> Self {
+        let (mods, mut errors) = mods::installed();
+        let enabled = mods::enabled().unwrap_or_else(|error| {
+            errors.push(error);
+            Vec::new()
+        });
+        Self {
+            mods,
+            enabled,
+            index: 0,
+            errors,
+            changed: false,
+        }
+    }
+
+    pub fn process_key(&mut self, key_code: KeyCode) -> ModsEvent {
+        match key_code {
+            KeyCode::Esc => {
+                if self.changed
+                    && let Err(error) = mods::save_enabled(&self.enabled)
+                {
+                    self.errors.push(error);
+                    return ModsEvent::None;
+                }
+                return ModsEvent::Close;
+            }
+            KeyCode::Up if !self.mods.is_empty() => {
+                self.index = (self.index + self.mods.len() - 1) % self.mods.len()
+            }
+            KeyCode::Down if !self.mods.is_empty() => {
+                self.index = (self.index + 1) % self.mods.len()
+            }
+            KeyCode::Enter | KeyCode::Char(' ') if !self.mods.is_empty() => self.toggle(),
+            _ => {}
+        }
+        ModsEvent::None
+    }
+
+    /// enables the selected mod with its dependencies, or disables it with the mods depending on it
+    fn toggle(&mut self) {
+        let id = self.mods[self.index].id.clone();
+        let mut pending = vec![id.clone()];
+        if self.enabled.contains(&id) {
+            while let Some(id) = pending.pop() {
+                self.enabled.retain(|enabled| *enabled != id);
+                pending.extend(
+                    self.mods
+                        .iter()
+                        .filter(|manifest| {
+                            self.enabled.contains(&manifest.id)
+                                && manifest.dependency_ids().any(|dependency| dependency == id)
+                        })
+                        .map(|manifest| manifest.id.clone()),
+                );
+            }
+        } else {
+            while let Some(id) = pending.pop() {
+                if self.enabled.contains(&id) {
+                    continue;
+                }
+                if let Some(manifest) = self.mods.iter().find(|manifest| manifest.id == id) {
+                    pending.extend(manifest.dependency_ids().map(str::to_string));
+                }
+                self.enabled.push(id);
+            }
+        }
+        self.changed = true;
+    }
+
+    pub fn draw(&self, frame: &mut Frame, area: Rect) {
+        let popup_area = centered_area(area, 70, 27);
+        frame.render_widget(Clear, popup_area);
+
+        let block = Block::default()
+            .title(" Mods ")
+            .borders(Borders::ALL)
+            .border_style(Style::new().light_red())
+            .title_style(Style::new().white().bold())
+            .style(Style::new().bg(Color::Rgb(30, 30, 40)));
+
+        let mut lines = Vec::new();
+        if self.mods.is_empty() {
+            lines.push(Line::from(
+                format!("no mod installed in {}", mods::mods_directory().display()).dim(),
+            ));
+        }
+        for (index, manifest) in self.mods.iter().enumerate() {
+            let selected = index == self.index;
+            let enabled = self.enabled.contains(&manifest.id);
+            let style = match (selected, enabled) {
+                (true, _) => Style::default().fg(Color::Yellow).bold(),
+                (false, true) => Style::default().fg(Color::White),
+                (false, false) => Style::default().fg(Color::Gray),
+            };
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!(
+                        "{}[{}] {} {}",
+                        if selected { "> " } else { "  " },
+                        if enabled { "x" } else { " " },
+                        manifest.name,
+                        manifest.version
+                    ),
+                    style,
+                ),
+                Span::styled(
+                    format!(" - {}", manifest.description),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]));
+            let dependencies: Vec<&str> = manifest.dependency_ids().collect();
+            let mut details = format!("      {}, load order {}", manifest.id, manifest.load_order);
+            if !dependencies.is_empty() {
+                details.push_str(&format!(", needs {}", dependencies.join(", ")));
+            }
+            lines.push(Line::from(details.dim()));
+        }
+        for error in self.errors.iter() {
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                error.as_str(),
+                Style::default().fg(Color::LightRed),
+            )));
+        }
+        if self.changed {
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                "the mods load on the next start",
+                Style::default().fg(Color::Cyan),
+            )));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(
+            "Up/Down: choose  Enter: enable or disable  Esc: save and back".dim(),
+        ));
+
+        let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+        frame.render_widget(paragraph, popup_area);
+    }
+}
+
+impl Default for ModSelection {
+    fn default() -> Self {
+        Self::new()
+    }
+}
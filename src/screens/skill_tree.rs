@@ -0,0 +1,134 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+};
+
+use crate::{
+    data::perks::{PerkStatus, perks},
+    entities::{character::Attribute, entity::Entity},
+};
+
+use super::centered_area;
+
+/// returns the attribute raised by a digit key
+pub fn attribute_from_key(c: char) -> Option<Attribute> {
+    let index = c.to_digit(10)?.checked_sub(1)?;
+    Attribute::ALL.get(index as usize).copied()
+}
+
+/// returns the perks allowed for the Entity as (index in the registry, depth), each perk following the first perk it requires
+pub fn perk_tree(entity: &Entity) -> Vec<(usize, usize)> {
+    let allowed = entity.allowed_perks();
+    let all = perks().all();
+    let parent = |index: usize| {
+        all[index]
+            .requires
+            .first()
+            .and_then(|id| allowed.iter().copied().find(|&other| &all[other].id == id))
+    };
+
+    let mut tree = Vec::new();
+    // the perks without allowed parent are the roots, visited depth first
+    let mut stack: Vec<(usize, usize)> = allowed
+        .iter()
+        .rev()
+        .filter(|&&index| parent(index).is_none())
+        .map(|&index| (index, 0))
+        .collect();
+    while let Some((index, depth)) = stack.pop() {
+        tree.push((index, depth));
+        for &child in allowed.iter().rev() {
+            if parent(child) == Some(index) {
+                stack.push((child, depth + 1));
+            }
+        }
+    }
+    tree
+}
+
+/// draws the pop-up where the player spends the attribute and perk points
+pub fn draw(frame: &mut Frame, area: Rect, entity: &Entity) {
+    let popup_area = centered_area(area, 70, 30);
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(" Skill tree ")
+        .borders(Borders::ALL)
+        .border_style(Style::new().light_red())
+        .title_style(Style::new().white().bold())
+        .style(Style::new().bg(Color::Rgb(30, 30, 40)));
+
+    let mut lines = Vec::new();
+    lines.push(Line::from(Span::styled(
+        format!(
+            "Level {}  HP {}  Mana {}  Defense {}  Strength {}  Magic {}",
+            entity.level_manager.level,
+            entity.stats.max_hp,
+            entity.stats.max_mana,
+            entity.stats.defense,
+            entity.stats.strength,
+            entity.stats.magic
+        ),
+        Style::default().fg(Color::Cyan),
+    )));
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        format!(
+            "ATTRIBUTES ({} points)",
+            entity.level_manager.attribute_points
+        ),
+        Style::default().fg(Color::Yellow).bold(),
+    )));
+    for (index, attribute) in Attribute::ALL.iter().enumerate() {
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("{}) {}", index + 1, attribute.name()),
+                Style::default().fg(Color::White),
+            ),
+            Span::styled(
+                format!(" {}", attribute.description()),
+                Style::default().fg(Color::Gray),
+            ),
+        ]));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        format!("PERKS ({} points)", entity.level_manager.perk_points),
+        Style::default().fg(Color::Yellow).bold(),
+    )));
+    let all = perks().all();
+    for (letter_index, (index, depth)) in perk_tree(entity).into_iter().enumerate() {
+        let perk = &all[index];
+        let letter = (b'a' + letter_index as u8) as char;
+        let (status, style) = match perk.status(&entity.perks) {
+            PerkStatus::Taken => ("taken", Style::default().fg(Color::Green)),
+            PerkStatus::Available => ("", Style::default().fg(Color::White)),
+            PerkStatus::Locked => ("locked", Style::default().fg(Color::DarkGray)),
+        };
+        let branch = if depth > 0 {
+            format!("{}└ ", "  ".repeat(depth - 1))
+        } else {
+            String::new()
+        };
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("{}) {}", letter, branch),
+                Style::default().fg(Color::Gray),
+            ),
+            Span::styled(perk.name.clone(), style.bold()),
+            Span::styled(
+                format!(" - {} {}", perk.description, status),
+                Style::default().fg(Color::Gray),
+            ),
+        ]));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(
+        "1-5: raise an attribute  a-z: take a perk  Esc: close".dim(),
+    ));
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+    frame.render_widget(paragraph, popup_area);
+}
@@ -0,0 +1,86 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+};
+
+use crate::entities::entity::Entity;
+
+use super::centered_area;
+
+/// draws the pop-up listing the spells of the Entity grouped by school
+pub fn draw(frame: &mut Frame, area: Rect, entity: &Entity) {
+    let popup_area = centered_area(area, 60, 24);
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(" Spellbook ")
+        .borders(Borders::ALL)
+        .border_style(Style::new().light_red())
+        .title_style(Style::new().white().bold())
+        .style(Style::new().bg(Color::Rgb(30, 30, 40)));
+
+    let mut lines = Vec::new();
+    lines.push(Line::from(Span::styled(
+        format!(
+            "Mana: {}/{}  Magic: {}",
+            entity.stats.mana, entity.stats.max_mana, entity.stats.magic
+        ),
+        Style::default().fg(Color::Cyan),
+    )));
+    let schools = entity.spellbook.by_school();
+    if schools.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "No spell known",
+            Style::default().fg(Color::Gray),
+        )));
+    }
+    for (school, indices) in schools {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            school.name().to_uppercase(),
+            Style::default().fg(Color::Yellow).bold(),
+        )));
+        for index in indices {
+            let spell = &entity.spellbook.spells[index];
+            let status = if !spell.evolution_branches().is_empty() {
+                Span::styled(" can evolve", Style::default().fg(Color::LightRed))
+            } else if spell.is_ready() {
+                Span::styled(" ready", Style::default().fg(Color::Green))
+            } else {
+                Span::styled(
+                    format!(" {} turns", spell.remaining_cooldown),
+                    Style::default().fg(Color::Red),
+                )
+            };
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("  {}", spell.action.name()),
+                    Style::default().fg(Color::White),
+                ),
+                Span::styled(
+                    format!(
+                        " lvl {} ({}/{} xp) - {} mana, range {}, cooldown {}, cast {}",
+                        spell.mastery.level,
+                        spell.mastery.current_xp(),
+                        spell.mastery.xp_to_next_level(),
+                        spell.action.mana_cost(),
+                        spell.action.range(),
+                        spell.cooldown,
+                        spell.casting_time
+                    ),
+                    Style::default().fg(Color::Gray),
+                ),
+                status,
+            ]));
+        }
+    }
+    lines.push(Line::from(""));
+    if entity.spellbook.pending_evolution().is_some() {
+        lines.push(Line::from("v: evolve  Esc: close".dim()));
+    } else {
+        lines.push(Line::from("Esc: close".dim()));
+    }
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+    frame.render_widget(paragraph, popup_area);
+}
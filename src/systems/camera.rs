@@ -1,5 +1,3 @@
-use std::collections::HashSet;
-
 use ratatui::{
     buffer::Buffer,
     layout::{Position, Rect},
@@ -57,52 +55,6 @@ impl Camera {
         }
     }
 
-    /// updates the visible_tiles of the map based on the player position
-    pub fn update_visibility(&self, player_position: (i32, i32, i32), range: i32, map: &mut Map) {
-        // computes FOV for player
-        let visible = self.compute_fov(player_position, range, map);
-
-        // reset visible tiles
-        for chunk in map.chunks.values_mut() {
-            for layer in chunk.layers.values_mut() {
-                layer.visible_tiles.clear();
-            }
-        }
-
-        // updates visible tiles
-        for (global_x, global_y) in visible {
-            let chunk_coords = Map::convert_to_chunk_coordinates(global_x, global_y);
-            if let Some(chunk) = map.chunks.get_mut(&chunk_coords) {
-                if let Some(layer) = chunk.layers.get_mut(&player_position.2) {
-                    layer.visible_tiles.insert((global_x, global_y));
-                    layer.revealed_tiles.insert((global_x, global_y));
-                }
-            }
-        }
-    }
-
-    /// private method to update visibility; returns the set of coordinates of the tiles visible to the player
-    fn compute_fov(
-        &self,
-        player_position: (i32, i32, i32),
-        range: i32,
-        map: &Map,
-    ) -> HashSet<(i32, i32)> {
-        let mut visible = HashSet::new();
-        for y in (player_position.1 - range)..=(player_position.1 + range) {
-            for x in (player_position.0 - range)..=(player_position.0 + range) {
-                let dx = x - player_position.0;
-                let dy = y - player_position.1;
-                if dx * dx + dy * dy <= range * range {
-                    if self.in_line_of_sight(player_position, (x, y), map) {
-                        visible.insert((x, y));
-                    }
-                }
-            }
-        }
-        visible
-    }
-
     /// returns a grayed-out version of the RGB color
     pub fn grayed_out_color(color: Color) -> Color {
         match color {
@@ -146,6 +98,14 @@ impl Camera {
         }
     }
 
+    /// converts buffer coordinates to a world position
+    pub fn screen_to_world(&self, screen: (u16, u16), area: Rect) -> (i32, i32) {
+        (
+            self.position.0 + screen.0 as i32 - area.x as i32,
+            self.position.1 + screen.1 as i32 - area.y as i32,
+        )
+    }
+
     /// returns true if the point is visible by the camera, false otherwise
     pub fn is_point_on_screen(&self, global_position: (i32, i32, i32), area: Rect) -> bool {
         self.world_to_screen((global_position.0, global_position.1), area) != None
@@ -190,53 +150,4 @@ impl Camera {
         }
         false
     }
-
-    /// create a line between (x0, y0) and (x1, y1)
-    fn bresenham_line(x0: i32, y0: i32, x1: i32, y1: i32) -> Vec<(i32, i32)> {
-        let mut points = Vec::new();
-
-        let dx = (x1 - x0).abs();
-        let dy = -(y1 - y0).abs();
-        let mut err = dx + dy;
-        let mut x = x0;
-        let mut y = y0;
-        let sx = if x0 < x1 { 1 } else { -1 };
-        let sy = if y0 < y1 { 1 } else { -1 };
-
-        loop {
-            points.push((x, y));
-            if x == x1 && y == y1 {
-                break;
-            }
-            let e2 = 2 * err;
-            if e2 >= dy {
-                err += dy;
-                x += sx;
-            }
-            if e2 <= dx {
-                err += dx;
-                y += sy;
-            }
-        }
-        points
-    }
-
-    /// compute all line of sight to finds tiles that are visible by the player
-    pub fn in_line_of_sight(&self, start: (i32, i32, i32), target: (i32, i32), map: &Map) -> bool {
-        let line = Self::bresenham_line(start.0, start.1, target.0, target.1);
-        for (_i, &(x, y)) in line.iter().enumerate().skip(1) {
-            // if it's the target it means its visible
-            if (x, y) == target {
-                return true;
-            }
-            if let Some(tile) = map.get_tile((x, y, self.position.2)) {
-                if tile.block_sight() {
-                    return false;
-                }
-            } else {
-                return false;
-            }
-        }
-        true
-    }
 }
@@ -0,0 +1,133 @@
+use ratatui::style::Color;
+
+pub const SECONDS_PER_MINUTE: u64 = 60;
+pub const SECONDS_PER_HOUR: u64 = 60 * SECONDS_PER_MINUTE;
+pub const SECONDS_PER_DAY: u64 = 24 * SECONDS_PER_HOUR;
+pub const DAYS_PER_SEASON: u64 = 30;
+/// the game starts on the first morning of spring
+const STARTING_TIME: u64 = 8 * SECONDS_PER_HOUR;
+/// hours when the sun rises and sets
+const DAYTIME: (u64, u64) = (6, 20);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    pub const ALL: [Season; 4] = [
+        Season::Spring,
+        Season::Summer,
+        Season::Autumn,
+        Season::Winter,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Season::Spring => "spring",
+            Season::Summer => "summer",
+            Season::Autumn => "autumn",
+            Season::Winter => "winter",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Season::Spring => Color::LightGreen,
+            Season::Summer => Color::Yellow,
+            Season::Autumn => Color::Rgb(255, 140, 0),
+            Season::Winter => Color::White,
+        }
+    }
+
+    /// degrees added to the temperature of the biome
+    pub fn temperature_modifier(&self) -> i32 {
+        match self {
+            Season::Spring => 0,
+            Season::Summer => 6,
+            Season::Autumn => -2,
+            Season::Winter => -5,
+        }
+    }
+}
+
+/// time of the game world, in seconds since the start of the calendar, only moved forward by the actions
+pub struct GameClock {
+    seconds: u64,
+}
+
+impl GameClock {
+    pub fn new() -> Self {
+        Self {
+            seconds: STARTING_TIME,
+        }
+    }
+
+    pub fn seconds(&self) -> u64 {
+        self.seconds
+    }
+
+    pub fn advance(&mut self, seconds: u64) {
+        self.seconds += seconds;
+    }
+
+    pub fn minute(&self) -> u64 {
+        (self.seconds / SECONDS_PER_MINUTE) % 60
+    }
+
+    pub fn hour(&self) -> u64 {
+        (self.seconds / SECONDS_PER_HOUR) % 24
+    }
+
+    /// days since the start of the calendar, starting at 0
+    fn days(&self) -> u64 {
+        self.seconds / SECONDS_PER_DAY
+    }
+
+    /// day of the season, starting at 1
+    pub fn day(&self) -> u64 {
+        self.days() % DAYS_PER_SEASON + 1
+    }
+
+    pub fn season(&self) -> Season {
+        Season::ALL[(self.days() / DAYS_PER_SEASON) as usize % Season::ALL.len()]
+    }
+
+    /// year of the calendar, starting at 1
+    pub fn year(&self) -> u64 {
+        self.days() / (DAYS_PER_SEASON * Season::ALL.len() as u64) + 1
+    }
+
+    pub fn is_night(&self) -> bool {
+        !(DAYTIME.0..DAYTIME.1).contains(&self.hour())
+    }
+
+    /// "day 3 of spring, year 1"
+    pub fn date(&self) -> String {
+        format!(
+            "day {} of {}, year {}",
+            self.day(),
+            self.season().name(),
+            self.year()
+        )
+    }
+
+    /// "08:05"
+    pub fn time(&self) -> String {
+        format!("{:02}:{:02}", self.hour(), self.minute())
+    }
+}
+
+impl Default for GameClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// turns of game time an action lasts, in which every Entity acts: one per minute, at least one
+pub fn elapsed_turns(seconds: u64) -> u32 {
+    (seconds / SECONDS_PER_MINUTE).max(1) as u32
+}
@@ -1,32 +1,97 @@
-use crossterm::event::KeyCode;
 use ratatui::{buffer::Buffer, layout::Rect};
 
-use crate::{common::utils::Drawable, entities::entity::Entity, map::map::Map, menu::Logger};
+use crate::{
+    common::utils::Drawable,
+    entities::{
+        controller::{Controller, PlayerInput},
+        entity::Entity,
+    },
+    map::{environment, fluids::GasKind, map::Map},
+    menu::Logger,
+};
 
-use super::camera::Camera;
+use super::{
+    camera::Camera, clock::elapsed_turns, fov::FieldOfView, replay::StateHasher, scripting,
+};
+
+/// density of the miasma rising from a fresh corpse
+const MIASMA_DENSITY: u32 = 4;
 
 pub struct EntityManager {
     //pub player: Entity,
     entities: Vec<Entity>,
     dead_entities: Vec<Entity>,
+    /// turns played since the start of the game
+    turn: u64,
 }
 
 impl EntityManager {
     pub fn new() -> Self {
         Self {
-            entities: vec![Entity::player((0, 0, 0))],
+            entities: Vec::new(),
             dead_entities: Vec::new(),
+            turn: 0,
         }
     }
 
+    pub fn turn(&self) -> u64 {
+        self.turn
+    }
+
+    /// the player is the first to act each turn
+    pub fn add_player(&mut self, player: Entity) {
+        self.entities.insert(0, player);
+    }
+
     pub fn add_entity(&mut self, entity: Entity) {
         self.entities.push(entity);
     }
 
+    /// plays the input of the player, the game time it lasts being split into turns in which every Entity acts, the status effects tick and the world changes
     pub fn update(
         &mut self,
-        key_code: KeyCode,
-        camera: &Camera,
+        input: PlayerInput,
+        fov: &mut FieldOfView,
+        map: &mut Map,
+        logger: &mut Logger,
+    ) {
+        let elapsed = self
+            .get_player()
+            .map_or(0, |player| Controller::duration(player, input));
+        let turns = elapsed_turns(elapsed);
+        let resting = Controller::is_rest(input);
+        for turn in 0..turns {
+            if turn > 0 {
+                // a rest is cut short by a creature in view
+                if !self.visible_hostiles(fov, map).is_empty() {
+                    logger.push_message("the rest is cut short, a creature is in view".to_string());
+                    break;
+                }
+                if let Some(player) = self.get_player_mut() {
+                    player.resting = resting;
+                }
+            }
+            // the last turn gets what remains of the elapsed seconds
+            let seconds = if turn + 1 < turns {
+                elapsed / turns as u64
+            } else {
+                elapsed - elapsed / turns as u64 * (turns as u64 - 1)
+            };
+            self.play_turn(input, turn == 0, fov, map, logger);
+            map.clock.advance(seconds);
+            if self.get_player().is_none() {
+                break;
+            }
+        }
+        self.turn += 1;
+    }
+
+    /// plays one turn of game time, the player acting only on the first one
+    fn play_turn(
+        &mut self,
+        input: PlayerInput,
+        first: bool,
+        fov: &mut FieldOfView,
         map: &mut Map,
         logger: &mut Logger,
     ) {
@@ -37,16 +102,34 @@ impl EntityManager {
             let mut other_entities: Vec<&mut Entity> =
                 left.iter_mut().chain(right.iter_mut()).collect();
 
-            current.update(key_code, map, other_entities.as_mut_slice(), logger);
+            let input = (first || !current.is_player()).then_some(input);
+            current.update(input, map, other_entities.as_mut_slice(), logger);
             if current.is_player() {
-                camera.update_visibility(current.position, 50, map);
+                fov.update(
+                    current.position,
+                    environment::sight_radius(map, current.position),
+                    map,
+                );
             }
         }
+        environment::update(map, &mut self.entities, logger);
 
-        self.handle_dead_entities();
+        self.handle_dead_entities(map, logger);
+        if let Some(player) = self.get_player_mut() {
+            scripting::on_turn(player, map.clock.hour(), &mut map.scripts, logger);
+        }
+        self.entities.extend(map.scripts.take_spawns());
     }
 
-    fn handle_dead_entities(&mut self) {
+    /// applies a choice of the player that doesn't take a turn, the other entities don't act
+    pub fn apply_player_choice(&mut self, input: PlayerInput, logger: &mut Logger) {
+        if let Some(player) = self.entities.iter_mut().find(|e| e.is_player()) {
+            Controller::apply_choice(player, input, logger);
+        }
+    }
+
+    /// moves the dead entities to the corpses, a cloud of miasma rising from them
+    fn handle_dead_entities(&mut self, map: &mut Map, logger: &mut Logger) {
         let size = self.entities.len();
         let mut dead_entity_indices = Vec::new();
         for i in 0..size {
@@ -56,6 +139,10 @@ impl EntityManager {
         }
         for &i in dead_entity_indices.iter().rev() {
             let dead_entity = self.entities.remove(i);
+            environment::release_gas(map, dead_entity.position, GasKind::Miasma, MIASMA_DENSITY);
+            if let Some(player) = self.get_player_mut() {
+                scripting::on_death(player, &dead_entity.creature.id, &mut map.scripts, logger);
+            }
             self.dead_entities.push(dead_entity);
         }
     }
@@ -86,10 +173,39 @@ impl EntityManager {
         counter
     }
 
+    /// returns the positions of the living entities the player can see, sorted by distance to the player
+    pub fn visible_hostiles(&self, fov: &FieldOfView, map: &Map) -> Vec<(i32, i32, i32)> {
+        let player_position = self.get_player_position().unwrap_or((0, 0, 0));
+        let mut hostiles: Vec<(i32, i32, i32)> = self
+            .entities
+            .iter()
+            .filter(|e| !e.is_player() && !e.is_dead() && fov.is_visible(e.position, map))
+            .map(|e| e.position)
+            .collect();
+        hostiles.sort_by_key(|(x, y, _)| {
+            (x - player_position.0).pow(2) + (y - player_position.1).pow(2)
+        });
+        hostiles
+    }
+
+    /// returns the living entities then the dead ones
+    pub fn entities(&self) -> impl Iterator<Item = &Entity> {
+        self.entities.iter().chain(self.dead_entities.iter())
+    }
+
     pub fn get_player(&self) -> Option<&Entity> {
         self.entities.iter().find(|e| e.is_player())
     }
 
+    pub fn get_player_mut(&mut self) -> Option<&mut Entity> {
+        self.entities.iter_mut().find(|e| e.is_player())
+    }
+
+    /// returns the living entities, the player among them
+    pub fn living_entities_mut(&mut self) -> impl Iterator<Item = &mut Entity> {
+        self.entities.iter_mut()
+    }
+
     pub fn get_player_position(&self) -> Option<(i32, i32, i32)> {
         if let Some(player) = self.get_player() {
             Some(player.position)
@@ -98,6 +214,38 @@ impl EntityManager {
         }
     }
 
+    /// returns a hash of the entities and the world, the same for two games played with the same seed and inputs
+    pub fn state_hash(&self, map: &Map) -> u64 {
+        let mut hasher = StateHasher::new();
+        hasher.write_u64(self.turn);
+        hasher.write_u64(map.clock.seconds());
+        hasher.write_str(map.weather.kind.name());
+        for (flag, value) in map.scripts.flags() {
+            hasher.write_str(flag);
+            hasher.write_u64(*value as u64);
+        }
+        for entity in self.entities() {
+            hasher.write_str(entity.symbol());
+            hasher.write_position(entity.position);
+            hasher.write_u64(entity.stats.hp as u64);
+            hasher.write_u64(entity.stats.mana as u64);
+            hasher.write_u64(entity.level_manager.level as u64);
+            for item in entity.inventory.items() {
+                hasher.write_str(&item.name);
+            }
+        }
+        let mut changed_tiles: Vec<_> = map
+            .chunks
+            .values()
+            .flat_map(|chunk| chunk.changed_tiles.iter())
+            .collect();
+        changed_tiles.sort();
+        for &position in changed_tiles {
+            hasher.write_position(position);
+        }
+        hasher.finish()
+    }
+
     pub fn draw(&self, buffer: &mut Buffer, area: Rect, camera: &Camera, map: &Map) {
         for dead_entity in self.dead_entities.iter() {
             dead_entity.draw(buffer, area, camera, map);
@@ -0,0 +1,96 @@
+use std::collections::HashSet;
+
+use crate::{common::utils::bresenham_line, map::map::Map};
+
+/// what the player sees, kept in the visible and revealed tiles of the map
+#[derive(Default)]
+pub struct FieldOfView {
+    /// layer of the player when the field of view was last computed
+    pub layer: i32,
+    /// the field of view is off from the debug console, every loaded tile being visible
+    pub see_all: bool,
+}
+
+impl FieldOfView {
+    /// updates the visible_tiles of the map based on the player position
+    pub fn update(&mut self, player_position: (i32, i32, i32), range: i32, map: &mut Map) {
+        self.layer = player_position.2;
+        // computes FOV for player
+        let visible = if self.see_all {
+            Self::loaded_tiles(player_position.2, map)
+        } else {
+            Self::compute_fov(player_position, range, map)
+        };
+
+        // reset visible tiles
+        for chunk in map.chunks.values_mut() {
+            for layer in chunk.layers.values_mut() {
+                layer.visible_tiles.clear();
+            }
+        }
+
+        // updates visible tiles
+        for (global_x, global_y) in visible {
+            let chunk_coords = Map::convert_to_chunk_coordinates(global_x, global_y);
+            if let Some(chunk) = map.chunks.get_mut(&chunk_coords)
+                && let Some(layer) = chunk.layers.get_mut(&player_position.2)
+            {
+                layer.visible_tiles.insert((global_x, global_y));
+                layer.revealed_tiles.insert((global_x, global_y));
+            }
+        }
+    }
+
+    /// returns true if the player sees the tile
+    pub fn is_visible(&self, position: (i32, i32, i32), map: &Map) -> bool {
+        let (x, y, z) = position;
+        z == self.layer
+            && map
+                .chunks
+                .get(&Map::convert_to_chunk_coordinates(x, y))
+                .and_then(|chunk| chunk.layers.get(&z))
+                .is_some_and(|layer| layer.visible_tiles.contains(&(x, y)))
+    }
+
+    /// returns the coordinates of the tiles of the loaded chunks on the layer
+    fn loaded_tiles(layer: i32, map: &Map) -> HashSet<(i32, i32)> {
+        map.chunks
+            .values()
+            .filter_map(|chunk| chunk.layers.get(&layer))
+            .flat_map(|layer| layer.positions())
+            .collect()
+    }
+
+    /// returns the set of coordinates of the tiles visible to the player
+    fn compute_fov(player_position: (i32, i32, i32), range: i32, map: &Map) -> HashSet<(i32, i32)> {
+        let mut visible = HashSet::new();
+        for y in (player_position.1 - range)..=(player_position.1 + range) {
+            for x in (player_position.0 - range)..=(player_position.0 + range) {
+                let dx = x - player_position.0;
+                let dy = y - player_position.1;
+                if dx * dx + dy * dy <= range * range
+                    && Self::in_line_of_sight(player_position, (x, y), map)
+                {
+                    visible.insert((x, y));
+                }
+            }
+        }
+        visible
+    }
+
+    /// returns true if no tile blocks the sight between the start and the target, on the layer of the start
+    fn in_line_of_sight(start: (i32, i32, i32), target: (i32, i32), map: &Map) -> bool {
+        let line = bresenham_line(start.0, start.1, target.0, target.1);
+        for &(x, y) in line.iter().skip(1) {
+            // if it's the target it means its visible
+            if (x, y) == target {
+                return true;
+            }
+            match map.get_tile((x, y, start.2)) {
+                Some(tile) if !tile.block_sight() => {}
+                _ => return false,
+            }
+        }
+        true
+    }
+}
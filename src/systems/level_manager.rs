@@ -5,14 +5,14 @@ pub trait XPCurve {
     fn xp_required(&self, level: u32) -> u32;
 }
 
-struct ExponentialCurve {
+pub struct ExponentialCurve {
     /// base xp required
     base: u32,
     exponent: f32,
 }
 
 impl ExponentialCurve {
-    fn new(base: u32, exponent: f32) -> Self {
+    pub fn new(base: u32, exponent: f32) -> Self {
         Self { base, exponent }
     }
 }
@@ -30,11 +30,82 @@ impl XPCurve for ExponentialCurve {
     }
 }
 
+pub struct LinearCurve {
+    /// xp required for the level 2
+    base: u32,
+    /// xp added for each following level
+    increment: u32,
+}
+
+impl LinearCurve {
+    pub fn new(base: u32, increment: u32) -> Self {
+        Self { base, increment }
+    }
+}
+
+impl XPCurve for LinearCurve {
+    fn xp_required(&self, level: u32) -> u32 {
+        // XP required = base + increment * (level - 2)
+        (self.base + self.increment * level.saturating_sub(2)).max(1)
+    }
+}
+
+pub struct PolynomialCurve {
+    /// coefficients from the constant term to the highest degree
+    coefficients: Vec<f32>,
+}
+
+impl PolynomialCurve {
+    pub fn new(coefficients: Vec<f32>) -> Self {
+        Self { coefficients }
+    }
+}
+
+impl XPCurve for PolynomialCurve {
+    fn xp_required(&self, level: u32) -> u32 {
+        // XP required = c0 + c1 * level + c2 * level^2 + ..., rounded up and at least 1
+        let xp: f32 = self
+            .coefficients
+            .iter()
+            .enumerate()
+            .map(|(degree, coefficient)| coefficient * (level as f32).powi(degree as i32))
+            .sum();
+        (xp.ceil() as u32).max(1)
+    }
+}
+
+pub struct TableCurve {
+    /// xp required for the levels 2, 3, ...; the last value is repeated for the higher levels
+    xp: Vec<u32>,
+}
+
+impl TableCurve {
+    pub fn new(xp: Vec<u32>) -> Self {
+        Self { xp }
+    }
+}
+
+impl XPCurve for TableCurve {
+    fn xp_required(&self, level: u32) -> u32 {
+        let index = (level.saturating_sub(2) as usize).min(self.xp.len().saturating_sub(1));
+        self.xp.get(index).copied().unwrap_or(1).max(1)
+    }
+}
+
+/// attribute points granted at each level up
+pub const ATTRIBUTE_POINTS_PER_LEVEL: u32 = 3;
+/// perk points granted at each level up
+pub const PERK_POINTS_PER_LEVEL: u32 = 1;
+
 /// responsible for counting levels, XP and leveling up
 pub struct LevelManager {
     pub level: u32,
     current_xp: u32,
     xp_curve: Box<dyn XPCurve>,
+    /// points to spend on the attributes
+    pub attribute_points: u32,
+    /// points to spend on the perks
+    pub perk_points: u32,
 }
 
 impl LevelManager {
@@ -43,6 +114,8 @@ impl LevelManager {
             level: initial_level,
             current_xp,
             xp_curve,
+            attribute_points: 0,
+            perk_points: 0,
         }
     }
 
@@ -58,29 +131,32 @@ impl LevelManager {
         self.level - initial_level
     }
 
+    pub fn current_xp(&self) -> u32 {
+        self.current_xp
+    }
+
+    /// raises the level without the xp, granting the points of every level
+    pub fn grant_levels(&mut self, levels: u32) {
+        self.level += levels;
+        self.attribute_points += ATTRIBUTE_POINTS_PER_LEVEL * levels;
+        self.perk_points += PERK_POINTS_PER_LEVEL * levels;
+    }
+
     /// returns the required xp for next lvl
     pub fn xp_to_next_level(&self) -> u32 {
         self.xp_curve.xp_required(self.level + 1)
     }
 
-    /// add stats and increase level of the entity
+    /// increase level of the entity, restore its hp and mana and grant the points to spend
     fn handle_level_up(&mut self, entity_stats: &mut EntityStats) {
         self.current_xp -= self.xp_curve.xp_required(self.level + 1);
         self.level += 1;
 
-        // increase max_hp and reset current hp
-        let bonus_hp = (entity_stats.max_hp as f32 * 0.1).ceil() as u32;
-        entity_stats.max_hp += bonus_hp;
         entity_stats.hp = entity_stats.max_hp;
-
-        // increase max_mana and reset current mana
-        let bonus_mana = (entity_stats.max_mana as f32 * 0.1).ceil() as u32;
-        entity_stats.max_mana += bonus_mana;
         entity_stats.mana = entity_stats.max_mana;
 
-        entity_stats.defense += 1;
-        entity_stats.strength += 1;
-        entity_stats.magic += 1;
+        self.attribute_points += ATTRIBUTE_POINTS_PER_LEVEL;
+        self.perk_points += PERK_POINTS_PER_LEVEL;
     }
 }
 
@@ -1,4 +1,12 @@
 pub mod camera;
+pub mod clock;
 pub mod entity_manager;
+pub mod fov;
 pub mod level_manager;
+pub mod random;
+pub mod replay;
+pub mod scripting;
+pub mod simulation;
 pub mod spawner;
+pub mod targeting;
+pub mod wizard;
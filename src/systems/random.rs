@@ -0,0 +1,73 @@
+use rand::{Rng, SeedableRng, rng};
+use rand_chacha::ChaCha8Rng;
+
+/// systems drawing random numbers, each from its own stream so that the rolls of one don't shift the others; a new stream goes last so that the seeds of the others stay the same
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Stream {
+    /// generation of the chunks, only used through Random::chunk
+    World,
+    Spawning,
+    /// fire, fluids, gases and weather
+    Environment,
+    /// rolls of the attacks and the spells
+    Combat,
+    /// decisions of the creatures
+    Ai,
+}
+
+impl Stream {
+    const ALL: [Stream; 5] = [
+        Stream::World,
+        Stream::Spawning,
+        Stream::Environment,
+        Stream::Combat,
+        Stream::Ai,
+    ];
+}
+
+/// seeded random number generators of the world, the same seed and inputs giving the same game; ChaCha8 gives the same numbers on every platform and version, unlike StdRng
+pub struct Random {
+    seed: u64,
+    streams: Vec<ChaCha8Rng>,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            streams: Stream::ALL
+                .iter()
+                .map(|&stream| ChaCha8Rng::seed_from_u64(mix(seed, stream as u64)))
+                .collect(),
+        }
+    }
+
+    /// picks a new world seed, the only unseeded roll of the game
+    pub fn random_seed() -> u64 {
+        rng().random()
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn stream(&mut self, stream: Stream) -> &mut ChaCha8Rng {
+        &mut self.streams[stream as usize]
+    }
+
+    /// returns the generator of a chunk layer, the same whatever the order the chunks are generated in
+    pub fn chunk(&self, chunk_x: i32, chunk_y: i32, layer: i32) -> ChaCha8Rng {
+        let position = mix(mix(chunk_x as u64, chunk_y as u64), layer as u64);
+        ChaCha8Rng::seed_from_u64(mix(mix(self.seed, Stream::World as u64), position))
+    }
+}
+
+/// combines two values into a well spread seed (splitmix64)
+fn mix(a: u64, b: u64) -> u64 {
+    let mut z = a ^ b
+        .wrapping_add(0x9E37_79B9_7F4A_7C15)
+        .wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
@@ -0,0 +1,363 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+use crossterm::event::KeyCode;
+
+use crate::{
+    actions::action_bar::ActionSlot,
+    data::mods,
+    entities::{
+        character::{Attribute, Class, Race},
+        controller::PlayerInput,
+    },
+};
+
+/// file the inputs of the current game are recorded to
+pub const REPLAY_FILE: &str = "last_game.replay";
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// FNV-1a written out, so that the hash recorded in a replay stays the same whatever the versions of Rust and of the dependencies
+pub struct StateHasher(u64);
+
+impl StateHasher {
+    pub fn new() -> Self {
+        Self(FNV_OFFSET_BASIS)
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    pub fn write_i32(&mut self, value: i32) {
+        self.write(&value.to_le_bytes());
+    }
+
+    /// the length comes first so that "ab" then "c" differs from "a" then "bc"
+    pub fn write_str(&mut self, text: &str) {
+        self.write_u64(text.len() as u64);
+        self.write(text.as_bytes());
+    }
+
+    pub fn write_position(&mut self, (x, y, z): (i32, i32, i32)) {
+        self.write_i32(x);
+        self.write_i32(y);
+        self.write_i32(z);
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Default for StateHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// game to play again: the choices of the new game screen and the inputs of the player with their turn
+pub struct Replay {
+    pub seed: u64,
+    pub race: Race,
+    pub class: Class,
+    pub name: String,
+    /// ids of the mods enabled in the recorded game with their load order, in the order they were loaded
+    pub mods: Vec<(String, i32)>,
+    pub inputs: Vec<(u64, PlayerInput)>,
+    /// hash of the state at the end of the recorded game, missing if the game crashed
+    pub hash: Option<u64>,
+}
+
+impl Replay {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+        Self::parse(&content).map_err(|e| format!("{path}:{e}"))
+    }
+
+    /// parses a replay file, the errors starting with their line number
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let mut seed = None;
+        let mut race = None;
+        let mut class = None;
+        let mut name = None;
+        let mut mods = Vec::new();
+        let mut inputs = Vec::new();
+        let mut hash = None;
+        for (index, line) in content.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            let error = |message: &str| format!("{line_number}: {message}");
+            match key {
+                "seed" => seed = Some(value.parse().map_err(|_| error("invalid seed"))?),
+                "race" => {
+                    race = Some(
+                        Race::ALL
+                            .into_iter()
+                            .find(|race| race.name() == value)
+                            .ok_or_else(|| error("unknown race"))?,
+                    )
+                }
+                "class" => {
+                    class = Some(
+                        Class::ALL
+                            .into_iter()
+                            .find(|class| class.name() == value)
+                            .ok_or_else(|| error("unknown class"))?,
+                    )
+                }
+                "name" => name = Some(value.to_string()),
+                "mod" => {
+                    let (id, load_order) = value
+                        .split_once(' ')
+                        .and_then(|(id, load_order)| Some((id, load_order.parse().ok()?)))
+                        .ok_or_else(|| error("invalid mod"))?;
+                    mods.push((id.to_string(), load_order));
+                }
+                "hash" => {
+                    hash = Some(u64::from_str_radix(value, 16).map_err(|_| error("invalid hash"))?)
+                }
+                turn => {
+                    let turn = turn
+                        .parse()
+                        .map_err(|_| error(&format!("unknown line {key}")))?;
+                    let input = parse_input(value).ok_or_else(|| error("invalid input"))?;
+                    inputs.push((turn, input));
+                }
+            }
+        }
+        Ok(Self {
+            seed: seed.ok_or("missing seed")?,
+            race: race.ok_or("missing race")?,
+            class: class.ok_or("missing class")?,
+            name: name.ok_or("missing name")?,
+            mods,
+            inputs,
+            hash,
+        })
+    }
+
+    /// fails if the mods loaded now differ from the ones of the recorded game, their data giving another game
+    pub fn check_mods(&self) -> Result<(), String> {
+        let loaded = loaded_mods();
+        if loaded == self.mods {
+            return Ok(());
+        }
+        let describe = |mods: &[(String, i32)]| {
+            if mods.is_empty() {
+                "no mod".to_string()
+            } else {
+                mods.iter()
+                    .map(|(id, load_order)| format!("{id} ({load_order})"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }
+        };
+        Err(format!(
+            "the replay was recorded with {} but {} is loaded",
+            describe(&self.mods),
+            describe(&loaded)
+        ))
+    }
+}
+
+/// ids and load orders of the mods loaded at startup
+fn loaded_mods() -> Vec<(String, i32)> {
+    mods::active()
+        .iter()
+        .map(|manifest| (manifest.id.clone(), manifest.load_order))
+        .collect()
+}
+
+/// writes the inputs of the player to the replay file as they come, so a crash leaves a usable replay
+pub struct Recorder {
+    file: Option<BufWriter<File>>,
+}
+
+impl Recorder {
+    /// a recorder writing nowhere, used while replaying
+    pub fn disabled() -> Self {
+        Self { file: None }
+    }
+
+    /// creates the replay file and writes the choices of the new game and the loaded mods
+    pub fn start(seed: u64, race: Race, class: Class, name: &str) -> Self {
+        let mut recorder = Self {
+            file: File::create(REPLAY_FILE).ok().map(BufWriter::new),
+        };
+        recorder.write(&format!(
+            "# Mireveil replay\nseed {seed}\nrace {}\nclass {}\nname {name}",
+            race.name(),
+            class.name()
+        ));
+        for (id, load_order) in loaded_mods() {
+            recorder.write(&format!("mod {id} {load_order}"));
+        }
+        recorder
+    }
+
+    pub fn record(&mut self, turn: u64, input: PlayerInput) {
+        self.write(&format!("{turn} {}", format_input(input)));
+    }
+
+    /// writes the hash of the state at the end of the game
+    pub fn finish(&mut self, hash: u64) {
+        self.write(&format!("hash {hash:016x}"));
+    }
+
+    /// stops recording at the first error rather than interrupting the game
+    fn write(&mut self, line: &str) {
+        if let Some(file) = &mut self.file
+            && writeln!(file, "{line}").and_then(|_| file.flush()).is_err()
+        {
+            self.file = None;
+        }
+    }
+}
+
+/// "key up", "use_item 2", "use_action spell 1 10 -4 0"...
+fn format_input(input: PlayerInput) -> String {
+    match input {
+        PlayerInput::Key(key_code) => format!(
+            "key {}",
+            match key_code {
+                KeyCode::Up => "up".to_string(),
+                KeyCode::Down => "down".to_string(),
+                KeyCode::Left => "left".to_string(),
+                KeyCode::Right => "right".to_string(),
+                // the character is written as a number so spaces survive
+                KeyCode::Char(c) => (c as u32).to_string(),
+                // the other keys do nothing but still take a turn
+                _ => "other".to_string(),
+            }
+        ),
+        PlayerInput::UseItem(index) => format!("use_item {index}"),
+        PlayerInput::UseAction { slot, target } => {
+            let (kind, index) = match slot {
+                ActionSlot::Action(index) => ("action", index),
+                ActionSlot::Spell(index) => ("spell", index),
+            };
+            format!(
+                "use_action {kind} {index} {} {} {}",
+                target.0, target.1, target.2
+            )
+        }
+        PlayerInput::EvolveSpell {
+            spell_index,
+            branch,
+        } => format!("evolve_spell {spell_index} {branch}"),
+        PlayerInput::SpendAttributePoint(attribute) => {
+            format!("spend_attribute_point {}", attribute.name())
+        }
+        PlayerInput::TakePerk(index) => format!("take_perk {index}"),
+        PlayerInput::Craft(index) => format!("craft {index}"),
+    }
+}
+
+fn parse_input(text: &str) -> Option<PlayerInput> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let number = |index: usize| words.get(index)?.parse::<usize>().ok();
+    let coordinate = |index: usize| words.get(index)?.parse::<i32>().ok();
+    let input = match *words.first()? {
+        "key" => PlayerInput::Key(match *words.get(1)? {
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "other" => KeyCode::Null,
+            code => KeyCode::Char(char::from_u32(code.parse().ok()?)?),
+        }),
+        "use_item" => PlayerInput::UseItem(number(1)?),
+        "use_action" => PlayerInput::UseAction {
+            slot: match *words.get(1)? {
+                "action" => ActionSlot::Action(number(2)?),
+                "spell" => ActionSlot::Spell(number(2)?),
+                _ => return None,
+            },
+            target: (coordinate(3)?, coordinate(4)?, coordinate(5)?),
+        },
+        "evolve_spell" => PlayerInput::EvolveSpell {
+            spell_index: number(1)?,
+            branch: number(2)?,
+        },
+        "spend_attribute_point" => {
+            let name = *words.get(1)?;
+            PlayerInput::SpendAttributePoint(
+                Attribute::ALL
+                    .into_iter()
+                    .find(|attribute| attribute.name() == name)?,
+            )
+        }
+        "take_perk" => PlayerInput::TakePerk(number(1)?),
+        "craft" => PlayerInput::Craft(number(1)?),
+        _ => return None,
+    };
+    Some(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_hasher_is_fnv_1a() {
+        let mut hasher = StateHasher::new();
+        hasher.write(b"a");
+        assert_eq!(hasher.finish(), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn inputs_survive_the_replay_file() {
+        let inputs = [
+            PlayerInput::Key(KeyCode::Up),
+            PlayerInput::Key(KeyCode::Down),
+            PlayerInput::Key(KeyCode::Left),
+            PlayerInput::Key(KeyCode::Right),
+            PlayerInput::Key(KeyCode::Char(' ')),
+            PlayerInput::Key(KeyCode::Char('r')),
+            PlayerInput::Key(KeyCode::Null),
+            PlayerInput::UseItem(3),
+            PlayerInput::UseAction {
+                slot: ActionSlot::Action(1),
+                target: (10, -4, 0),
+            },
+            PlayerInput::UseAction {
+                slot: ActionSlot::Spell(2),
+                target: (-7, 3, -1),
+            },
+            PlayerInput::EvolveSpell {
+                spell_index: 1,
+                branch: 2,
+            },
+            PlayerInput::SpendAttributePoint(Attribute::Magic),
+            PlayerInput::TakePerk(4),
+            PlayerInput::Craft(5),
+        ];
+        for input in inputs {
+            assert_eq!(parse_input(&format_input(input)), Some(input));
+        }
+    }
+
+    #[test]
+    fn parse_errors_give_their_line() {
+        let content =
+            "# Mireveil replay\nseed 1\nrace Human\nclass Warrior\nname Tester\n0 key up\n1 jump\n";
+        assert_eq!(
+            Replay::parse(content).err().as_deref(),
+            Some("7: invalid input")
+        );
+    }
+}
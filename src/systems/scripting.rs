@@ -0,0 +1,519 @@
+use std::{cell::RefCell, collections::BTreeMap, fs, path::Path, rc::Rc, time::SystemTime};
+
+use rhai::{AST, Dynamic, Engine, Scope};
+
+use crate::{
+    data::{
+        creatures::{CreatureDefinition, creatures},
+        items::items,
+        mods,
+    },
+    entities::entity::Entity,
+    menu::Logger,
+};
+
+/// directory of the scripts of the game, each .rhai file defining the hooks it needs, the mods adding theirs
+pub const SCRIPTS_DIRECTORY: &str = "data/scripts";
+/// the scripts of the game by name, embedded in the executable like the data files
+const GAME_SCRIPTS: [(&str, &str); 4] = [
+    ("coward", include_str!("../../data/scripts/coward.rhai")),
+    (
+        "cursed_chest",
+        include_str!("../../data/scripts/cursed_chest.rhai"),
+    ),
+    (
+        "mending_scroll",
+        include_str!("../../data/scripts/mending_scroll.rhai"),
+    ),
+    (
+        "sheep_hunt",
+        include_str!("../../data/scripts/sheep_hunt.rhai"),
+    ),
+];
+
+/// operations a script may run per call, so that an endless loop doesn't freeze the game
+const MAX_OPERATIONS: u64 = 100_000;
+const MAX_CALL_LEVELS: usize = 32;
+const MAX_STRING_SIZE: usize = 4096;
+const MAX_ARRAY_SIZE: usize = 1024;
+
+/// what a script asks the game to do, applied to the Entity once the script returns
+#[derive(Clone)]
+enum Request {
+    Log(String),
+    Heal(u32),
+    Damage(u32),
+    RestoreMana(u32),
+    Give(String),
+    /// spawns the creature at the offset from the Entity
+    Spawn(&'static CreatureDefinition, i32, i32),
+}
+
+/// what a script sees of the game: a copy of the Entity it runs for, and the requests it makes
+#[derive(Clone)]
+pub struct ScriptContext(Rc<RefCell<ContextState>>);
+
+struct ContextState {
+    name: String,
+    hp: u32,
+    max_hp: u32,
+    mana: u32,
+    max_mana: u32,
+    position: (i32, i32, i32),
+    items: Vec<String>,
+    /// position of the player, the target of a scripted creature
+    target: (i32, i32, i32),
+    requests: Vec<Request>,
+    /// quest progress, shared by all the scripts
+    flags: Rc<RefCell<BTreeMap<String, i64>>>,
+}
+
+impl ScriptContext {
+    /// copies the Entity, the flags of the world being lent to the script until apply
+    fn new(entity: &Entity, target: (i32, i32, i32), state: &mut ScriptState) -> Self {
+        Self(Rc::new(RefCell::new(ContextState {
+            name: entity.name.clone(),
+            hp: entity.stats.hp,
+            max_hp: entity.stats.max_hp,
+            mana: entity.stats.mana,
+            max_mana: entity.stats.max_mana,
+            position: entity.position,
+            items: entity
+                .inventory
+                .items()
+                .iter()
+                .map(|item| item.name.clone())
+                .collect(),
+            target,
+            requests: Vec::new(),
+            flags: Rc::new(RefCell::new(std::mem::take(&mut state.flags))),
+        })))
+    }
+
+    fn request(&mut self, request: Request) {
+        self.0.borrow_mut().requests.push(request);
+    }
+
+    /// applies the requests of the script to the Entity and gives the flags back to the world
+    fn apply(&self, entity: &mut Entity, state: &mut ScriptState, logger: &mut Logger) {
+        let mut context = self.0.borrow_mut();
+        state.flags = context.flags.take();
+        for request in context.requests.drain(..) {
+            match request {
+                Request::Log(message) => logger.push_message(message),
+                Request::Heal(amount) => {
+                    entity.heal(amount);
+                }
+                Request::Damage(amount) => {
+                    entity.lose_hp(amount);
+                }
+                Request::RestoreMana(amount) => {
+                    entity.stats.mana = (entity.stats.mana + amount).min(entity.stats.max_mana);
+                }
+                Request::Give(id) => match items().get(&id) {
+                    Some(definition) => entity.inventory.add(definition.build()),
+                    None => logger.push_message(format!("script: unknown item {id}")),
+                },
+                Request::Spawn(creature, dx, dy) => {
+                    let (x, y, z) = entity.position;
+                    state
+                        .spawns
+                        .push(Entity::spawn(creature, (x + dx, y + dy, z)));
+                }
+            }
+        }
+    }
+}
+
+/// what the scripts keep of a world between calls, created and hashed with the Map so that a new world starts without it
+#[derive(Default)]
+pub struct ScriptState {
+    /// quest progress, shared by all the scripts
+    flags: BTreeMap<String, i64>,
+    /// creatures spawned by the scripts, added to the world at the end of the turn
+    spawns: Vec<Entity>,
+}
+
+impl ScriptState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn flags(&self) -> &BTreeMap<String, i64> {
+        &self.flags
+    }
+
+    /// returns the creatures the scripts spawned since the last call
+    pub fn take_spawns(&mut self) -> Vec<Entity> {
+        std::mem::take(&mut self.spawns)
+    }
+}
+
+/// the scripts of the game and of the mods compiled, the same for every world
+struct Scripts {
+    engine: Engine,
+    /// the compiled scripts by file name, with the time the file of a mod was modified
+    scripts: BTreeMap<String, (AST, Option<SystemTime>)>,
+    /// errors and prints of the scripts, shown in the logs
+    messages: Rc<RefCell<Vec<String>>>,
+}
+
+impl Scripts {
+    fn new() -> Self {
+        let messages = Rc::new(RefCell::new(Vec::new()));
+        let mut scripts = Self {
+            engine: engine(messages.clone()),
+            scripts: BTreeMap::new(),
+            messages,
+        };
+        for (name, source) in GAME_SCRIPTS {
+            match scripts.engine.compile(source) {
+                Ok(ast) => {
+                    scripts.scripts.insert(name.to_string(), (ast, None));
+                }
+                Err(error) => scripts
+                    .messages
+                    .borrow_mut()
+                    .push(format!("{SCRIPTS_DIRECTORY}/{name}.rhai: {error}")),
+            }
+        }
+        scripts.reload();
+        scripts
+    }
+
+    /// compiles again the scripts of the mods modified since they were loaded, and the new ones
+    fn reload(&mut self) {
+        // a script of a mod replaces the one of the game or of an earlier mod with the same name
+        let mut paths = BTreeMap::new();
+        for directory in mods::script_directories() {
+            let Ok(entries) = fs::read_dir(directory) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path
+                    .extension()
+                    .is_some_and(|extension| extension == "rhai")
+                {
+                    paths.insert(script_name(&path), path);
+                }
+            }
+        }
+        for (name, path) in paths {
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+            if self
+                .scripts
+                .get(&name)
+                .is_some_and(|(_, loaded)| loaded.is_some() && *loaded == modified)
+            {
+                continue;
+            }
+            let compiled = fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|source| self.engine.compile(source).map_err(|e| e.to_string()));
+            match compiled {
+                Ok(ast) => {
+                    self.scripts.insert(name, (ast, modified));
+                }
+                Err(error) => {
+                    // a script keeps its last valid version
+                    self.messages
+                        .borrow_mut()
+                        .push(format!("{}: {error}", path.display()));
+                }
+            }
+        }
+    }
+
+    /// returns true if the script defines the function with the context and the number of arguments
+    fn defines(&self, script: &str, function: &str, arguments: usize) -> bool {
+        self.scripts.get(script).is_some_and(|(ast, _)| {
+            ast.iter_functions()
+                .any(|f| f.name == function && f.params.len() == arguments + 1)
+        })
+    }
+
+    /// calls the function of the script if it defines it, with the context before the arguments; returns what the function returned
+    fn call(
+        &self,
+        script: &str,
+        function: &str,
+        context: &ScriptContext,
+        arguments: &[Dynamic],
+        logger: &mut Logger,
+    ) -> Option<Dynamic> {
+        if !self.defines(script, function, arguments.len()) {
+            return None;
+        }
+        let (ast, _) = self.scripts.get(script)?;
+        let mut values = vec![Dynamic::from(context.clone())];
+        values.extend_from_slice(arguments);
+        match self
+            .engine
+            .call_fn::<Dynamic>(&mut Scope::new(), ast, function, values)
+        {
+            Ok(value) => Some(value),
+            Err(error) => {
+                logger.push_message(format!("{SCRIPTS_DIRECTORY}/{script}.rhai: {error}"));
+                None
+            }
+        }
+    }
+
+    /// calls the function of the script for the Entity and applies what the script asked
+    fn call_for(
+        &self,
+        script: &str,
+        function: &str,
+        entity: &mut Entity,
+        target: (i32, i32, i32),
+        state: &mut ScriptState,
+        logger: &mut Logger,
+    ) -> Option<Dynamic> {
+        let context = ScriptContext::new(entity, target, state);
+        let result = self.call(script, function, &context, &[], logger);
+        context.apply(entity, state, logger);
+        result
+    }
+
+    /// calls the function in every script defining it
+    fn call_all(
+        &self,
+        function: &str,
+        entity: &mut Entity,
+        arguments: &[Dynamic],
+        state: &mut ScriptState,
+        logger: &mut Logger,
+    ) {
+        for name in self.scripts.keys() {
+            if !self.defines(name, function, arguments.len()) {
+                continue;
+            }
+            let context = ScriptContext::new(entity, entity.position, state);
+            self.call(name, function, &context, arguments, logger);
+            context.apply(entity, state, logger);
+        }
+    }
+}
+
+/// "fire_scroll" for data/scripts/fire_scroll.rhai
+fn script_name(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// an engine without access to the files, with limits so that a script can't freeze the game, and the API of the ScriptContext
+fn engine(messages: Rc<RefCell<Vec<String>>>) -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_max_operations(MAX_OPERATIONS)
+        .set_max_call_levels(MAX_CALL_LEVELS)
+        .set_max_string_size(MAX_STRING_SIZE)
+        .set_max_array_size(MAX_ARRAY_SIZE)
+        .set_max_map_size(MAX_ARRAY_SIZE);
+    engine.disable_symbol("eval");
+    engine.on_print(move |text| messages.borrow_mut().push(text.to_string()));
+    engine.on_debug(|_, _, _| {});
+
+    engine
+        .register_type_with_name::<ScriptContext>("Context")
+        .register_get("name", |c: &mut ScriptContext| c.0.borrow().name.clone())
+        .register_get("hp", |c: &mut ScriptContext| c.0.borrow().hp as i64)
+        .register_get("max_hp", |c: &mut ScriptContext| c.0.borrow().max_hp as i64)
+        .register_get("mana", |c: &mut ScriptContext| c.0.borrow().mana as i64)
+        .register_get("max_mana", |c: &mut ScriptContext| {
+            c.0.borrow().max_mana as i64
+        })
+        .register_get("x", |c: &mut ScriptContext| c.0.borrow().position.0 as i64)
+        .register_get("y", |c: &mut ScriptContext| c.0.borrow().position.1 as i64)
+        .register_get("z", |c: &mut ScriptContext| c.0.borrow().position.2 as i64)
+        .register_get("target_x", |c: &mut ScriptContext| {
+            c.0.borrow().target.0 as i64
+        })
+        .register_get("target_y", |c: &mut ScriptContext| {
+            c.0.borrow().target.1 as i64
+        })
+        .register_get("distance", |c: &mut ScriptContext| {
+            let state = c.0.borrow();
+            let (dx, dy) = (
+                state.target.0 - state.position.0,
+                state.target.1 - state.position.1,
+            );
+            dx.abs().max(dy.abs()) as i64
+        })
+        .register_fn("has_item", |c: &mut ScriptContext, name: &str| {
+            c.0.borrow().items.iter().any(|item| item == name)
+        })
+        .register_fn("log", |c: &mut ScriptContext, message: &str| {
+            c.request(Request::Log(message.to_string()))
+        })
+        .register_fn("heal", |c: &mut ScriptContext, amount: i64| {
+            c.request(Request::Heal(amount.max(0) as u32))
+        })
+        .register_fn("damage", |c: &mut ScriptContext, amount: i64| {
+            c.request(Request::Damage(amount.max(0) as u32))
+        })
+        .register_fn("restore_mana", |c: &mut ScriptContext, amount: i64| {
+            c.request(Request::RestoreMana(amount.max(0) as u32))
+        })
+        .register_fn("give", |c: &mut ScriptContext, id: &str| {
+            c.request(Request::Give(id.to_string()))
+        })
+        .register_fn(
+            "summon",
+            |c: &mut ScriptContext, creature: &str, dx: i64, dy: i64| {
+                if let Some(creature) = creatures().find(creature) {
+                    c.request(Request::Spawn(creature, dx as i32, dy as i32));
+                }
+            },
+        )
+        .register_fn("get_flag", |c: &mut ScriptContext, key: &str| {
+            c.0.borrow().flags.borrow().get(key).copied().unwrap_or(0)
+        })
+        .register_fn(
+            "set_flag",
+            |c: &mut ScriptContext, key: &str, value: i64| {
+                c.0.borrow()
+                    .flags
+                    .borrow_mut()
+                    .insert(key.to_string(), value);
+            },
+        );
+    engine
+}
+
+thread_local! {
+    static SCRIPTS: RefCell<Option<Scripts>> = const { RefCell::new(None) };
+}
+
+/// runs the closure on the scripts, loading them the first time
+fn with_scripts<R>(f: impl FnOnce(&mut Scripts) -> R) -> R {
+    SCRIPTS.with(|scripts| {
+        let mut scripts = scripts.borrow_mut();
+        f(scripts.get_or_insert_with(Scripts::new))
+    })
+}
+
+/// compiles again the modified scripts of the mods, only in debug builds so they can be edited while playing
+pub fn hot_reload() {
+    if cfg!(debug_assertions) {
+        with_scripts(Scripts::reload);
+    }
+}
+
+/// returns the errors and the prints of the scripts since the last call
+pub fn take_messages() -> Vec<String> {
+    with_scripts(|scripts| std::mem::take(&mut *scripts.messages.borrow_mut()))
+}
+
+/// uses an item whose effect is the script, calling its use_item(ctx); returns false if the script can't be used
+pub fn use_item(
+    script: &str,
+    entity: &mut Entity,
+    state: &mut ScriptState,
+    logger: &mut Logger,
+) -> bool {
+    with_scripts(|scripts| {
+        let target = entity.position;
+        scripts
+            .call_for(script, "use_item", entity, target, state, logger)
+            .is_some()
+    })
+}
+
+/// calls on_interact(ctx, structure) of the scripts when the Entity interacts with a structure
+pub fn on_interact(
+    entity: &mut Entity,
+    structure: &str,
+    state: &mut ScriptState,
+    logger: &mut Logger,
+) {
+    with_scripts(|scripts| {
+        scripts.call_all("on_interact", entity, &[structure.into()], state, logger)
+    });
+}
+
+/// calls on_turn(ctx, hour) of the scripts for the player at the end of each turn
+pub fn on_turn(player: &mut Entity, hour: u64, state: &mut ScriptState, logger: &mut Logger) {
+    with_scripts(|scripts| {
+        scripts.call_all("on_turn", player, &[(hour as i64).into()], state, logger)
+    });
+}
+
+/// calls on_death(ctx, creature) of the scripts for the player when a creature dies, with the id of the creature
+pub fn on_death(player: &mut Entity, creature: &str, state: &mut ScriptState, logger: &mut Logger) {
+    with_scripts(|scripts| scripts.call_all("on_death", player, &[creature.into()], state, logger));
+}
+
+/// returns what decide(ctx) of the script answers for the creature: "attack", "wait", "flee", "north"...
+pub fn decide(
+    script: &str,
+    creature: &mut Entity,
+    target: (i32, i32, i32),
+    state: &mut ScriptState,
+    logger: &mut Logger,
+) -> Option<String> {
+    with_scripts(|scripts| {
+        scripts
+            .call_for(script, "decide", creature, target, state, logger)
+            .and_then(|decision| decision.into_string().ok())
+    })
+}
+
+/// returns true if the game ships the script <name>.rhai
+pub fn is_game_script(script: &str) -> bool {
+    GAME_SCRIPTS.iter().any(|(name, _)| *name == script)
+}
+
+/// returns true if the script <name>.rhai of the game or of a mod was loaded
+pub fn exists(script: &str) -> bool {
+    with_scripts(|scripts| scripts.scripts.contains_key(script))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::character::{Class, Race};
+
+    fn player() -> Entity {
+        Entity::character("Reader".to_string(), Race::Human, Class::Mage, (0, 0, 0))
+    }
+
+    #[test]
+    fn scripts_compile() {
+        assert!(exists("mending_scroll"));
+        assert_eq!(take_messages(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn mending_scroll_heals() {
+        let mut player = player();
+        player.stats.hp = 1;
+        assert!(use_item(
+            "mending_scroll",
+            &mut player,
+            &mut ScriptState::new(),
+            &mut Logger::new()
+        ));
+        assert!(player.stats.hp > 1);
+    }
+
+    #[test]
+    fn sheep_hunt_gives_the_reward() {
+        let mut player = player();
+        let mut state = ScriptState::new();
+        let mut logger = Logger::new();
+        for _ in 0..3 {
+            on_death(&mut player, "sheep", &mut state, &mut logger);
+        }
+        assert!(
+            player
+                .inventory
+                .items()
+                .iter()
+                .any(|item| item.name == "Scroll of mending")
+        );
+        assert_eq!(state.flags().get("sheep_killed"), Some(&3));
+    }
+}
@@ -0,0 +1,344 @@
+use crossterm::event::KeyCode;
+
+use crate::{
+    actions::projectile::Projectile,
+    data::creatures::CreatureDefinition,
+    entities::{
+        AIbehavior::{AIBehavior, ChasePlayerBehavior},
+        character::{Class, Race},
+        controller::PlayerInput,
+        entity::Entity,
+    },
+    map::map::Map,
+    menu::Logger,
+};
+
+use super::{
+    entity_manager::EntityManager,
+    fov::FieldOfView,
+    scripting,
+    spawner::{Spawner, SpawnerConfiguration},
+};
+
+/// distance between the player and the creature at the start of a fight
+const FIGHT_DISTANCE: i32 = 3;
+/// turns after which a fight is a draw
+const MAX_FIGHT_TURNS: u64 = 500;
+
+/// the world and its entities without anything drawn, played by the App or headless
+pub struct Simulation {
+    pub map: Map,
+    pub entity_manager: EntityManager,
+    pub logger: Logger,
+    /// what the player sees
+    pub fov: FieldOfView,
+    /// spawns creatures around the player, None in a fight
+    pub spawner: Option<Spawner>,
+}
+
+impl Simulation {
+    pub fn new(map: Map) -> Self {
+        Self {
+            map,
+            entity_manager: EntityManager::new(),
+            logger: Logger::new(),
+            fov: FieldOfView::default(),
+            spawner: Some(Spawner::new(SpawnerConfiguration::default())),
+        }
+    }
+
+    pub fn turn(&self) -> u64 {
+        self.entity_manager.turn()
+    }
+
+    pub fn player(&self) -> Option<&Entity> {
+        self.entity_manager.get_player()
+    }
+
+    pub fn state_hash(&self) -> u64 {
+        self.entity_manager.state_hash(&self.map)
+    }
+
+    /// plays the input of the player, a choice not taking a turn; returns the projectiles launched, for the App to animate
+    pub fn step(&mut self, input: PlayerInput) -> Vec<Projectile> {
+        if input.is_choice() {
+            self.entity_manager
+                .apply_player_choice(input, &mut self.logger);
+            return Vec::new();
+        }
+        self.entity_manager
+            .update(input, &mut self.fov, &mut self.map, &mut self.logger);
+        // spawns once per turn so that the spawns don't depend on the frame rate
+        if let Some(spawner) = &mut self.spawner {
+            spawner.try_spawn(&mut self.entity_manager, &mut self.map);
+        }
+        for message in scripting::take_messages() {
+            self.logger.push_message(message);
+        }
+        std::mem::take(&mut self.map.launched)
+    }
+
+    /// plays up to the number of turns with the inputs given by next_input, stopping when it returns None or when the player dies; returns the turns played
+    pub fn run(
+        &mut self,
+        turns: u64,
+        mut next_input: impl FnMut(&Simulation) -> Option<PlayerInput>,
+    ) -> u64 {
+        let start = self.turn();
+        while self.turn() - start < turns && self.player().is_some() {
+            let Some(input) = next_input(self) else {
+                break;
+            };
+            self.step(input);
+        }
+        self.turn() - start
+    }
+
+    /// returns the input of a player controlled like the creatures: it uses its best action on the closest creature in reach, walks toward it otherwise and waits when alone
+    pub fn auto_input(&self) -> PlayerInput {
+        let Some(player) = self.player() else {
+            return PlayerInput::Key(KeyCode::Null);
+        };
+        let Some(target) = self
+            .entity_manager
+            .entities()
+            .filter(|entity| !entity.is_player() && !entity.is_dead())
+            .map(|entity| entity.position)
+            .min_by_key(|&(x, y, _)| (x - player.position.0).abs() + (y - player.position.1).abs())
+        else {
+            return PlayerInput::Key(KeyCode::Null);
+        };
+        if let Some(slot) = ChasePlayerBehavior.choose_action(player, target, &self.map) {
+            return PlayerInput::UseAction { slot, target };
+        }
+        let (dx, dy) = (target.0 - player.position.0, target.1 - player.position.1);
+        PlayerInput::Key(if dx.abs() >= dy.abs() {
+            if dx > 0 {
+                KeyCode::Right
+            } else {
+                KeyCode::Left
+            }
+        } else if dy > 0 {
+            KeyCode::Down
+        } else {
+            KeyCode::Up
+        })
+    }
+}
+
+/// result of one fight between a character played by auto_input and a creature
+pub struct FightOutcome {
+    pub won: bool,
+    pub lost: bool,
+    pub turns: u64,
+    pub damage_dealt: u32,
+    pub damage_taken: u32,
+}
+
+/// fights a creature in the world of the seed, with nothing else spawning
+pub fn fight(
+    race: Race,
+    class: Class,
+    creature: &'static CreatureDefinition,
+    seed: u64,
+) -> FightOutcome {
+    let mut simulation = Simulation::new(Map::generate(seed));
+    simulation.spawner = None;
+    let player = Entity::character("Fighter".to_string(), race, class, (0, 0, 0));
+    let player_hp = player.stats.max_hp;
+    simulation.entity_manager.add_player(player);
+    simulation
+        .entity_manager
+        .add_entity(Entity::spawn(creature, (FIGHT_DISTANCE, 0, 0)));
+    let creature_hp = simulation
+        .entity_manager
+        .entities()
+        .find(|entity| !entity.is_player())
+        .map_or(0, |creature| creature.stats.max_hp);
+
+    // the fight is over once the creature is dead
+    let turns = simulation.run(MAX_FIGHT_TURNS, |simulation| {
+        simulation
+            .entity_manager
+            .entities()
+            .any(|entity| !entity.is_player() && !entity.is_dead())
+            .then(|| simulation.auto_input())
+    });
+
+    // the player and the creature are among the corpses once dead
+    let hp = |player: bool| {
+        simulation
+            .entity_manager
+            .entities()
+            .find(|entity| entity.is_player() == player)
+            .map_or(0, |entity| entity.stats.hp)
+    };
+    let lost = simulation.player().is_none();
+    let won = !lost && hp(false) == 0;
+    FightOutcome {
+        won,
+        lost,
+        turns,
+        damage_dealt: creature_hp.saturating_sub(hp(false)),
+        damage_taken: player_hp.saturating_sub(hp(true)),
+    }
+}
+
+/// outcomes of many fights against the same creature
+#[derive(Default)]
+pub struct FightStatistics {
+    pub fights: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub turns: u64,
+    pub damage_dealt: u64,
+    pub damage_taken: u64,
+}
+
+impl FightStatistics {
+    /// plays the fights in the worlds of the seeds 0 to count - 1
+    pub fn run(
+        race: Race,
+        class: Class,
+        creature: &'static CreatureDefinition,
+        count: u32,
+    ) -> Self {
+        let mut statistics = Self::default();
+        for seed in 0..count {
+            statistics.add(fight(race, class, creature, seed as u64));
+        }
+        statistics
+    }
+
+    fn add(&mut self, outcome: FightOutcome) {
+        self.fights += 1;
+        self.wins += outcome.won as u32;
+        self.losses += outcome.lost as u32;
+        self.turns += outcome.turns;
+        self.damage_dealt += outcome.damage_dealt as u64;
+        self.damage_taken += outcome.damage_taken as u64;
+    }
+
+    pub fn win_rate(&self) -> f64 {
+        self.wins as f64 / self.fights.max(1) as f64
+    }
+
+    /// returns the average of a total over the fights
+    pub fn average(&self, total: u64) -> f64 {
+        total as f64 / self.fights.max(1) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        actions::action_bar::ActionSlot,
+        data::creatures::creatures,
+        entities::entity::EntityKind,
+        systems::clock::{SECONDS_PER_HOUR, SECONDS_PER_MINUTE},
+    };
+
+    fn walk(seed: u64) -> u64 {
+        let mut simulation = Simulation::new(Map::generate(seed));
+        simulation.entity_manager.add_player(Entity::character(
+            "Walker".to_string(),
+            Race::Elf,
+            Class::Mage,
+            (0, 0, 0),
+        ));
+        let keys = [KeyCode::Right, KeyCode::Down, KeyCode::Left, KeyCode::Up];
+        simulation.run(40, |simulation| {
+            Some(PlayerInput::Key(keys[simulation.turn() as usize / 5 % 4]))
+        });
+        simulation.state_hash()
+    }
+
+    #[test]
+    fn same_seed_and_inputs_give_same_state() {
+        assert_eq!(walk(3), walk(3));
+    }
+
+    #[test]
+    fn fight_ends_when_creature_dies() {
+        let sheep = creatures().of_kind(EntityKind::Sheep);
+        let outcome = fight(Race::Human, Class::Warrior, sheep, 0);
+        assert!(outcome.won);
+        assert!(outcome.turns < MAX_FIGHT_TURNS);
+    }
+
+    #[test]
+    fn rest_is_cut_short_by_a_creature_in_view() {
+        let rest = |creature: bool| {
+            let mut simulation = Simulation::new(Map::generate(0));
+            simulation.spawner = None;
+            simulation.entity_manager.add_player(Entity::character(
+                "Sleeper".to_string(),
+                Race::Human,
+                Class::Warrior,
+                (0, 0, 0),
+            ));
+            if creature {
+                simulation
+                    .entity_manager
+                    .add_entity(Entity::monster(EntityKind::Sheep, (FIGHT_DISTANCE, 0, 0)));
+            }
+            let start = simulation.map.clock.seconds();
+            simulation.step(PlayerInput::Key(KeyCode::Char('r')));
+            simulation.map.clock.seconds() - start
+        };
+        assert_eq!(rest(false), SECONDS_PER_HOUR);
+        assert_eq!(rest(true), SECONDS_PER_MINUTE);
+    }
+
+    #[test]
+    fn spell_waits_its_whole_cooldown() {
+        let mut simulation = Simulation::new(Map::generate(0));
+        simulation.spawner = None;
+        let player = Entity::character("Caster".to_string(), Race::Elf, Class::Mage, (0, 0, 0));
+        let slot = ActionSlot::Spell(0);
+        let cooldown = player.spellbook.spells[0].cooldown;
+        simulation.entity_manager.add_player(player);
+        let target = (FIGHT_DISTANCE, 0, 0);
+        let remaining_cooldowns: Vec<u32> = (0..=cooldown)
+            .map(|_| {
+                simulation.step(PlayerInput::UseAction { slot, target });
+                simulation.player().unwrap().spellbook.spells[0].remaining_cooldown
+            })
+            .collect();
+        // cast on the first turn, refused on the next ones
+        assert_eq!(
+            remaining_cooldowns,
+            (0..=cooldown).rev().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn selecting_a_slot_takes_no_turn() {
+        let mut simulation = Simulation::new(Map::generate(0));
+        simulation.spawner = None;
+        simulation.entity_manager.add_player(Entity::character(
+            "Picker".to_string(),
+            Race::Human,
+            Class::Rogue,
+            (0, 0, 0),
+        ));
+        simulation
+            .entity_manager
+            .add_entity(Entity::monster(EntityKind::Sheep, (FIGHT_DISTANCE, 0, 0)));
+        let positions = |simulation: &Simulation| -> Vec<(i32, i32, i32)> {
+            simulation
+                .entity_manager
+                .entities()
+                .map(|entity| entity.position)
+                .collect()
+        };
+        let (seconds, before) = (simulation.map.clock.seconds(), positions(&simulation));
+        for key in ['2', 'n', 'm'] {
+            simulation.step(PlayerInput::Key(KeyCode::Char(key)));
+        }
+        assert_eq!(simulation.map.clock.seconds(), seconds);
+        assert_eq!(positions(&simulation), before);
+        assert_eq!(simulation.turn(), 0);
+    }
+}
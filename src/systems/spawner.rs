@@ -1,73 +1,66 @@
-use std::time::{Duration, Instant};
-
 use rand::{
     Rng,
     distr::{Distribution, weighted::WeightedIndex},
-    rng,
 };
 
 use crate::{
-    entities::{
-        AIbehavior::ChasePlayerBehavior,
-        controller::Controller,
-        entity::{Entity, EntityKind},
-    },
+    data::{creatures::CreatureDefinition, spawns::spawns},
+    entities::entity::Entity,
     map::map::Map,
 };
 
-use super::entity_manager::EntityManager;
+use super::{entity_manager::EntityManager, random::Stream};
 
 pub struct SpawnerConfiguration {
-    interval: Duration,
+    /// game time between two spawns, in seconds
+    interval: u64,
     // spawns entities only if total quantity of entities is lower to max_entities
     max_entities: usize,
     spawn_radius: i32,
     spawn_around_player: bool,
-    spawn_chances: Vec<(EntityKind, f32)>,
+    /// the creatures with their weights, from data/spawns.toml
+    spawn_chances: Vec<(&'static CreatureDefinition, f32)>,
 }
 
 impl Default for SpawnerConfiguration {
     fn default() -> Self {
         Self {
-            interval: Duration::from_secs(3),
+            interval: 30,
             max_entities: 10,
             spawn_radius: 20,
             spawn_around_player: true,
-            spawn_chances: vec![
-                (EntityKind::Human, 0.1),
-                (EntityKind::Sheep, 0.5),
-                (EntityKind::Dragon, 0.001),
-            ],
+            spawn_chances: spawns().chances(),
         }
     }
 }
 
 pub struct Spawner {
     pub config: SpawnerConfiguration,
-    pub last_spawn: Instant,
+    /// game time of the last spawn, in seconds
+    pub last_spawn: u64,
 }
 
 impl Spawner {
     pub fn new(config: SpawnerConfiguration) -> Self {
         Self {
             config,
-            last_spawn: Instant::now(),
+            last_spawn: 0,
         }
     }
 
     // spawns an entity if cooldown has passed and if there is an empty tile
-    pub fn try_spawn(&mut self, entity_manager: &mut EntityManager, map: &Map) {
-        let now = Instant::now();
-        if now.duration_since(self.last_spawn) < self.config.interval {
+    pub fn try_spawn(&mut self, entity_manager: &mut EntityManager, map: &mut Map) {
+        let now = map.clock.seconds();
+        if now.saturating_sub(self.last_spawn) < self.config.interval {
             return;
         }
 
-        let mut rng = rng();
-        let kinds: Vec<EntityKind> = self
+        let rng = map.random.stream(Stream::Spawning);
+        let creatures: Vec<&'static CreatureDefinition> = self
             .config
             .spawn_chances
             .iter()
-            .map(|(kind, _)| *kind)
+            .map(|(creature, _)| *creature)
             .collect();
         let weights: Vec<f32> = self
             .config
@@ -77,7 +70,7 @@ impl Spawner {
             .collect();
         let dist = WeightedIndex::new(&weights)
             .expect("Error creating WeightedIndex in Spawner::try_spawn()");
-        let chosen_kind = kinds[dist.sample(&mut rng)];
+        let chosen_creature = creatures[dist.sample(rng)];
 
         // spawns around player or world spawn (0,0)
         let player_position = entity_manager.get_player_position().unwrap_or((0, 0, 0));
@@ -103,12 +96,7 @@ impl Spawner {
                 .is_none()
         {
             self.last_spawn = now;
-            let new_entity = Entity::new(
-                chosen_kind,
-                chosen_kind.name().to_owned(),
-                (spawn_x, spawn_y, layer),
-                Controller::AI(Box::new(ChasePlayerBehavior)),
-            );
+            let new_entity = Entity::spawn(chosen_creature, (spawn_x, spawn_y, layer));
             // only spawns the entity if there is not too many entities on the map
             if entity_manager.count_living_entities() < self.config.max_entities as u32 {
                 entity_manager.add_entity(new_entity);
@@ -0,0 +1,126 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::{Position, Rect},
+    style::{Color, Style, Stylize},
+};
+
+use crate::{
+    actions::action_bar::ActionSlot,
+    entities::entity::Entity,
+    map::map::{Direction, Map},
+};
+
+use super::camera::Camera;
+
+/// aiming state of the player while choosing the target of an action
+pub struct Targeting {
+    /// action or spell of the player being aimed
+    pub slot: ActionSlot,
+    /// global coordinates of the reticle
+    pub cursor: (i32, i32, i32),
+    /// index in the list of visible hostiles of the one selected last with Tab
+    hostile_index: Option<usize>,
+}
+
+impl Targeting {
+    pub fn new(slot: ActionSlot, cursor: (i32, i32, i32)) -> Self {
+        Self {
+            slot,
+            cursor,
+            hostile_index: None,
+        }
+    }
+
+    pub fn move_cursor(&mut self, dx: i32, dy: i32) {
+        self.cursor.0 += dx;
+        self.cursor.1 += dy;
+    }
+
+    /// moves the cursor to the next hostile, the hostiles being sorted by distance
+    pub fn cycle_hostiles(&mut self, hostiles: &[(i32, i32, i32)]) {
+        if hostiles.is_empty() {
+            return;
+        }
+        let next = match self.hostile_index {
+            Some(index) => (index + 1) % hostiles.len(),
+            None => 0,
+        };
+        self.hostile_index = Some(next);
+        self.cursor = hostiles[next];
+    }
+
+    /// returns true if the cursor is in line of sight of the player and in range of the action
+    pub fn is_valid_target(&self, player: &Entity, camera: &Camera, map: &Map) -> bool {
+        let in_range = player
+            .action(self.slot)
+            .is_some_and(|action| action.can_reach(player, self.cursor));
+        in_range && camera.is_visible_tile(self.cursor, map)
+    }
+
+    /// highlights the range of the action, its area of effect around the cursor and the cursor itself
+    pub fn draw(
+        &self,
+        buffer: &mut Buffer,
+        area: Rect,
+        camera: &Camera,
+        map: &Map,
+        player: &Entity,
+    ) {
+        let Some(action) = player.action(self.slot) else {
+            return;
+        };
+
+        // range
+        let range = action.range() as i32;
+        let (px, py, pz) = player.position;
+        for y in (py - range)..=(py + range) {
+            for x in (px - range)..=(px + range) {
+                let position = (x, y, pz);
+                if action.can_reach(player, position) && camera.is_visible_tile(position, map) {
+                    Self::highlight(buffer, area, camera, position, Color::Rgb(40, 50, 70));
+                }
+            }
+        }
+
+        // area of effect
+        let is_valid = self.is_valid_target(player, camera, map);
+        let area_color = if is_valid {
+            Color::Rgb(120, 60, 20)
+        } else {
+            Color::Rgb(70, 70, 70)
+        };
+        // the player turns toward the target when using the action
+        let direction =
+            Direction::towards(player.position, self.cursor).unwrap_or(player.direction);
+        for position in action.get_area_of_effect(player.position, direction, self.cursor, map) {
+            if camera.is_visible_tile(position, map) {
+                Self::highlight(buffer, area, camera, position, area_color);
+            }
+        }
+
+        // reticle
+        if let Some((x, y)) = camera.world_to_screen((self.cursor.0, self.cursor.1), area) {
+            let color = if is_valid { Color::Yellow } else { Color::Red };
+            camera.draw_from_screen_coordinates(
+                "X",
+                Style::default().fg(color).bold(),
+                Position { x, y },
+                buffer,
+            );
+        }
+    }
+
+    fn highlight(
+        buffer: &mut Buffer,
+        area: Rect,
+        camera: &Camera,
+        position: (i32, i32, i32),
+        color: Color,
+    ) {
+        if let Some((x, y)) = camera.world_to_screen((position.0, position.1), area)
+            && let Some(cell) = buffer.cell_mut(Position { x, y })
+        {
+            cell.set_bg(color);
+        }
+    }
+}
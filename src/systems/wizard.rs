@@ -0,0 +1,259 @@
+use crate::{
+    data::{
+        creatures::{CreatureDefinition, creatures},
+        items::items,
+    },
+    entities::{AIbehavior::ScriptedBehavior, controller::Controller, entity::Entity},
+    map::{
+        environment,
+        map::{CHUNK_SIZE, Map},
+    },
+};
+
+use super::{clock::SECONDS_PER_HOUR, fov::FieldOfView, scripting, simulation::Simulation};
+
+/// names of the commands of the debug console, with their arguments
+const COMMANDS: [(&str, &str); 11] = [
+    ("spawn", "<creature> [script]"),
+    ("teleport", "<x> <y>"),
+    ("give", "<item>"),
+    ("set", "<stat> <value>"),
+    ("reveal", ""),
+    ("fov", ""),
+    ("god", ""),
+    ("kill", ""),
+    ("time", "<hours>"),
+    ("layer", "<z>"),
+    ("help", ""),
+];
+
+const STATS: [&str; 7] = [
+    "hp", "max_hp", "mana", "max_mana", "defense", "strength", "magic",
+];
+
+/// command of the debug console, cheating on the simulation to test the content
+pub enum Command {
+    /// spawns the creature on the selected tile, deciding with the script of data/scripts if one is given
+    Spawn(&'static CreatureDefinition, Option<String>),
+    Teleport(i32, i32),
+    /// gives the player the item of data/items.toml
+    Give(String),
+    SetStat(&'static str, u32),
+    /// reveals the loaded chunks of every layer
+    Reveal,
+    /// turns the field of view off or back on
+    ToggleFov,
+    /// makes the player invulnerable or vulnerable again
+    ToggleGodMode,
+    /// kills every creature but the player
+    KillAll,
+    /// advances the clock by hours
+    AdvanceTime(u64),
+    /// moves the player to the layer
+    ChangeLayer(i32),
+    Help,
+}
+
+impl Command {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let argument = |index: usize| {
+            words
+                .get(index)
+                .copied()
+                .ok_or_else(|| format!("usage: {}", usage(words[0])))
+        };
+        let number = |index: usize| {
+            argument(index)?
+                .parse::<i32>()
+                .map_err(|_| format!("usage: {}", usage(words[0])))
+        };
+        let command = match words.first().copied().unwrap_or_default() {
+            "spawn" => {
+                let name = argument(1)?;
+                let creature = creatures()
+                    .find(name)
+                    .ok_or_else(|| format!("unknown creature {name}"))?;
+                let script = words.get(2).map(|script| script.to_string());
+                if let Some(script) = &script
+                    && !scripting::exists(script)
+                {
+                    return Err(format!("unknown script {script}"));
+                }
+                Command::Spawn(creature, script)
+            }
+            "teleport" => Command::Teleport(number(1)?, number(2)?),
+            "give" => {
+                let id = argument(1)?;
+                if items().get(id).is_none() {
+                    return Err(format!("unknown item {id}"));
+                }
+                Command::Give(id.to_string())
+            }
+            "set" => {
+                let name = argument(1)?;
+                let stat = STATS
+                    .into_iter()
+                    .find(|stat| *stat == name)
+                    .ok_or_else(|| format!("unknown stat {name}"))?;
+                Command::SetStat(stat, number(2)?.max(0) as u32)
+            }
+            "reveal" => Command::Reveal,
+            "fov" => Command::ToggleFov,
+            "god" => Command::ToggleGodMode,
+            "kill" => Command::KillAll,
+            "time" => Command::AdvanceTime(number(1)?.max(0) as u64),
+            "layer" => Command::ChangeLayer(number(1)?),
+            "help" => Command::Help,
+            "" => return Err("empty command".to_string()),
+            name => return Err(format!("unknown command {name}, try help")),
+        };
+        Ok(command)
+    }
+
+    /// runs the command, the creatures spawning on the cursor; returns the message to log
+    pub fn run(self, simulation: &mut Simulation, cursor: (i32, i32, i32)) -> String {
+        let map = &mut simulation.map;
+        let fov = &mut simulation.fov;
+        let Some(player) = simulation.entity_manager.get_player_mut() else {
+            return "the player is dead".to_string();
+        };
+        match self {
+            Command::Spawn(creature, script) => {
+                let mut entity = Entity::spawn(creature, cursor);
+                if let Some(script) = script {
+                    entity.controller = Controller::AI(Box::new(ScriptedBehavior { script }));
+                }
+                simulation.entity_manager.add_entity(entity);
+                format!(
+                    "{} spawned at ({}, {}, {})",
+                    creature.name, cursor.0, cursor.1, cursor.2
+                )
+            }
+            Command::Teleport(x, y) => {
+                move_player(player, (x, y, player.position.2), map, fov);
+                format!("teleported to ({x}, {y})")
+            }
+            Command::Give(id) => {
+                let item = items().build(&id);
+                let message = format!("{} given", item.name);
+                player.inventory.add(item);
+                message
+            }
+            Command::SetStat(stat, value) => {
+                let stats = &mut player.stats;
+                let field = match stat {
+                    "hp" => &mut stats.hp,
+                    "max_hp" => &mut stats.max_hp,
+                    "mana" => &mut stats.mana,
+                    "max_mana" => &mut stats.max_mana,
+                    "defense" => &mut stats.defense,
+                    "strength" => &mut stats.strength,
+                    _ => &mut stats.magic,
+                };
+                *field = value;
+                format!("{stat} set to {value}")
+            }
+            Command::Reveal => {
+                for chunk in map.chunks.values_mut() {
+                    for layer in chunk.layers.values_mut() {
+                        let positions: Vec<(i32, i32)> = layer.positions().collect();
+                        layer.revealed_tiles.extend(positions);
+                    }
+                }
+                "the loaded chunks are revealed".to_string()
+            }
+            Command::ToggleFov => {
+                fov.see_all = !fov.see_all;
+                fov.update(
+                    player.position,
+                    environment::sight_radius(map, player.position),
+                    map,
+                );
+                format!("field of view {}", if fov.see_all { "off" } else { "on" })
+            }
+            Command::ToggleGodMode => {
+                player.invulnerable = !player.invulnerable;
+                format!(
+                    "god mode {}",
+                    if player.invulnerable { "on" } else { "off" }
+                )
+            }
+            Command::KillAll => {
+                let mut killed = 0;
+                for entity in simulation
+                    .entity_manager
+                    .living_entities_mut()
+                    .filter(|entity| !entity.is_player())
+                {
+                    entity.stats.hp = 0;
+                    killed += 1;
+                }
+                format!("{killed} creatures killed")
+            }
+            Command::AdvanceTime(hours) => {
+                map.clock.advance(hours * SECONDS_PER_HOUR);
+                format!("it is now {} {}", map.clock.date(), map.clock.time())
+            }
+            Command::ChangeLayer(z) => {
+                move_player(player, (player.position.0, player.position.1, z), map, fov);
+                format!("moved to the layer {z}")
+            }
+            Command::Help => COMMANDS
+                .iter()
+                .map(|(name, _)| usage(name))
+                .collect::<Vec<_>>()
+                .join(", "),
+        }
+    }
+}
+
+/// moves the player, loading the chunks around it and what it sees
+fn move_player(
+    player: &mut Entity,
+    position: (i32, i32, i32),
+    map: &mut Map,
+    fov: &mut FieldOfView,
+) {
+    player.position = position;
+    map.load_around((
+        position.0.div_euclid(CHUNK_SIZE as i32),
+        position.1.div_euclid(CHUNK_SIZE as i32),
+        position.2,
+    ));
+    fov.update(position, environment::sight_radius(map, position), map);
+}
+
+/// "spawn <creature>"
+fn usage(name: &str) -> String {
+    match COMMANDS.iter().find(|(command, _)| *command == name) {
+        Some((_, "")) => name.to_string(),
+        Some((_, arguments)) => format!("{name} {arguments}"),
+        None => name.to_string(),
+    }
+}
+
+/// returns the words the last word of the line may be completed to
+pub fn completions(line: &str) -> Vec<String> {
+    let mut words: Vec<&str> = line.split_whitespace().collect();
+    // a trailing space starts a new word
+    if line.is_empty() || line.ends_with(' ') {
+        words.push("");
+    }
+    let prefix = words.last().copied().unwrap_or_default();
+    let candidates: Vec<String> = match (words.len(), words[0]) {
+        (1, _) => COMMANDS.iter().map(|(name, _)| name.to_string()).collect(),
+        (2, "spawn") => creatures()
+            .all()
+            .iter()
+            .map(|creature| creature.id.clone())
+            .collect(),
+        (2, "give") => items().all().iter().map(|item| item.id.clone()).collect(),
+        (2, "set") => STATS.iter().map(|stat| stat.to_string()).collect(),
+        _ => Vec::new(),
+    };
+    candidates
+        .into_iter()
+        .filter(|candidate| candidate.starts_with(prefix))
+        .collect()
+}
@@ -0,0 +1,153 @@
+//! draws the App into a TestBackend and compares the symbols and the foreground colors of the screen to the snapshots of tests/snapshots, `UPDATE_SNAPSHOTS=1 cargo test` rewriting them
+
+use std::{env, fs, path::PathBuf};
+
+use crossterm::event::KeyCode;
+use mireveil::{
+    app::{App, Config},
+    entities::{
+        character::{Class, Race},
+        controller::PlayerInput,
+        entity::{Entity, EntityKind},
+    },
+};
+use ratatui::{
+    Terminal,
+    backend::TestBackend,
+    buffer::Buffer,
+    layout::{Position, Rect},
+    style::Color,
+};
+
+const WIDTH: u16 = 80;
+const HEIGHT: u16 = 24;
+const SEED: u64 = 1;
+
+/// a game started on the seed, after one turn so that the player sees around it
+fn app() -> App {
+    let mut app = App::new(Config::default());
+    app.start_game(SEED, Race::Human, Class::Warrior, "Tester".to_string());
+    app.simulation.step(PlayerInput::Key(KeyCode::Null));
+    app
+}
+
+fn render(app: &mut App) -> Buffer {
+    app.update_camera_position(Rect::new(0, 0, WIDTH, HEIGHT));
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+    terminal.draw(|frame| app.draw(frame)).unwrap();
+    terminal.backend().buffer().clone()
+}
+
+/// letters standing for the foreground colors in the snapshots, in the order they first appear
+const COLOR_LETTERS: &str = "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// the symbols of the buffer one line per row, then their foreground colors as the letters of the legend below them
+fn to_text(buffer: &Buffer) -> String {
+    let mut colors: Vec<Color> = Vec::new();
+    let (mut symbols, mut foregrounds) = (String::new(), String::new());
+    for y in 0..buffer.area.height {
+        for x in 0..buffer.area.width {
+            let cell = &buffer[(x, y)];
+            symbols.push_str(cell.symbol());
+            let index = colors
+                .iter()
+                .position(|&color| color == cell.fg)
+                .unwrap_or_else(|| {
+                    colors.push(cell.fg);
+                    colors.len() - 1
+                });
+            let letter = COLOR_LETTERS
+                .chars()
+                .nth(index)
+                .expect("too many colors for the snapshot");
+            foregrounds.push(letter);
+        }
+        symbols.push('\n');
+        foregrounds.push('\n');
+    }
+    let legend: String = COLOR_LETTERS
+        .chars()
+        .zip(&colors)
+        .map(|(letter, color)| format!("{letter}: {color:?}\n"))
+        .collect();
+    format!("{symbols}\n{foregrounds}\n{legend}")
+}
+
+fn assert_snapshot(name: &str, buffer: &Buffer) {
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "snapshots"]
+        .iter()
+        .collect::<PathBuf>()
+        .join(format!("{name}.txt"));
+    let actual = to_text(buffer);
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "missing snapshot {}, run with UPDATE_SNAPSHOTS=1",
+            path.display()
+        )
+    });
+    assert_eq!(
+        actual, expected,
+        "the screen differs from the snapshot {name}"
+    );
+}
+
+/// returns the screen position of the global coordinates once the camera follows the player
+fn screen_position(app: &App, position: (i32, i32)) -> Position {
+    let camera = app.camera.position;
+    Position::new(
+        (position.0 - camera.0) as u16,
+        (position.1 - camera.1) as u16,
+    )
+}
+
+fn is_gray(color: Color) -> bool {
+    match color {
+        Color::Rgb(r, g, b) => r == g && g == b,
+        Color::Gray => true,
+        _ => false,
+    }
+}
+
+#[test]
+fn map_and_menu() {
+    let mut app = app();
+    let buffer = render(&mut app);
+    assert_snapshot("map_and_menu", &buffer);
+}
+
+#[test]
+fn revealed_tiles_are_grayed_out() {
+    let mut app = app();
+    // the player forgets what it sees, the tiles only staying revealed
+    for chunk in app.simulation.map.chunks.values_mut() {
+        for layer in chunk.layers.values_mut() {
+            layer.visible_tiles.clear();
+        }
+    }
+    let buffer = render(&mut app);
+    assert_snapshot("revealed_tiles", &buffer);
+
+    let player = screen_position(&app, (0, 0));
+    let revealed = Position::new(player.x - 3, player.y);
+    assert_ne!(buffer[revealed].symbol(), " ");
+    assert!(is_gray(buffer[revealed].fg));
+}
+
+#[test]
+fn dead_entity_is_a_cross() {
+    let mut app = app();
+    let mut sheep = Entity::monster(EntityKind::Sheep, (-2, 0, 0));
+    sheep.stats.hp = 0;
+    app.simulation.entity_manager.add_entity(sheep);
+    let buffer = render(&mut app);
+    assert_snapshot("dead_entity", &buffer);
+
+    let corpse = &buffer[screen_position(&app, (-2, 0))];
+    assert_eq!(corpse.symbol(), "†");
+    assert!(is_gray(corpse.fg));
+}